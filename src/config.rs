@@ -1,10 +1,12 @@
-//! Configuration data structures and TOML parsing.
+//! Configuration data structures and TOML/JSON parsing.
 //!
-//! The config file uses TOML format. Example:
+//! The config file uses TOML format, or JSON when its name ends in `.json`.
+//! Example:
 //!
 //! ```toml
 //! [global]
 //! log_level = "info"
+//! y_axis = "down"
 //!
 //! [global.thresholds]
 //! swipe_time_max = 0.9
@@ -23,24 +25,45 @@
 //!
 //! [device.kiosk]
 //! device_usb_id = "1234:5678"
+//! # device_path = "/dev/input/event3"   # bypasses USB-ID lookup
+//! # device_phys = "usb-0000:00:14.0-3/input0"   # pick one of several identical panels
 //! enabled = true
+//! skip_initial_touches = 1
+//! # only_when_class = "firefox"         # needs the x11 feature
+//! # active_hours = "08:00-20:00"        # local time, may wrap past midnight
 //!
 //! [device.kiosk.gestures.swipe_left]
 //! action = "xdotool key Left"
 //! enabled = true
 //!
+//! [device.kiosk.gestures.swipe_right]
+//! key = "KEY_RIGHT"            # injected via uinput, no xdotool needed
+//! enabled = true
+//!
+//! [device.kiosk.gestures.pinch_out]
+//! scroll = "vertical"          # emit REL_WHEEL via uinput
+//! enabled = true
+//!
 //! [device.kiosk.thresholds]
 //! swipe_time_max = 1.5
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use log::{debug, warn};
-use serde::Deserialize;
+use evdev::Key;
+use log::{LevelFilter, debug, warn};
+use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
 
+use crate::logfile::DEFAULT_ROTATE_KEEP;
+use crate::recognizer::GestureType;
+use crate::uinput::parse_key;
+
 /// Top-level error type used throughout the crate.
 #[derive(Debug, Error)]
 pub enum BodgestrError {
@@ -50,11 +73,258 @@ pub enum BodgestrError {
         source: std::io::Error,
     },
 
-    #[error("Failed to parse config file {path}: {message}")]
-    ConfigParseError { path: PathBuf, message: String },
+    #[error("Failed to parse config file {path} as {format}: {message}")]
+    ConfigParseError {
+        path: PathBuf,
+        format: ConfigFormat,
+        message: String,
+    },
 
     #[error("Config validation error for device '{device}': missing threshold(s): {missing}")]
     MissingThresholds { device: String, missing: String },
+
+    #[error("Failed to read stats file {path}: {message}")]
+    StatsReadError { path: PathBuf, message: String },
+
+    #[error("Failed to write stats file {path}: {message}")]
+    StatsWriteError { path: PathBuf, message: String },
+
+    #[error(
+        "Config validation error for device '{device}': unknown key '{key}' for gesture '{gesture}'"
+    )]
+    InvalidKey {
+        device: String,
+        gesture: String,
+        key: String,
+    },
+
+    #[error(
+        "Config validation error for device '{device}': invalid finger count '{fingers}' \
+         in by_fingers for gesture '{gesture}'"
+    )]
+    InvalidFingerCount {
+        device: String,
+        gesture: String,
+        fingers: String,
+    },
+
+    #[error(
+        "Config validation error for device '{device}': invalid tap_count {count} for gesture \
+         '{gesture}' (only tap takes a tap_count, of at least 1)"
+    )]
+    InvalidTapCount {
+        device: String,
+        gesture: String,
+        count: usize,
+    },
+
+    #[error(
+        "Config validation error for device '{device}': invalid active_hours '{value}' \
+         (expected HH:MM-HH:MM)"
+    )]
+    InvalidActiveHours { device: String, value: String },
+
+    #[error(
+        "Config validation error for device '{device}': reference_resolution must be two \
+         positive numbers, got [{width}, {height}]"
+    )]
+    InvalidReferenceResolution {
+        device: String,
+        width: f64,
+        height: f64,
+    },
+
+    #[error(
+        "Config validation error for device '{device}': undefined template '{template}' \
+         in action '{action}'"
+    )]
+    UndefinedTemplate {
+        device: String,
+        template: String,
+        action: String,
+    },
+
+    #[error(
+        "Config validation error for device '{device}': unknown threshold '{name}' \
+         for gesture '{gesture}'"
+    )]
+    UnknownThreshold {
+        device: String,
+        gesture: String,
+        name: String,
+    },
+
+    #[error(
+        "Config validation error for device '{device}': invalid log_level '{level}' \
+         for gesture '{gesture}'"
+    )]
+    InvalidGestureLogLevel {
+        device: String,
+        gesture: String,
+        level: String,
+    },
+
+    #[error(
+        "Config validation error for device '{device}': swipe_distance_min_pct and \
+         swipe_distance_min_px are mutually exclusive"
+    )]
+    ConflictingSwipeDistance { device: String },
+
+    #[error("Config validation error for device '{device}': unknown profile '{profile}'")]
+    UnknownProfile { device: String, profile: String },
+
+    #[error("Config validation error: unknown gesture '{name}' in disabled_gestures")]
+    UnknownDisabledGesture { name: String },
+
+    #[error("Config validation error: invalid log level '{level}' for log target '{target}'")]
+    InvalidLogLevel { target: String, level: String },
+
+    #[error("uinput error: {message}")]
+    UinputError { message: String },
+
+    #[error("Failed to execute action '{action}': {source}")]
+    ActionSpawnFailed {
+        action: String,
+        source: std::io::Error,
+    },
+}
+
+/// Syntax of a config file, chosen by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// JSON for a `.json` file (any case), TOML for everything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Json => "JSON",
+        })
+    }
+}
+
+/// Direction in which the device's Y coordinate grows on the visible screen.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum YAxis {
+    /// Y grows toward the bottom of the screen (evdev convention).
+    #[default]
+    Down,
+    /// Y grows toward the top of the screen.
+    Up,
+}
+
+/// Which contact single-finger gestures follow while other fingers are down.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrimaryFinger {
+    /// The finger that touched down first.
+    #[default]
+    First,
+    /// The contact with the lowest tracking ID.
+    LowestId,
+    /// The contact in the lowest multitouch slot.
+    LowestSlot,
+}
+
+/// Units the device reports touch positions in.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateSpace {
+    /// Device units, spanning the axis ranges the device advertises.
+    #[default]
+    Device,
+    /// Fractions 0-1 of each axis, as delivered by normalizing layers such
+    /// as libinput for touchpads; the device's axis ranges are not queried.
+    Normalized,
+}
+
+impl CoordinateSpace {
+    /// Axis ranges implied by the space itself, or `None` when they must be
+    /// read from the device.
+    pub fn fixed_ranges(self) -> Option<((f64, f64), (f64, f64))> {
+        match self {
+            CoordinateSpace::Device => None,
+            CoordinateSpace::Normalized => Some(((0.0, 1.0), (0.0, 1.0))),
+        }
+    }
+}
+
+/// Wheel axis a gesture scrolls through the uinput virtual device.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollAxis {
+    /// `REL_WHEEL`.
+    Vertical,
+    /// `REL_HWHEEL`.
+    Horizontal,
+}
+
+/// Daily local-time window in which gestures are dispatched, parsed from
+/// `"HH:MM-HH:MM"`.  A start after the end wraps past midnight
+/// (`"22:00-06:00"`); equal start and end cover the whole day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveHours {
+    /// Minutes after midnight at which the window opens.
+    pub start: u16,
+    /// Minutes after midnight at which the window closes (exclusive).
+    pub end: u16,
+}
+
+impl ActiveHours {
+    /// Parse `"HH:MM-HH:MM"`; returns `None` for malformed or out-of-range times.
+    pub fn parse(raw: &str) -> Option<Self> {
+        fn minutes(hhmm: &str) -> Option<u16> {
+            let (h, m) = hhmm.trim().split_once(':')?;
+            let (h, m): (u16, u16) = (h.parse().ok()?, m.parse().ok()?);
+            (h < 24 && m < 60).then_some(h * 60 + m)
+        }
+        let (start, end) = raw.split_once('-')?;
+        Some(Self {
+            start: minutes(start)?,
+            end: minutes(end)?,
+        })
+    }
+
+    /// Whether `minute_of_day` (0..1440, local time) falls inside the window.
+    pub fn contains(&self, minute_of_day: u16) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+impl fmt::Display for ActiveHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start / 60,
+            self.start % 60,
+            self.end / 60,
+            self.end % 60
+        )
+    }
+}
+
+impl Serialize for ActiveHours {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
 }
 
 /// Root of the TOML config file.
@@ -64,6 +334,15 @@ struct RawConfig {
     global: RawGlobal,
     #[serde(default)]
     device: HashMap<String, RawDevice>,
+    #[serde(default)]
+    profiles: HashMap<String, RawProfile>,
+}
+
+/// A `[profiles.<name>]` section shared by the devices naming it in `profile`.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct RawProfile {
+    thresholds: RawThresholds,
 }
 
 /// The `[global]` section.
@@ -72,12 +351,68 @@ struct RawConfig {
 struct RawGlobal {
     log_level: Option<String>,
     log_file: Option<String>,
+    log_max_bytes: Option<u64>,
+    log_rotate_keep: Option<usize>,
+    log_targets: HashMap<String, String>,
+    stats_file: Option<String>,
+    serialize_actions: Option<bool>,
+    action_queue_capacity: Option<usize>,
+    watchdog_timeout_secs: Option<f64>,
+    selftest: Option<bool>,
+    instant_tap: Option<bool>,
+    pass_trajectory: Option<bool>,
+    auto_enable_all: Option<bool>,
+    feedback_command: Option<String>,
+    disabled_gestures: Vec<String>,
+    y_axis: Option<YAxis>,
+    primary_finger: Option<PrimaryFinger>,
+    action_retries: Option<u32>,
+    wake_on_idle_secs: Option<f64>,
+    wake_action: Option<String>,
+    long_press_drag: Option<bool>,
+    corner_gestures: Option<bool>,
+    swipe_then_tap_ms: Option<u64>,
+    hold_before_swipe_ms: Option<u64>,
+    normalize_coordinates: Option<bool>,
+    require_both_axes: Option<bool>,
+    suppress_repeat: Option<bool>,
+    swipe_once_per_contact: Option<bool>,
+    max_track_points: Option<usize>,
+    max_fingers: Option<usize>,
+    id_merge_ms: Option<u64>,
+    stale_touch_timeout_ms: Option<u64>,
+    release_timeout_ms: Option<u64>,
+    max_events_per_sec: Option<u32>,
+    post_action_delay_ms: Option<u64>,
+    reference_resolution: Option<[f64; 2]>,
+    only_when_class: Option<String>,
+    active_hours: Option<String>,
+    mqtt: Option<MqttConfig>,
+    fifo_path: Option<PathBuf>,
+    control_socket: Option<PathBuf>,
+    http_addr: Option<SocketAddr>,
+    /// Named snippets expanded as `{{name}}` in action strings.
+    templates: HashMap<String, String>,
     #[serde(default)]
     thresholds: RawThresholds,
     #[serde(default)]
     gestures: HashMap<String, RawGestureConfig>,
 }
 
+/// The `[global.mqtt]` section: where recognized gestures are published.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct MqttConfig {
+    /// Broker host name or address.
+    pub broker: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    pub topic: String,
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
 /// Threshold values - all optional so device sections can partially override.
 #[derive(Debug, Deserialize, Default, Clone)]
 #[serde(default)]
@@ -91,13 +426,88 @@ struct RawThresholds {
     tap_distance_max: Option<f64>,
     double_tap_distance_max: Option<f64>,
     pinch_threshold_pct: Option<f64>,
+    swipe_commit_distance_pct: Option<f64>,
+    swipe_reversal_pct: Option<f64>,
+    swipe_velocity_min: Option<f64>,
+    pinch_threshold_min_px: Option<f64>,
+    swipe_distance_min_px: Option<f64>,
+    pinch_repeat_interval: Option<f64>,
+    circle_turn_min: Option<f64>,
+    circle_radius_tolerance_pct: Option<f64>,
+    swipe_reject_band_deg: Option<f64>,
+    long_press_drift_max: Option<f64>,
+    long_swipe_distance_pct: Option<f64>,
+    /// Overrides used while the touch surface is taller than it is wide.
+    portrait: Option<Box<RawThresholds>>,
+    /// Overrides used while the touch surface is wider than it is tall.
+    landscape: Option<Box<RawThresholds>>,
+}
+
+impl RawThresholds {
+    /// Fill unset thresholds from `fallback`.
+    ///
+    /// The minimum swipe distance is one setting in two units: a table that
+    /// sets either `swipe_distance_min_pct` or `swipe_distance_min_px` keeps
+    /// its own and inherits neither.
+    fn merge_with_fallback(&self, fallback: &RawThresholds) -> RawThresholds {
+        let mut merged = self.merge_fields(fallback);
+        if self.swipe_distance_min_pct.is_some() || self.swipe_distance_min_px.is_some() {
+            merged.swipe_distance_min_pct = self.swipe_distance_min_pct;
+            merged.swipe_distance_min_px = self.swipe_distance_min_px;
+        }
+        merged
+    }
+
+    /// Validate that all required thresholds are set; a pixel minimum swipe
+    /// distance stands in for the percentage.
+    fn into_validated(mut self) -> Result<ValidatedThresholds, Vec<&'static str>> {
+        if self.swipe_distance_min_px.is_some() {
+            self.swipe_distance_min_pct.get_or_insert(0.0);
+        }
+        self.validate_fields()
+    }
+
+    /// Whether this table, or one of its orientation tables, sets the
+    /// minimum swipe distance in both units.
+    fn sets_both_swipe_distances(&self) -> bool {
+        (self.swipe_distance_min_pct.is_some() && self.swipe_distance_min_px.is_some())
+            || [&self.portrait, &self.landscape]
+                .into_iter()
+                .flatten()
+                .any(|oriented| oriented.sets_both_swipe_distances())
+    }
+
+    /// Layer these thresholds over a profile's, orientation tables included.
+    fn over_profile(&self, profile: &RawThresholds) -> RawThresholds {
+        let oriented =
+            |own: &Option<Box<RawThresholds>>, shared: &Option<Box<RawThresholds>>| match (
+                own, shared,
+            ) {
+                (Some(own), Some(shared)) => Some(Box::new(own.merge_with_fallback(shared))),
+                (own, shared) => own.clone().or_else(|| shared.clone()),
+            };
+        RawThresholds {
+            portrait: oriented(&self.portrait, &profile.portrait),
+            landscape: oriented(&self.landscape, &profile.landscape),
+            ..self.merge_with_fallback(profile)
+        }
+    }
 }
 
-/// A gesture entry (action + enabled).
+/// A gesture entry (actions, key, scroll + enabled).
 #[derive(Debug, Deserialize, Clone, Default)]
 #[serde(default)]
 struct RawGestureConfig {
     action: Option<String>,
+    key: Option<String>,
+    scroll: Option<ScrollAxis>,
+    active_hours: Option<String>,
+    by_fingers: HashMap<String, String>,
+    tap_count: Option<usize>,
+    post_action_delay_ms: Option<u64>,
+    thresholds: HashMap<String, f64>,
+    log: Option<bool>,
+    log_level: Option<String>,
     enabled: Option<bool>,
 }
 
@@ -106,7 +516,36 @@ struct RawGestureConfig {
 #[serde(default)]
 struct RawDevice {
     device_usb_id: Option<String>,
+    device_path: Option<PathBuf>,
+    device_phys: Option<String>,
     enabled: Option<bool>,
+    profile: Option<String>,
+    single_touch: Option<bool>,
+    coordinate_space: Option<CoordinateSpace>,
+    skip_initial_touches: Option<u32>,
+    y_axis: Option<YAxis>,
+    primary_finger: Option<PrimaryFinger>,
+    action_retries: Option<u32>,
+    wake_on_idle_secs: Option<f64>,
+    wake_action: Option<String>,
+    long_press_drag: Option<bool>,
+    corner_gestures: Option<bool>,
+    swipe_then_tap_ms: Option<u64>,
+    hold_before_swipe_ms: Option<u64>,
+    normalize_coordinates: Option<bool>,
+    require_both_axes: Option<bool>,
+    suppress_repeat: Option<bool>,
+    swipe_once_per_contact: Option<bool>,
+    max_track_points: Option<usize>,
+    max_fingers: Option<usize>,
+    id_merge_ms: Option<u64>,
+    stale_touch_timeout_ms: Option<u64>,
+    release_timeout_ms: Option<u64>,
+    max_events_per_sec: Option<u32>,
+    post_action_delay_ms: Option<u64>,
+    reference_resolution: Option<[f64; 2]>,
+    only_when_class: Option<String>,
+    active_hours: Option<String>,
     #[serde(default)]
     thresholds: RawThresholds,
     #[serde(default)]
@@ -116,7 +555,7 @@ struct RawDevice {
 /// Fully validated thresholds - all values guaranteed to be present.
 ///
 /// Created via threshold merging during config parsing.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ValidatedThresholds {
     pub swipe_time_max: f64,
     pub swipe_distance_min_pct: f64,
@@ -127,42 +566,407 @@ pub struct ValidatedThresholds {
     pub tap_distance_max: f64,
     pub double_tap_distance_max: f64,
     pub pinch_threshold_pct: f64,
+    /// Distance a swipe must reach to fire; shorter swipes past
+    /// `swipe_distance_min_pct` report `swipe_begin` then `swipe_cancel`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swipe_commit_distance_pct: Option<f64>,
+    /// Cancel a swipe whose finger travels back at least this fraction of
+    /// its outbound distance before release.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swipe_reversal_pct: Option<f64>,
+    /// Slowest a swipe may travel, in screen fractions per second along its
+    /// axis; slower motions are not swipes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swipe_velocity_min: Option<f64>,
+    /// Absolute floor, in device units, for the pinch distance change, so
+    /// jitter between close-together fingers is not a pinch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinch_threshold_min_px: Option<f64>,
+    /// Distance a swipe must cover in device units, in place of
+    /// `swipe_distance_min_pct`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swipe_distance_min_px: Option<f64>,
+    /// Seconds between `pinch_out_repeat` reports while spread fingers are
+    /// held still; repeats are off when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinch_repeat_interval: Option<f64>,
+    /// Fraction of a full turn a single-finger loop must sweep around its
+    /// centroid to be a circle; circles are off when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circle_turn_min: Option<f64>,
+    /// Largest standard deviation of a circle's radius, as a fraction of its
+    /// mean radius (default 0.25).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circle_radius_tolerance_pct: Option<f64>,
+    /// Reject swipes whose angle is within this many degrees of a diagonal,
+    /// leaving a dead zone between the direction sectors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swipe_reject_band_deg: Option<f64>,
+    /// Farthest, in device units, a long-pressed finger may stray from
+    /// where it landed over the whole press, in place of comparing only its
+    /// final position with `tap_distance_max`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_press_drift_max: Option<f64>,
+    /// Distance, as a fraction of the span, from which a swipe is reported
+    /// as `long_swipe_*`; long swipes are off when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_swipe_distance_pct: Option<f64>,
 }
 
-/// Gesture configuration (action + enabled).
-#[derive(Debug, Clone)]
+/// Gesture configuration (actions, key, scroll + enabled).
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct GestureConfig {
     pub action: Option<String>,
+    /// Key to press and release through the uinput virtual device.
+    #[serde(serialize_with = "serialize_key")]
+    pub key: Option<Key>,
+    /// Wheel axis to scroll one notch along through the uinput virtual device.
+    pub scroll: Option<ScrollAxis>,
+    /// Local-time window outside which this gesture is suppressed.
+    pub active_hours: Option<ActiveHours>,
+    /// Actions keyed by finger count, preferred over `action` when the
+    /// recognized touch used that many fingers.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_fingers: BTreeMap<usize, String>,
+    /// Consecutive taps a `tap` binding fires on (1 when unset).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tap_count: Option<usize>,
+    /// Overrides the device's `post_action_delay_ms` for this gesture.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_action_delay_ms: Option<u64>,
+    /// Thresholds replacing the device's when deciding on this gesture.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub thresholds: BTreeMap<String, f64>,
+    /// Level this gesture's dispatch lines are logged at instead of info;
+    /// `Off` with `log = false`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_level"
+    )]
+    pub log_level: Option<LevelFilter>,
     pub enabled: bool,
 }
 
 /// Configuration for a single touch device.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DeviceConfig {
+    /// USB `vendor:product` ID; empty when the device is located by path only.
     pub device_usb_id: String,
+    /// Event node to open directly, bypassing USB-ID enumeration.
+    pub device_path: Option<PathBuf>,
+    /// Physical path (`phys`, e.g. `usb-0000:00:14.0-3/input0`) the device
+    /// must report, telling identical panels apart by the port they use.
+    pub device_phys: Option<String>,
+    /// Accept a panel reporting only legacy `ABS_X`/`ABS_Y` (no multi-touch
+    /// axes), with `BTN_TOUCH` as finger down/up.
+    pub single_touch: bool,
+    /// Whether positions arrive in device units or already normalized to 0-1.
+    pub coordinate_space: CoordinateSpace,
+    /// Number of complete gestures to discard after the device is opened.
+    pub skip_initial_touches: u32,
+    pub y_axis: YAxis,
+    /// Contact followed for single-finger gestures when several are down.
+    pub primary_finger: PrimaryFinger,
+    /// Extra attempts when spawning an action fails.
+    pub action_retries: u32,
+    /// Idle period after which the next gesture only wakes the screen.
+    #[serde(rename = "wake_on_idle_secs", serialize_with = "serialize_secs")]
+    pub wake_on_idle: Option<Duration>,
+    /// Command run when a gesture is consumed as a wake event.
+    pub wake_action: Option<String>,
+    /// Report long-press-then-move as `long_press_drag_start`/`_end`.
+    pub long_press_drag: bool,
+    /// Report L-shaped strokes as `corner_*` gestures.
+    pub corner_gestures: bool,
+    /// Milliseconds after a swipe within which a tap near its end is
+    /// reported as `swipe_*_then_tap`.
+    pub swipe_then_tap_ms: Option<u64>,
+    /// Milliseconds a finger must rest before swiping for the swipe to be
+    /// reported as `hold_swipe_*`.
+    pub hold_before_swipe_ms: Option<u64>,
+    /// Classify swipes in a square 0-1 space, ignoring the aspect ratio.
+    pub normalize_coordinates: bool,
+    /// Wait for both axes before committing a new contact's first point.
+    pub require_both_axes: bool,
+    /// Report a gesture identical to the previous one only after all
+    /// fingers lifted in between.
+    pub suppress_repeat: bool,
+    /// Report at most one swipe until every finger has lifted.
+    pub swipe_once_per_contact: bool,
+    /// Cap on touch points kept per contact; older intermediate points are
+    /// dropped beyond it.
+    pub max_track_points: Option<usize>,
+    /// More simultaneous contacts than this cancel the touch as a palm.
+    pub max_fingers: Option<usize>,
+    /// Milliseconds within which a tracking ID replacing another in the
+    /// same slot, close to it, still counts as the same finger.
+    pub id_merge_ms: Option<u64>,
+    /// Milliseconds after which a contact that neither moved nor lifted is
+    /// dropped, recovering from a lost release.
+    pub stale_touch_timeout_ms: Option<u64>,
+    /// Milliseconds of silence that end a contact on panels reporting
+    /// neither tracking IDs nor `BTN_TOUCH`.
+    pub release_timeout_ms: Option<u64>,
+    /// Input events processed per second at most; the excess is dropped.
+    pub max_events_per_sec: Option<u32>,
+    /// Milliseconds the device's next action waits after one is started.
+    pub post_action_delay_ms: Option<u64>,
+    /// Axis spans (width, height) the pixel thresholds were written for;
+    /// they are scaled to the device's actual spans when set.
+    pub reference_resolution: Option<[f64; 2]>,
+    /// Only dispatch gestures while an X11 window of this class is focused.
+    pub only_when_class: Option<String>,
+    /// Local-time window outside which all gestures are suppressed.
+    pub active_hours: Option<ActiveHours>,
+    #[serde(serialize_with = "serialize_sorted")]
     pub gestures: HashMap<String, GestureConfig>,
     pub thresholds: ValidatedThresholds,
+    /// Thresholds used instead of `thresholds` on a portrait touch surface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub portrait_thresholds: Option<ValidatedThresholds>,
+    /// Thresholds used instead of `thresholds` on a landscape touch surface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub landscape_thresholds: Option<ValidatedThresholds>,
+}
+
+/// Shape of a touch surface, from its axis ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl Orientation {
+    /// Portrait if the Y axis spans more than the X axis, landscape if the
+    /// reverse, `None` for a square surface.
+    pub fn from_ranges(x_range: (f64, f64), y_range: (f64, f64)) -> Option<Self> {
+        let width = (x_range.1 - x_range.0).abs();
+        let height = (y_range.1 - y_range.0).abs();
+        if height > width {
+            Some(Orientation::Portrait)
+        } else if width > height {
+            Some(Orientation::Landscape)
+        } else {
+            None
+        }
+    }
+}
+
+/// How a configured device is located at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceLocator<'a> {
+    /// Open this event node directly.
+    Path(&'a Path),
+    /// Enumerate touch devices and match on `phys`, and on `vendor:product`
+    /// too when a USB ID is configured.
+    Phys(&'a str),
+    /// Enumerate touch devices and match on `vendor:product`.
+    UsbId(&'a str),
+}
+
+impl fmt::Display for DeviceLocator<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceLocator::Path(path) => write!(f, "path {}", path.display()),
+            DeviceLocator::Phys(phys) => write!(f, "phys {phys}"),
+            DeviceLocator::UsbId(usb_id) => write!(f, "USB ID {usb_id}"),
+        }
+    }
 }
 
+impl DeviceConfig {
+    /// How to find this device - `device_path` takes precedence over
+    /// `device_phys`, which takes precedence over the USB ID.
+    pub fn locator(&self) -> DeviceLocator<'_> {
+        match (&self.device_path, &self.device_phys) {
+            (Some(path), _) => DeviceLocator::Path(path),
+            (None, Some(phys)) => DeviceLocator::Phys(phys),
+            (None, None) => DeviceLocator::UsbId(&self.device_usb_id),
+        }
+    }
+
+    /// Thresholds for a surface with these axis ranges - the portrait or
+    /// landscape set when configured, the plain `thresholds` otherwise.
+    pub fn thresholds_for(&self, x_range: (f64, f64), y_range: (f64, f64)) -> &ValidatedThresholds {
+        let oriented = match Orientation::from_ranges(x_range, y_range) {
+            Some(Orientation::Portrait) => self.portrait_thresholds.as_ref(),
+            Some(Orientation::Landscape) => self.landscape_thresholds.as_ref(),
+            None => None,
+        };
+        oriented.unwrap_or(&self.thresholds)
+    }
+
+    /// How long the device's next action waits after one for `gesture` is
+    /// started - the gesture's `post_action_delay_ms`, else the device's.
+    pub fn post_action_delay(&self, gesture: GestureType) -> Duration {
+        let name: &str = gesture.into();
+        let ms = self
+            .gestures
+            .get(name)
+            .and_then(|gc| gc.post_action_delay_ms)
+            .or(self.post_action_delay_ms);
+        Duration::from_millis(ms.unwrap_or(0))
+    }
+
+    /// `base` with each gesture's `thresholds` applied, for the gestures
+    /// that set any.
+    pub fn gesture_thresholds(
+        &self,
+        base: &ValidatedThresholds,
+    ) -> Vec<(GestureType, ValidatedThresholds)> {
+        self.gestures
+            .iter()
+            .filter(|(_, gc)| !gc.thresholds.is_empty())
+            .filter_map(|(name, gc)| {
+                let gesture = name.parse().ok()?;
+                let mut thresholds = base.clone();
+                for (threshold, &value) in &gc.thresholds {
+                    thresholds.set(threshold, value);
+                }
+                Some((gesture, thresholds))
+            })
+            .collect()
+    }
+
+    /// Whether any action of this device is followed by a delay.
+    pub fn paces_actions(&self) -> bool {
+        self.post_action_delay_ms.is_some_and(|ms| ms > 0)
+            || self
+                .gestures
+                .values()
+                .any(|gc| gc.post_action_delay_ms.is_some_and(|ms| ms > 0))
+    }
+}
+
+/// Id of the `[device."*"]` section applying to every unlisted touch device.
+pub const WILDCARD_DEVICE: &str = "*";
+
 /// Top-level parsed configuration.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AppConfig {
     pub log_level: String,
     pub log_file: Option<String>,
+    /// Size at which `log_file` is rotated; never when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_max_bytes: Option<u64>,
+    /// Rotated log files kept next to `log_file`.
+    pub log_rotate_keep: usize,
+    /// Log levels overriding `log_level` for particular modules, keyed by
+    /// target suffix such as `recognizer`.
+    #[serde(
+        skip_serializing_if = "BTreeMap::is_empty",
+        serialize_with = "serialize_levels"
+    )]
+    pub log_targets: BTreeMap<String, LevelFilter>,
+    /// Where per-gesture counts are persisted, if set.
+    pub stats_file: Option<String>,
+    /// Spawn actions one at a time across all devices, under a shared lock.
+    pub serialize_actions: bool,
+    /// Queue actions for a worker thread instead of spawning them directly;
+    /// actions beyond this many pending are dropped.
+    pub action_queue_capacity: Option<usize>,
+    /// Restart a device thread stuck this long in a read while its device
+    /// is still present.
+    #[serde(rename = "watchdog_timeout_secs", serialize_with = "serialize_secs")]
+    pub watchdog_timeout: Option<Duration>,
+    /// Check at startup that each device's thresholds can recognize a
+    /// synthetic swipe.
+    pub selftest: bool,
+    /// Report taps on release, never waiting for (or reporting) a double tap.
+    pub instant_tap: bool,
+    /// Write each gesture's touch points as JSON to its action's stdin.
+    pub pass_trajectory: bool,
+    /// Shell command run on every recognized gesture, bound or not, with
+    /// its name in `BODGESTR_GESTURE` - e.g. a short beep.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_command: Option<String>,
+    /// Gestures never recognized on any device, whatever their bindings.
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_gestures"
+    )]
+    pub disabled_gestures: Vec<GestureType>,
+    /// Publish recognized gestures to this MQTT broker.
+    pub mqtt: Option<MqttConfig>,
+    /// Named pipe each dispatched gesture is written to as a line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fifo_path: Option<PathBuf>,
+    /// Unix socket accepting `bind` commands that remap gestures at runtime.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_socket: Option<PathBuf>,
+    /// Address serving `/gestures` (server-sent events) and `/stats`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_addr: Option<SocketAddr>,
+    /// Global thresholds, if the `[global.thresholds]` section is complete.
+    pub global_thresholds: Option<ValidatedThresholds>,
+    pub y_axis: YAxis,
+    #[serde(rename = "device", serialize_with = "serialize_sorted")]
     pub devices: HashMap<String, DeviceConfig>,
+    /// Settings for every touch device not matched by `devices`, from
+    /// `[device."*"]` or `[global] auto_enable_all`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_device: Option<DeviceConfig>,
+}
+
+/// Serialize a key by its evdev name, e.g. `"KEY_LEFT"`.
+fn serialize_key<S: Serializer>(key: &Option<Key>, s: S) -> Result<S::Ok, S::Error> {
+    key.map(|k| format!("{k:?}")).serialize(s)
+}
+
+/// Serialize a duration as fractional seconds, matching the `*_secs` options.
+fn serialize_secs<S: Serializer>(d: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+    d.map(|d| d.as_secs_f64()).serialize(s)
+}
+
+/// Serialize gestures by config name, e.g. `"pinch_in"`.
+fn serialize_gestures<S: Serializer>(gestures: &[GestureType], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(gestures.iter().map(|gesture| gesture.to_string()))
+}
+
+/// Serialize log levels by name, e.g. `"DEBUG"`.
+fn serialize_levels<S: Serializer>(
+    levels: &BTreeMap<String, LevelFilter>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.collect_map(
+        levels
+            .iter()
+            .map(|(target, level)| (target, level.as_str())),
+    )
+}
+
+/// Serialize an optional log level by name, e.g. `"DEBUG"`.
+fn serialize_level<S: Serializer>(level: &Option<LevelFilter>, s: S) -> Result<S::Ok, S::Error> {
+    match level {
+        Some(level) => s.serialize_str(level.as_str()),
+        None => s.serialize_none(),
+    }
 }
 
-/// Generate merge, validate, and into_validated for threshold fields.
+/// Serialize a map with sorted keys so dumps are stable.
+fn serialize_sorted<S: Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(s)
+}
+
+/// Generate merge, validate, and into_validated for threshold fields, plus
+/// name-based access for overrides.
+///
+/// Optional fields are merged the same way but never reported as missing.
 macro_rules! threshold_fields {
-    ($($field:ident),+ $(,)?) => {
+    (required: $($field:ident),+ ; optional: $($opt:ident),* $(,)?) => {
         impl RawThresholds {
-            fn merge_with_fallback(&self, fallback: &RawThresholds) -> RawThresholds {
+            fn merge_fields(&self, fallback: &RawThresholds) -> RawThresholds {
                 RawThresholds {
                     $($field: self.$field.or(fallback.$field),)+
+                    $($opt: self.$opt.or(fallback.$opt),)*
+                    ..RawThresholds::default()
                 }
             }
 
-            fn into_validated(self) -> Result<ValidatedThresholds, Vec<&'static str>> {
+            fn validate_fields(self) -> Result<ValidatedThresholds, Vec<&'static str>> {
                 let missing: Vec<&str> = [$(
                     if self.$field.is_none() { Some(stringify!($field)) } else { None },
                 )+].into_iter().flatten().collect();
@@ -173,13 +977,31 @@ macro_rules! threshold_fields {
 
                 Ok(ValidatedThresholds {
                     $($field: self.$field.unwrap(),)+
+                    $($opt: self.$opt,)*
                 })
             }
         }
+
+        /// Names of all threshold fields, as written in the config file.
+        pub const THRESHOLD_NAMES: &[&str] = &[$(stringify!($field),)+ $(stringify!($opt),)*];
+
+        impl ValidatedThresholds {
+            /// Set the threshold called `name` to `value`.  Returns `false`
+            /// if there is no such threshold.
+            pub fn set(&mut self, name: &str, value: f64) -> bool {
+                match name {
+                    $(stringify!($field) => self.$field = value,)+
+                    $(stringify!($opt) => self.$opt = Some(value),)*
+                    _ => return false,
+                }
+                true
+            }
+        }
     };
 }
 
 threshold_fields!(
+    required:
     swipe_time_max,
     swipe_distance_min_pct,
     angle_tolerance_deg,
@@ -188,46 +1010,286 @@ threshold_fields!(
     double_tap_interval,
     tap_distance_max,
     double_tap_distance_max,
-    pinch_threshold_pct,
+    pinch_threshold_pct;
+    optional:
+    swipe_commit_distance_pct,
+    swipe_reversal_pct,
+    swipe_velocity_min,
+    pinch_threshold_min_px,
+    swipe_distance_min_px,
+    pinch_repeat_interval,
+    circle_turn_min,
+    circle_radius_tolerance_pct,
+    swipe_reject_band_deg,
+    long_press_drift_max,
+    long_swipe_distance_pct,
 );
 
 /// Merge gesture maps: global first, then device-specific overrides.
+///
+/// Gesture names are normalized to lowercase so `Swipe_Left` binds `swipe_left`.
 fn merge_gestures(
+    device_id: &str,
     global: &HashMap<String, RawGestureConfig>,
     device: &HashMap<String, RawGestureConfig>,
-) -> HashMap<String, GestureConfig> {
+    templates: &HashMap<String, String>,
+) -> Result<HashMap<String, GestureConfig>, BodgestrError> {
     let mut merged = HashMap::new();
 
     // Insert all global + device gesture names, device values override.
     for (name, gc) in global.iter().chain(device.iter()) {
-        let entry = merged.entry(name.clone()).or_insert(GestureConfig {
-            action: None,
-            enabled: false,
-        });
-        if gc.action.is_some() {
-            entry.action.clone_from(&gc.action);
+        let name = name.to_lowercase();
+        let entry = merged
+            .entry(name.clone())
+            .or_insert_with(GestureConfig::default);
+        if let Some(action) = &gc.action {
+            entry.action = Some(expand_templates(device_id, action, templates)?);
+        }
+        if let Some(key_name) = &gc.key {
+            entry.key = Some(
+                parse_key(key_name).ok_or_else(|| BodgestrError::InvalidKey {
+                    device: device_id.to_string(),
+                    gesture: name.clone(),
+                    key: key_name.clone(),
+                })?,
+            );
+        }
+        if gc.scroll.is_some() {
+            entry.scroll = gc.scroll;
+        }
+        for (fingers, action) in &gc.by_fingers {
+            let count = fingers
+                .trim()
+                .parse()
+                .ok()
+                .filter(|&n: &usize| n > 0)
+                .ok_or_else(|| BodgestrError::InvalidFingerCount {
+                    device: device_id.to_string(),
+                    gesture: name.clone(),
+                    fingers: fingers.clone(),
+                })?;
+            entry
+                .by_fingers
+                .insert(count, expand_templates(device_id, action, templates)?);
+        }
+        if let Some(raw_hours) = &gc.active_hours {
+            entry.active_hours = Some(parse_active_hours(device_id, raw_hours)?);
+        }
+        if let Some(count) = gc.tap_count {
+            let tap: &str = GestureType::Tap.into();
+            if count == 0 || name != tap {
+                return Err(BodgestrError::InvalidTapCount {
+                    device: device_id.to_string(),
+                    gesture: name,
+                    count,
+                });
+            }
+            entry.tap_count = Some(count);
+        }
+        if gc.post_action_delay_ms.is_some() {
+            entry.post_action_delay_ms = gc.post_action_delay_ms;
+        }
+        for (threshold, &value) in &gc.thresholds {
+            if !THRESHOLD_NAMES.contains(&threshold.as_str()) {
+                return Err(BodgestrError::UnknownThreshold {
+                    device: device_id.to_string(),
+                    gesture: name,
+                    name: threshold.clone(),
+                });
+            }
+            entry.thresholds.insert(threshold.clone(), value);
+        }
+        if let Some(level) = &gc.log_level {
+            entry.log_level =
+                Some(
+                    level
+                        .parse()
+                        .map_err(|_| BodgestrError::InvalidGestureLogLevel {
+                            device: device_id.to_string(),
+                            gesture: name.clone(),
+                            level: level.clone(),
+                        })?,
+                );
+        } else if gc.log == Some(true) {
+            entry.log_level = None;
+        }
+        if gc.log == Some(false) {
+            entry.log_level = Some(LevelFilter::Off);
         }
         if let Some(enabled) = gc.enabled {
             entry.enabled = enabled;
         }
     }
 
-    merged
+    Ok(merged)
+}
+
+/// Replace each `{{name}}` in `action` with its `[global.templates]` snippet.
+///
+/// Snippets are inserted verbatim, not expanded again.  An unterminated
+/// `{{` is kept as literal text.
+fn expand_templates(
+    device_id: &str,
+    action: &str,
+    templates: &HashMap<String, String>,
+) -> Result<String, BodgestrError> {
+    let mut expanded = String::with_capacity(action.len());
+    let mut rest = action;
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[open + 2..open + 2 + close].trim();
+        let snippet = templates
+            .get(name)
+            .ok_or_else(|| BodgestrError::UndefinedTemplate {
+                device: device_id.to_string(),
+                template: name.to_string(),
+                action: action.to_string(),
+            })?;
+        expanded.push_str(&rest[..open]);
+        expanded.push_str(snippet);
+        rest = &rest[open + 2 + close + 2..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
 }
 
-/// Parse a TOML config file and return the fully resolved `AppConfig`.
+/// Level for log records from `target`, e.g. `bodgestr::recognizer`.
+///
+/// A `log_targets` key matches a target equal to it or ending in `::key`;
+/// the longest matching key wins.  Other targets use `default`.
+pub fn target_level(
+    targets: &BTreeMap<String, LevelFilter>,
+    default: LevelFilter,
+    target: &str,
+) -> LevelFilter {
+    targets
+        .iter()
+        .filter(|(key, _)| {
+            target == key.as_str()
+                || target
+                    .strip_suffix(key.as_str())
+                    .is_some_and(|prefix| prefix.ends_with("::"))
+        })
+        .max_by_key(|(key, _)| key.len())
+        .map_or(default, |(_, &level)| level)
+}
+
+/// Parse `[global] disabled_gestures` names.
+fn parse_disabled_gestures(names: &[String]) -> Result<Vec<GestureType>, BodgestrError> {
+    names
+        .iter()
+        .map(|name| {
+            name.parse()
+                .map_err(|_| BodgestrError::UnknownDisabledGesture { name: name.clone() })
+        })
+        .collect()
+}
+
+/// Parse `[global.log_targets]` level names.
+fn parse_log_targets(
+    raw: &HashMap<String, String>,
+) -> Result<BTreeMap<String, LevelFilter>, BodgestrError> {
+    raw.iter()
+        .map(|(target, level)| {
+            level
+                .parse()
+                .map(|level| (target.clone(), level))
+                .map_err(|_| BodgestrError::InvalidLogLevel {
+                    target: target.clone(),
+                    level: level.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Parse an `active_hours` value, reporting it against `device_id` on error.
+fn parse_active_hours(device_id: &str, raw: &str) -> Result<ActiveHours, BodgestrError> {
+    ActiveHours::parse(raw).ok_or_else(|| BodgestrError::InvalidActiveHours {
+        device: device_id.to_string(),
+        value: raw.to_string(),
+    })
+}
+
+/// Check that both `reference_resolution` dimensions are positive.
+fn validate_reference_resolution(
+    device_id: &str,
+    [width, height]: [f64; 2],
+) -> Result<[f64; 2], BodgestrError> {
+    if width > 0.0 && height > 0.0 {
+        Ok([width, height])
+    } else {
+        Err(BodgestrError::InvalidReferenceResolution {
+            device: device_id.to_string(),
+            width,
+            height,
+        })
+    }
+}
+
+/// System-wide config file, used when no per-user file exists.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/bodgestr/gestures.toml";
+
+/// Config file to load when none is given on the command line.
+///
+/// Candidates in order: [`SYSTEM_CONFIG_PATH`],
+/// `$XDG_CONFIG_HOME/bodgestr/gestures.toml` and
+/// `$HOME/.config/bodgestr/gestures.toml`.  The first one for which `exists`
+/// holds wins; if none does, the system path is returned so the read error
+/// names it.  An empty `xdg_config_home` is ignored, as the XDG spec asks.
+pub fn resolve_config_path(
+    xdg_config_home: Option<&Path>,
+    home: Option<&Path>,
+    exists: impl Fn(&Path) -> bool,
+) -> PathBuf {
+    let system = PathBuf::from(SYSTEM_CONFIG_PATH);
+    let user_file = |dir: PathBuf| dir.join("bodgestr").join("gestures.toml");
+    [
+        Some(system.clone()),
+        xdg_config_home
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| user_file(dir.to_path_buf())),
+        home.map(|dir| user_file(dir.join(".config"))),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|path| exists(path))
+    .unwrap_or(system)
+}
+
+/// [`resolve_config_path`] against the environment and the filesystem.
+pub fn default_config_path() -> PathBuf {
+    let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    resolve_config_path(xdg_config_home.as_deref(), home.as_deref(), Path::exists)
+}
+
+/// Parse a TOML or JSON config file (see [`ConfigFormat::from_path`]) and
+/// return the fully resolved `AppConfig`.
 pub fn parse_config_file(path: &Path) -> Result<AppConfig, BodgestrError> {
-    let raw: RawConfig =
-        toml::from_str(
-            &fs::read_to_string(path).map_err(|e| BodgestrError::ConfigReadError {
-                path: path.to_path_buf(),
-                source: e,
-            })?,
-        )
-        .map_err(|e| BodgestrError::ConfigParseError {
-            path: path.to_path_buf(),
-            message: e.to_string(),
-        })?;
+    let content = fs::read_to_string(path).map_err(|e| BodgestrError::ConfigReadError {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let format = ConfigFormat::from_path(path);
+    let parsed: Result<RawConfig, String> = match format {
+        ConfigFormat::Toml => toml::from_str(&content).map_err(|e| e.to_string()),
+        ConfigFormat::Json => serde_json::from_str(&content).map_err(|e| e.to_string()),
+    };
+    let mut raw = parsed.map_err(|message| BodgestrError::ConfigParseError {
+        path: path.to_path_buf(),
+        format,
+        message,
+    })?;
+    if raw.global.auto_enable_all == Some(true) {
+        raw.device
+            .entry(WILDCARD_DEVICE.to_string())
+            .or_insert_with(|| RawDevice {
+                enabled: Some(true),
+                ..RawDevice::default()
+            });
+    }
 
     let mut devices = HashMap::new();
 
@@ -237,34 +1299,284 @@ pub fn parse_config_file(path: &Path) -> Result<AppConfig, BodgestrError> {
             continue;
         }
 
-        let Some(usb_id) = raw_dev.device_usb_id.as_deref().filter(|s| !s.is_empty()) else {
+        let usb_id = raw_dev.device_usb_id.as_deref().filter(|s| !s.is_empty());
+        let device_path = raw_dev
+            .device_path
+            .clone()
+            .filter(|p| !p.as_os_str().is_empty());
+        let device_phys = raw_dev.device_phys.clone().filter(|s| !s.is_empty());
+        let wildcard = device_id == WILDCARD_DEVICE;
+        if usb_id.is_none() && device_path.is_none() && device_phys.is_none() && !wildcard {
             warn!(
-                "Device '{device_id}' is enabled but has no device_usb_id – skipping. \
-                 Run 'bodgestr --list-devices' to find your USB ID.",
+                "Device '{device_id}' is enabled but has no device_usb_id, device_path or \
+                 device_phys – \
+                 skipping. Run 'bodgestr --list-devices' to find your USB ID.",
             );
             continue;
+        }
+
+        // A profile's thresholds sit between the device's own and the global ones.
+        let device_thresholds = match &raw_dev.profile {
+            Some(name) => {
+                let profile =
+                    raw.profiles
+                        .get(name)
+                        .ok_or_else(|| BodgestrError::UnknownProfile {
+                            device: device_id.to_string(),
+                            profile: name.clone(),
+                        })?;
+                raw_dev.thresholds.over_profile(&profile.thresholds)
+            }
+            None => raw_dev.thresholds.clone(),
+        };
+        if device_thresholds.sets_both_swipe_distances()
+            || raw.global.thresholds.sets_both_swipe_distances()
+        {
+            return Err(BodgestrError::ConflictingSwipeDistance {
+                device: device_id.to_string(),
+            });
+        }
+        let base_thresholds = device_thresholds.merge_with_fallback(&raw.global.thresholds);
+        let validate = |thresholds: &RawThresholds| {
+            thresholds.clone().into_validated().map_err(|missing| {
+                BodgestrError::MissingThresholds {
+                    device: device_id.to_string(),
+                    missing: missing.join(", "),
+                }
+            })
+        };
+        // Orientation tables override single values: device before global,
+        // then the device's own thresholds before the global ones.
+        let oriented = |pick: fn(&RawThresholds) -> Option<&RawThresholds>| {
+            let device = pick(&device_thresholds);
+            let global = pick(&raw.global.thresholds);
+            if device.is_none() && global.is_none() {
+                return Ok(None);
+            }
+            let overrides = device.cloned().unwrap_or_default();
+            let overrides = overrides.merge_with_fallback(&global.cloned().unwrap_or_default());
+            validate(&overrides.merge_with_fallback(&base_thresholds)).map(Some)
         };
 
         devices.insert(
             device_id.clone(),
             DeviceConfig {
-                device_usb_id: usb_id.to_string(),
-                gestures: merge_gestures(&raw.global.gestures, &raw_dev.gestures),
-                thresholds: raw_dev
-                    .thresholds
-                    .merge_with_fallback(&raw.global.thresholds)
-                    .into_validated()
-                    .map_err(|missing| BodgestrError::MissingThresholds {
-                        device: device_id.to_string(),
-                        missing: missing.join(", "),
-                    })?,
+                device_usb_id: usb_id.unwrap_or_default().to_string(),
+                device_path,
+                device_phys,
+                single_touch: raw_dev.single_touch.unwrap_or(false),
+                coordinate_space: raw_dev.coordinate_space.unwrap_or_default(),
+                skip_initial_touches: raw_dev.skip_initial_touches.unwrap_or(0),
+                y_axis: raw_dev.y_axis.or(raw.global.y_axis).unwrap_or_default(),
+                primary_finger: raw_dev
+                    .primary_finger
+                    .or(raw.global.primary_finger)
+                    .unwrap_or_default(),
+                action_retries: raw_dev
+                    .action_retries
+                    .or(raw.global.action_retries)
+                    .unwrap_or(0),
+                wake_on_idle: raw_dev
+                    .wake_on_idle_secs
+                    .or(raw.global.wake_on_idle_secs)
+                    .filter(|secs| *secs > 0.0)
+                    .map(Duration::from_secs_f64),
+                wake_action: raw_dev
+                    .wake_action
+                    .as_deref()
+                    .or(raw.global.wake_action.as_deref())
+                    .map(|action| expand_templates(device_id, action, &raw.global.templates))
+                    .transpose()?,
+                long_press_drag: raw_dev
+                    .long_press_drag
+                    .or(raw.global.long_press_drag)
+                    .unwrap_or(false),
+                corner_gestures: raw_dev
+                    .corner_gestures
+                    .or(raw.global.corner_gestures)
+                    .unwrap_or(false),
+                swipe_then_tap_ms: raw_dev
+                    .swipe_then_tap_ms
+                    .or(raw.global.swipe_then_tap_ms)
+                    .filter(|&ms| ms > 0),
+                hold_before_swipe_ms: raw_dev
+                    .hold_before_swipe_ms
+                    .or(raw.global.hold_before_swipe_ms)
+                    .filter(|&ms| ms > 0),
+                normalize_coordinates: raw_dev
+                    .normalize_coordinates
+                    .or(raw.global.normalize_coordinates)
+                    .unwrap_or(false),
+                require_both_axes: raw_dev
+                    .require_both_axes
+                    .or(raw.global.require_both_axes)
+                    .unwrap_or(false),
+                suppress_repeat: raw_dev
+                    .suppress_repeat
+                    .or(raw.global.suppress_repeat)
+                    .unwrap_or(false),
+                swipe_once_per_contact: raw_dev
+                    .swipe_once_per_contact
+                    .or(raw.global.swipe_once_per_contact)
+                    .unwrap_or(false),
+                max_track_points: raw_dev
+                    .max_track_points
+                    .or(raw.global.max_track_points)
+                    .filter(|&n| n > 0),
+                max_fingers: raw_dev
+                    .max_fingers
+                    .or(raw.global.max_fingers)
+                    .filter(|&n| n > 0),
+                id_merge_ms: raw_dev
+                    .id_merge_ms
+                    .or(raw.global.id_merge_ms)
+                    .filter(|&ms| ms > 0),
+                stale_touch_timeout_ms: raw_dev
+                    .stale_touch_timeout_ms
+                    .or(raw.global.stale_touch_timeout_ms)
+                    .filter(|&ms| ms > 0),
+                release_timeout_ms: raw_dev
+                    .release_timeout_ms
+                    .or(raw.global.release_timeout_ms)
+                    .filter(|&ms| ms > 0),
+                max_events_per_sec: raw_dev
+                    .max_events_per_sec
+                    .or(raw.global.max_events_per_sec)
+                    .filter(|&n| n > 0),
+                post_action_delay_ms: raw_dev
+                    .post_action_delay_ms
+                    .or(raw.global.post_action_delay_ms),
+                reference_resolution: raw_dev
+                    .reference_resolution
+                    .or(raw.global.reference_resolution)
+                    .map(|resolution| validate_reference_resolution(device_id, resolution))
+                    .transpose()?,
+                only_when_class: raw_dev
+                    .only_when_class
+                    .clone()
+                    .or_else(|| raw.global.only_when_class.clone())
+                    .filter(|class| !class.is_empty()),
+                active_hours: raw_dev
+                    .active_hours
+                    .as_deref()
+                    .or(raw.global.active_hours.as_deref())
+                    .map(|hours| parse_active_hours(device_id, hours))
+                    .transpose()?,
+                gestures: merge_gestures(
+                    device_id,
+                    &raw.global.gestures,
+                    &raw_dev.gestures,
+                    &raw.global.templates,
+                )?,
+                thresholds: validate(&base_thresholds)?,
+                portrait_thresholds: oriented(|t| t.portrait.as_deref())?,
+                landscape_thresholds: oriented(|t| t.landscape.as_deref())?,
             },
         );
     }
 
+    let auto_device = devices.remove(WILDCARD_DEVICE);
+
     Ok(AppConfig {
         log_level: raw.global.log_level.unwrap_or_else(|| "info".to_string()),
         log_file: raw.global.log_file,
+        log_max_bytes: raw.global.log_max_bytes.filter(|&bytes| bytes > 0),
+        log_rotate_keep: raw.global.log_rotate_keep.unwrap_or(DEFAULT_ROTATE_KEEP),
+        log_targets: parse_log_targets(&raw.global.log_targets)?,
+        stats_file: raw.global.stats_file,
+        serialize_actions: raw.global.serialize_actions.unwrap_or(false),
+        action_queue_capacity: raw.global.action_queue_capacity.filter(|&n| n > 0),
+        watchdog_timeout: raw
+            .global
+            .watchdog_timeout_secs
+            .filter(|secs| *secs > 0.0)
+            .map(Duration::from_secs_f64),
+        selftest: raw.global.selftest.unwrap_or(false),
+        instant_tap: raw.global.instant_tap.unwrap_or(false),
+        pass_trajectory: raw.global.pass_trajectory.unwrap_or(false),
+        feedback_command: raw.global.feedback_command.filter(|s| !s.is_empty()),
+        disabled_gestures: parse_disabled_gestures(&raw.global.disabled_gestures)?,
+        mqtt: raw.global.mqtt,
+        fifo_path: raw.global.fifo_path,
+        control_socket: raw.global.control_socket,
+        http_addr: raw.global.http_addr,
+        global_thresholds: raw.global.thresholds.into_validated().ok(),
+        y_axis: raw.global.y_axis.unwrap_or_default(),
         devices,
+        auto_device,
     })
 }
+
+/// One difference between two resolved configurations, keyed by the dotted
+/// path of the setting as `--dump-config` prints it, e.g.
+/// `device.kiosk.gestures.swipe_left.action`.
+///
+/// Displays as `+ path = value`, `- path = value` or `~ path: old -> new`; a
+/// whole added or removed section, such as a device, is shown by path alone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigChange {
+    Added(String, serde_json::Value),
+    Removed(String, serde_json::Value),
+    Changed(String, serde_json::Value, serde_json::Value),
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigChange::Added(path, value) if value.is_object() => write!(f, "+ {path}"),
+            ConfigChange::Added(path, value) => write!(f, "+ {path} = {value}"),
+            ConfigChange::Removed(path, value) if value.is_object() => write!(f, "- {path}"),
+            ConfigChange::Removed(path, value) => write!(f, "- {path} = {value}"),
+            ConfigChange::Changed(path, old, new) => write!(f, "~ {path}: {old} -> {new}"),
+        }
+    }
+}
+
+/// Every setting that differs between `old` and `new`, in path order.
+pub fn diff_configs(old: &AppConfig, new: &AppConfig) -> Vec<ConfigChange> {
+    let to_value = |config| serde_json::to_value(config).unwrap_or_default();
+    let mut changes = Vec::new();
+    diff_values("", &to_value(old), &to_value(new), &mut changes);
+    changes
+}
+
+/// Compare `old` and `new` at `path`, descending into sections present in both.
+fn diff_values(
+    path: &str,
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    changes: &mut Vec<ConfigChange>,
+) {
+    use serde_json::Value;
+
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        let path = path.to_string();
+        match (old, new) {
+            _ if old == new => {}
+            (Value::Null, _) => changes.push(ConfigChange::Added(path, new.clone())),
+            (_, Value::Null) => changes.push(ConfigChange::Removed(path, old.clone())),
+            _ => changes.push(ConfigChange::Changed(path, old.clone(), new.clone())),
+        }
+        return;
+    };
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let child = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        match (old.get(key), new.get(key)) {
+            (Some(old), Some(new)) => diff_values(&child, old, new, changes),
+            (None, Some(new)) if !new.is_null() => {
+                changes.push(ConfigChange::Added(child, new.clone()));
+            }
+            (Some(old), None) if !old.is_null() => {
+                changes.push(ConfigChange::Removed(child, old.clone()));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Remap gestures at runtime through a control socket (`[global] control_socket`).
+//!
+//! Each line sent to the Unix socket is one command, answered with `ok` or
+//! `error: <reason>`:
+//!
+//! ```text
+//! bind <device> <gesture> <action...>
+//! ```
+//!
+//! A bound action replaces the configured one for that device and gesture
+//! until bodgestr restarts; the configuration file is never rewritten.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use log::{debug, info, warn};
+use nix::sys::stat::{Mode, umask};
+
+use crate::recognizer::GestureType;
+
+/// A parsed control socket command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Run `action` for `gesture` on `device` instead of its configured action.
+    Bind {
+        device: String,
+        gesture: GestureType,
+        action: String,
+    },
+}
+
+/// Parse one command line; the error is the reply sent to the client.
+pub fn parse_command(line: &str) -> Result<ControlCommand, String> {
+    let mut words = line.trim().splitn(2, char::is_whitespace);
+    match words.next().unwrap_or_default() {
+        "bind" => {
+            let mut args = words
+                .next()
+                .unwrap_or_default()
+                .trim_start()
+                .splitn(3, char::is_whitespace);
+            let (Some(device), Some(gesture), Some(action)) =
+                (args.next(), args.next(), args.next())
+            else {
+                return Err("usage: bind <device> <gesture> <action>".to_string());
+            };
+            let action = action.trim();
+            if action.is_empty() {
+                return Err("usage: bind <device> <gesture> <action>".to_string());
+            }
+            let gesture = gesture
+                .parse()
+                .map_err(|_| format!("unknown gesture '{gesture}'"))?;
+            Ok(ControlCommand::Bind {
+                device: device.to_string(),
+                gesture,
+                action: action.to_string(),
+            })
+        }
+        "" => Err("empty command".to_string()),
+        other => Err(format!("unknown command '{other}'")),
+    }
+}
+
+/// Actions bound through the control socket, shared by all device threads.
+#[derive(Debug, Default)]
+pub struct RuntimeBindings {
+    /// Devices a command may name; any device if empty.
+    devices: Vec<String>,
+    actions: RwLock<HashMap<(String, GestureType), String>>,
+}
+
+impl RuntimeBindings {
+    /// Accept bindings for the running `devices` only.
+    pub fn new(devices: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            devices: devices.into_iter().collect(),
+            actions: RwLock::default(),
+        }
+    }
+
+    /// Run one command line; the error is the reply sent to the client.
+    pub fn apply(&self, line: &str) -> Result<(), String> {
+        match parse_command(line)? {
+            ControlCommand::Bind {
+                device,
+                gesture,
+                action,
+            } => {
+                if !self.devices.is_empty() && !self.devices.contains(&device) {
+                    return Err(format!("no running device '{device}'"));
+                }
+                info!("{device}: {gesture} bound to '{action}' at runtime");
+                self.actions
+                    .write()
+                    .map_err(|_| "bindings unavailable".to_string())?
+                    .insert((device, gesture), action);
+                Ok(())
+            }
+        }
+    }
+
+    /// Action bound at runtime for `gesture` on `device_id`, if any.
+    pub fn action(&self, device_id: &str, gesture: GestureType) -> Option<String> {
+        let actions = self.actions.read().ok()?;
+        actions.get(&(device_id.to_string(), gesture)).cloned()
+    }
+}
+
+/// Listen for commands on `path`, replacing a stale socket left there.
+/// Only the owner may connect.
+pub fn listen(path: &Path, bindings: Arc<RuntimeBindings>) -> io::Result<()> {
+    match path.symlink_metadata() {
+        Ok(meta) if meta.file_type().is_socket() => fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    // Create the socket owner-only so it is never connectable by others,
+    // not even between bind and chmod.
+    let previous = umask(Mode::from_bits_truncate(0o177));
+    let bound = UnixListener::bind(path);
+    umask(previous);
+    let listener = bound?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+    thread::Builder::new()
+        .name("control".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let bindings = Arc::clone(&bindings);
+                        let spawned = thread::Builder::new()
+                            .name("control-client".into())
+                            .spawn(move || serve_client(stream, &bindings));
+                        if let Err(e) = spawned {
+                            warn!("Failed to spawn control client thread: {e}");
+                        }
+                    }
+                    Err(e) => warn!("Control socket accept failed: {e}"),
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// Answer each command line of one connection until the client hangs up.
+fn serve_client(stream: UnixStream, bindings: &RuntimeBindings) {
+    let mut reply_to = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => {
+            warn!("Control socket: {e}");
+            return;
+        }
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let reply = match bindings.apply(&line) {
+            Ok(()) => "ok\n".to_string(),
+            Err(e) => {
+                debug!("Control command '{}' rejected: {e}", line.trim());
+                format!("error: {e}\n")
+            }
+        };
+        if reply_to.write_all(reply.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
@@ -6,9 +6,12 @@
 //! side-effects.
 
 use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::config::GestureConfig;
-use crate::recognizer::{GestureRecognizer, GestureType};
+use crate::config::{AppConfig, DeviceConfig, GestureConfig};
+use crate::recognizer::{GestureDetails, GestureRecognizer, GestureType, NUM_GESTURES};
+use crate::uinput::scroll_event;
 
 // -- TouchEvent -----------------------------------------------
 
@@ -19,8 +22,15 @@ pub enum TouchEvent {
     PositionX(f64),
     PositionY(f64),
     TrackingId(i32),
+    /// Multitouch slot the following events refer to.
+    Slot(i32),
+    /// Contact start signalled by `BTN_TOUCH` rather than a tracking ID.
+    TouchBegin,
     FingerUp,
     SynReport,
+    /// `SYN_REPORT` carrying the kernel's event timestamp, used for gesture
+    /// timing instead of the time the frame was processed.
+    SynReportAt(SystemTime),
 }
 
 // -- Core processing ------------------------------------------
@@ -32,24 +42,63 @@ pub fn process_touch_events(
     recognizer: &mut GestureRecognizer,
     events: &[TouchEvent],
 ) -> Vec<GestureType> {
+    process_touch_events_detailed(recognizer, events)
+        .into_iter()
+        .map(|d| d.gesture)
+        .collect()
+}
+
+/// Like [`process_touch_events`], but returns each gesture together with
+/// the kinematics of the touch that produced it.
+pub fn process_touch_events_detailed(
+    recognizer: &mut GestureRecognizer,
+    events: &[TouchEvent],
+) -> Vec<GestureDetails> {
     let mut gestures = Vec::new();
     for event in events {
         match event {
             TouchEvent::PositionX(x) => recognizer.set_pending_x(*x),
             TouchEvent::PositionY(y) => recognizer.set_pending_y(*y),
             TouchEvent::TrackingId(id) => recognizer.set_tracking_id(*id),
+            TouchEvent::Slot(slot) => recognizer.set_slot(*slot),
+            TouchEvent::TouchBegin => recognizer.begin_touch(),
             TouchEvent::FingerUp => {
-                if let Some(g) = recognizer.check_pending_tap_expired() {
+                let touch_up = (recognizer.touch_events
+                    && !recognizer.is_disabled(GestureType::TouchUp))
+                .then(|| recognizer.touch_details(GestureType::TouchUp))
+                .flatten();
+                if let Some(g) = recognizer.check_pending_tap_expired_detailed() {
                     gestures.push(g);
                 }
-                if let Some(g) = recognizer.recognize_gesture() {
+                if let Some(g) = recognizer.recognize_gesture_detailed() {
                     gestures.push(g);
                 }
+                gestures.extend(touch_up);
+                recognizer.release_contact();
                 recognizer.reset();
             }
-            TouchEvent::SynReport => {
+            TouchEvent::SynReport | TouchEvent::SynReportAt(_) => {
+                if let TouchEvent::SynReportAt(time) = event {
+                    recognizer.set_event_time(*time);
+                }
+                let was_touching = recognizer.touch_start.is_some();
                 recognizer.flush_pending();
-                if let Some(g) = recognizer.check_pending_tap_expired() {
+                if let Some(g) = recognizer.check_pending_tap_expired_detailed() {
+                    gestures.push(g);
+                }
+                if recognizer.touch_events
+                    && !was_touching
+                    && !recognizer.is_disabled(GestureType::TouchDown)
+                {
+                    gestures.extend(recognizer.touch_details(GestureType::TouchDown));
+                }
+                if let Some(g) = recognizer.check_swipe_begin_detailed() {
+                    gestures.push(g);
+                }
+                if let Some(g) = recognizer.check_long_press_drag_detailed() {
+                    gestures.push(g);
+                }
+                if let Some(g) = recognizer.check_pinch_repeat_detailed() {
                     gestures.push(g);
                 }
             }
@@ -58,6 +107,72 @@ pub fn process_touch_events(
     gestures
 }
 
+/// Classify a batch of raw `evdev` events and feed the touch-relevant ones
+/// to the recognizer in one pass.
+///
+/// `scratch` is cleared and refilled, so a buffer kept across calls is only
+/// reallocated when a batch outgrows it.
+///
+/// With `single_touch`, legacy `ABS_X`/`ABS_Y` are positions too (see
+/// [`classify_single_touch_event`]).
+pub fn process_batch(
+    recognizer: &mut GestureRecognizer,
+    events: &[evdev::InputEvent],
+    scratch: &mut Vec<TouchEvent>,
+    single_touch: bool,
+) -> Vec<GestureDetails> {
+    let classify = if single_touch {
+        classify_single_touch_event
+    } else {
+        classify_event
+    };
+    scratch.clear();
+    scratch.extend(events.iter().filter_map(classify));
+    process_touch_events_detailed(recognizer, scratch)
+}
+
+/// Discard gestures while `remaining` is non-zero, decrementing it for each
+/// one dropped.  Used to ignore phantom contacts some panels emit on open.
+pub fn skip_initial_gestures<T>(gestures: Vec<T>, remaining: &mut u32) -> Vec<T> {
+    gestures
+        .into_iter()
+        .filter(|_| {
+            if *remaining == 0 {
+                return true;
+            }
+            *remaining -= 1;
+            false
+        })
+        .collect()
+}
+
+/// "Tap anywhere to wake": after `idle` without gestures, the next gesture
+/// only wakes the screen and is not dispatched.
+#[derive(Debug, Clone)]
+pub struct WakeGate {
+    idle: Option<Duration>,
+    last_activity: Instant,
+}
+
+impl WakeGate {
+    /// Create a gate; `now` counts as the most recent activity.  With `idle`
+    /// set to `None` the gate never consumes a gesture.
+    pub fn new(idle: Option<Duration>, now: Instant) -> Self {
+        Self {
+            idle,
+            last_activity: now,
+        }
+    }
+
+    /// Record a gesture at `now` and return `true` if it should be consumed
+    /// as a wake event instead of being dispatched.
+    pub fn consume_as_wake(&mut self, now: Instant) -> bool {
+        let idle_for = now.saturating_duration_since(self.last_activity);
+        self.last_activity = now;
+        self.idle.is_some_and(|idle| idle_for >= idle)
+    }
+}
+
 // -- Helpers --------------------------------------------------
 
 /// Parse a USB vendor:product ID string into `(vendor, product)`.
@@ -86,10 +201,189 @@ pub fn resolve_action(
         .and_then(|gc| gc.action.as_deref())
 }
 
+/// Like [`resolve_action`], but prefers the `by_fingers` entry matching the
+/// number of fingers the gesture was recognized with.
+pub fn resolve_finger_action(
+    gesture: GestureType,
+    finger_count: usize,
+    gestures: &HashMap<String, GestureConfig>,
+) -> Option<&str> {
+    let gesture_name: &str = gesture.into();
+    gestures
+        .get(gesture_name)
+        .filter(|gc| gc.enabled)
+        .and_then(|gc| {
+            gc.by_fingers
+                .get(&finger_count)
+                .or(gc.action.as_ref())
+                .map(String::as_str)
+        })
+}
+
+/// Whether `gesture` is enabled but bound to nothing - no action, key or
+/// scroll.  Such a gesture is recognized, counted and logged, but runs
+/// nothing, unlike one without an enabled binding.
+pub fn is_record_only(gesture: GestureType, gestures: &HashMap<String, GestureConfig>) -> bool {
+    let gesture_name: &str = gesture.into();
+    gestures.get(gesture_name).is_some_and(|gc| {
+        gc.enabled
+            && gc.action.is_none()
+            && gc.by_fingers.is_empty()
+            && gc.key.is_none()
+            && gc.scroll.is_none()
+    })
+}
+
+/// Whether a recognized tap was repeated the `tap_count` times its binding
+/// asks for (once when unset).  Always `true` for other gestures.
+pub fn tap_count_matches(
+    details: &GestureDetails,
+    gestures: &HashMap<String, GestureConfig>,
+) -> bool {
+    if details.gesture != GestureType::Tap {
+        return true;
+    }
+    let tap: &str = GestureType::Tap.into();
+    details.tap_count == gestures.get(tap).and_then(|gc| gc.tap_count).unwrap_or(1)
+}
+
+/// Whether an enabled `double_tap` binding exists that `[global]
+/// disabled_gestures` does not switch off.  Without one, taps need not wait
+/// `double_tap_interval` for a second tap.
+pub fn binds_double_tap(
+    gestures: &HashMap<String, GestureConfig>,
+    disabled_gestures: &[GestureType],
+) -> bool {
+    let double_tap: &str = GestureType::DoubleTap.into();
+    !disabled_gestures.contains(&GestureType::DoubleTap)
+        && gestures.get(double_tap).is_some_and(|gc| gc.enabled)
+}
+
+/// Consecutive taps the recognizer must count for the enabled `tap`
+/// binding - 1 unless it sets `tap_count`.
+pub fn max_tap_count(gestures: &HashMap<String, GestureConfig>) -> usize {
+    let tap: &str = GestureType::Tap.into();
+    gestures
+        .get(tap)
+        .filter(|gc| gc.enabled)
+        .and_then(|gc| gc.tap_count)
+        .unwrap_or(1)
+}
+
+/// Look up the uinput key bound to a recognized gesture in the device config.
+///
+/// Returns `Some(key)` if the gesture is configured, enabled, and has a key.
+pub fn resolve_key(
+    gesture: GestureType,
+    gestures: &HashMap<String, GestureConfig>,
+) -> Option<evdev::Key> {
+    let gesture_name: &str = gesture.into();
+    gestures
+        .get(gesture_name)
+        .filter(|gc| gc.enabled)
+        .and_then(|gc| gc.key)
+}
+
+/// Look up the uinput wheel event bound to a recognized gesture in the device config.
+///
+/// Returns `Some((axis, delta))` if the gesture is configured, enabled, has a
+/// `scroll` axis, and has a direction (see [`scroll_event`]).
+pub fn resolve_scroll(
+    gesture: GestureType,
+    gestures: &HashMap<String, GestureConfig>,
+) -> Option<(evdev::RelativeAxisType, i32)> {
+    let gesture_name: &str = gesture.into();
+    gestures
+        .get(gesture_name)
+        .filter(|gc| gc.enabled)
+        .and_then(|gc| gc.scroll)
+        .and_then(|axis| scroll_event(gesture, axis))
+}
+
+/// Render every device's resolved binding for each gesture type, for
+/// `--show-bindings`.
+///
+/// Devices and gestures are sorted by name.  A gesture shows its action, key
+/// and scroll bindings, `[disabled]` if configured but not enabled, or
+/// `[unbound]`; `by_fingers` actions follow as `<gesture> (<n> fingers)`.
+pub fn format_bindings(config: &AppConfig) -> String {
+    let mut gesture_names: Vec<&str> = (0..NUM_GESTURES)
+        .filter_map(GestureType::from_index)
+        .map(Into::into)
+        .collect();
+    gesture_names.sort_unstable();
+    let mut device_ids: Vec<&String> = config.devices.keys().collect();
+    device_ids.sort_unstable();
+
+    let mut out = String::new();
+    for device_id in device_ids {
+        let gestures = &config.devices[device_id].gestures;
+        let _ = writeln!(out, "[{device_id}]");
+        for &name in &gesture_names {
+            let gesture: GestureType = name.parse().expect("gesture names round-trip");
+            let binding = match gestures.get(name) {
+                Some(gc) if !gc.enabled => "[disabled]".to_string(),
+                _ => {
+                    let mut parts: Vec<String> = [
+                        resolve_action(gesture, gestures).map(str::to_string),
+                        resolve_key(gesture, gestures).map(|key| format!("key {key:?}")),
+                        resolve_scroll(gesture, gestures)
+                            .map(|(axis, delta)| format!("scroll {axis:?} {delta:+}")),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                    let taps = gestures.get(name).and_then(|gc| gc.tap_count);
+                    if let Some(taps) = taps.filter(|&n| n > 1 && !parts.is_empty()) {
+                        parts.push(format!("on {taps} taps"));
+                    }
+                    if parts.is_empty() {
+                        "[unbound]".to_string()
+                    } else {
+                        parts.join("; ")
+                    }
+                }
+            };
+            let _ = writeln!(out, "  {name:<24}{binding}");
+            if let Some(gc) = gestures.get(name).filter(|gc| gc.enabled) {
+                for (fingers, action) in &gc.by_fingers {
+                    let label = format!("{name} ({fingers} fingers)");
+                    let _ = writeln!(out, "  {label:<24}{action}");
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Check the device's and the gesture's `active_hours` at `minute_of_day`
+/// (minutes after local midnight).  Unset windows never suppress.
+pub fn within_active_hours(
+    gesture: GestureType,
+    config: &DeviceConfig,
+    minute_of_day: u16,
+) -> bool {
+    let gesture_name: &str = gesture.into();
+    let gesture_hours = config
+        .gestures
+        .get(gesture_name)
+        .and_then(|gc| gc.active_hours);
+    [config.active_hours, gesture_hours]
+        .into_iter()
+        .flatten()
+        .all(|hours| hours.contains(minute_of_day))
+}
+
 /// Classify a single `evdev::InputEvent` into one of the touch-relevant
 /// categories the handler cares about.  Returns `None` for irrelevant events.
+///
+/// `BTN_TOUCH` is mapped as a fallback contact signal for panels that do not
+/// report tracking-ID transitions: press → `TouchBegin`, release → `FingerUp`.
+///
+/// `SYN_REPORT` becomes `SynReportAt` with the event's timestamp, or plain
+/// `SynReport` when the event carries none (a zero time).
 pub fn classify_event(event: &evdev::InputEvent) -> Option<TouchEvent> {
-    use evdev::{AbsoluteAxisType, InputEventKind};
+    use evdev::{AbsoluteAxisType, InputEventKind, Key};
 
     match event.kind() {
         InputEventKind::AbsAxis(axis) => match axis {
@@ -99,6 +393,7 @@ pub fn classify_event(event: &evdev::InputEvent) -> Option<TouchEvent> {
             AbsoluteAxisType::ABS_MT_POSITION_Y => {
                 Some(TouchEvent::PositionY(event.value() as f64))
             }
+            AbsoluteAxisType::ABS_MT_SLOT => Some(TouchEvent::Slot(event.value())),
             AbsoluteAxisType::ABS_MT_TRACKING_ID => {
                 if event.value() == -1 {
                     Some(TouchEvent::FingerUp)
@@ -108,9 +403,72 @@ pub fn classify_event(event: &evdev::InputEvent) -> Option<TouchEvent> {
             }
             _ => None,
         },
+        InputEventKind::Key(Key::BTN_TOUCH) => match event.value() {
+            0 => Some(TouchEvent::FingerUp),
+            1 => Some(TouchEvent::TouchBegin),
+            _ => None,
+        },
         InputEventKind::Synchronization(evdev::Synchronization::SYN_REPORT) => {
-            Some(TouchEvent::SynReport)
+            match event.timestamp() {
+                SystemTime::UNIX_EPOCH => Some(TouchEvent::SynReport),
+                time => Some(TouchEvent::SynReportAt(time)),
+            }
         }
         _ => None,
     }
 }
+
+/// Like [`classify_event`], but also maps the legacy single-touch axes
+/// `ABS_X`/`ABS_Y` to positions, for panels without multi-touch axes
+/// (`single_touch = true`).  Contacts then come from `BTN_TOUCH`.
+pub fn classify_single_touch_event(event: &evdev::InputEvent) -> Option<TouchEvent> {
+    use evdev::{AbsoluteAxisType, InputEventKind};
+
+    match event.kind() {
+        InputEventKind::AbsAxis(AbsoluteAxisType::ABS_X) => {
+            Some(TouchEvent::PositionX(event.value() as f64))
+        }
+        InputEventKind::AbsAxis(AbsoluteAxisType::ABS_Y) => {
+            Some(TouchEvent::PositionY(event.value() as f64))
+        }
+        _ => classify_event(event),
+    }
+}
+
+// -- Contact release ------------------------------------------
+
+/// How a device signals that a contact has lifted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseSignal {
+    /// `ABS_MT_TRACKING_ID` of -1.
+    TrackingId,
+    /// `BTN_TOUCH` release, for panels without tracking IDs.
+    BtnTouch,
+    /// Neither is reported: a contact ends once the device stays silent for
+    /// this long.
+    Timeout(Duration),
+}
+
+impl ReleaseSignal {
+    /// Pick the signal from the axes and keys a device reports.
+    ///
+    /// Tracking IDs are preferred on multi-touch devices, then `BTN_TOUCH`;
+    /// a device reporting neither falls back to `timeout`.
+    pub fn select(
+        abs_axes: Option<&evdev::AttributeSetRef<evdev::AbsoluteAxisType>>,
+        keys: Option<&evdev::AttributeSetRef<evdev::Key>>,
+        single_touch: bool,
+        timeout: Duration,
+    ) -> Self {
+        let tracking_id = !single_touch
+            && abs_axes
+                .is_some_and(|axes| axes.contains(evdev::AbsoluteAxisType::ABS_MT_TRACKING_ID));
+        if tracking_id {
+            ReleaseSignal::TrackingId
+        } else if keys.is_some_and(|keys| keys.contains(evdev::Key::BTN_TOUCH)) {
+            ReleaseSignal::BtnTouch
+        } else {
+            ReleaseSignal::Timeout(timeout)
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Write recognized gestures to a named pipe (`[global] fifo_path`).
+//!
+//! Each gesture becomes one `<device>/<gesture>` line for a single reading
+//! script, e.g. `while read g < /run/bodgestr.fifo`.  The pipe is opened
+//! without blocking: while no reader is attached, or its end is full,
+//! lines are dropped instead of stalling a device thread.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::debug;
+use nix::fcntl::OFlag;
+use nix::sys::stat::Mode;
+
+use crate::recognizer::GestureType;
+
+/// Line written for `gesture` on `device_id`, newline included.
+pub fn gesture_line(device_id: &str, gesture: GestureType) -> String {
+    format!("{device_id}/{gesture}\n")
+}
+
+/// Writer end of the gesture FIFO; shared by all device threads.
+pub struct FifoWriter {
+    path: PathBuf,
+    /// Open while a reader is attached; reopened after it leaves.
+    pipe: Mutex<Option<File>>,
+}
+
+impl FifoWriter {
+    /// Use the FIFO at `path`, creating it if missing.  Fails if `path`
+    /// exists but is not a FIFO.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        match path.metadata() {
+            Ok(meta) if meta.file_type().is_fifo() => {}
+            Ok(_) => {
+                return Err(io::Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a FIFO", path.display()),
+                ));
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                nix::unistd::mkfifo(path, Mode::from_bits_truncate(0o600))?;
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            pipe: Mutex::new(None),
+        })
+    }
+
+    /// Write the line for `gesture` on `device_id`; `false` if it was
+    /// dropped because no reader is attached or the pipe is full.
+    pub fn send(&self, device_id: &str, gesture: GestureType) -> bool {
+        let Ok(mut pipe) = self.pipe.lock() else {
+            return false;
+        };
+        if pipe.is_none() {
+            // Opening the write end without a reader fails with ENXIO.
+            *pipe = OpenOptions::new()
+                .write(true)
+                .custom_flags(OFlag::O_NONBLOCK.bits())
+                .open(&self.path)
+                .map_err(|e| debug!("No reader on {}: {e}", self.path.display()))
+                .ok();
+        }
+        let Some(file) = pipe.as_mut() else {
+            return false;
+        };
+        match file.write_all(gesture_line(device_id, gesture).as_bytes()) {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("Dropped {gesture} for {}: {e}", self.path.display());
+                if e.kind() != ErrorKind::WouldBlock {
+                    // The reader left; reopen for the next one.
+                    *pipe = None;
+                }
+                false
+            }
+        }
+    }
+}
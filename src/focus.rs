@@ -0,0 +1,132 @@
+//! Restrict gestures to a focused X11 application (`only_when_class`).
+//!
+//! The active window's `WM_CLASS` is read through `x11rb`, which is only
+//! available with the `x11` cargo feature.  Without it - or without a
+//! reachable X server - the focused class is unknown and gestures of
+//! devices that set `only_when_class` are skipped.
+
+use std::time::{Duration, Instant};
+
+/// How long a focus query result is reused before asking the X server again.
+pub const FOCUS_CACHE_TTL: Duration = Duration::from_millis(500);
+
+/// Decide whether a gesture may be dispatched given the focused window class.
+///
+/// Always `true` without an `only_when_class` filter.  Otherwise the focused
+/// class must be known and equal the filter, ignoring ASCII case.
+pub fn focus_allows(only_when_class: Option<&str>, focused_class: Option<&str>) -> bool {
+    match only_when_class {
+        None => true,
+        Some(wanted) => focused_class.is_some_and(|class| class.eq_ignore_ascii_case(wanted)),
+    }
+}
+
+/// Remembers the last focus query for [`FOCUS_CACHE_TTL`] to avoid an X11
+/// round-trip per gesture.
+#[derive(Debug, Clone, Default)]
+pub struct FocusCache {
+    last: Option<(Instant, Option<String>)>,
+}
+
+impl FocusCache {
+    /// Return the focused class, calling `query` only if the cached value is
+    /// missing or older than [`FOCUS_CACHE_TTL`] at `now`.
+    pub fn focused_class(
+        &mut self,
+        now: Instant,
+        query: impl FnOnce() -> Option<String>,
+    ) -> Option<&str> {
+        let fresh = self
+            .last
+            .as_ref()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) < FOCUS_CACHE_TTL);
+        if !fresh {
+            self.last = Some((now, query()));
+        }
+        self.last.as_ref().and_then(|(_, class)| class.as_deref())
+    }
+}
+
+#[cfg(feature = "x11")]
+mod imp {
+    use log::debug;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+    use x11rb::rust_connection::RustConnection;
+
+    /// Lazily connected X11 client that reads the active window's class.
+    #[derive(Default)]
+    pub struct FocusQuery {
+        conn: Option<(RustConnection, usize)>,
+    }
+
+    impl FocusQuery {
+        /// Class of the window named by `_NET_ACTIVE_WINDOW`, if any.
+        ///
+        /// Connects on first use and reconnects after a failed request.
+        pub fn active_class(&mut self) -> Option<String> {
+            if self.conn.is_none() {
+                self.conn = x11rb::connect(None)
+                    .map_err(|e| debug!("Cannot connect to X server: {e}"))
+                    .ok();
+            }
+            let (conn, screen) = self.conn.as_ref()?;
+            match query_active_class(conn, *screen) {
+                Ok(class) => class,
+                Err(e) => {
+                    debug!("Focus query failed: {e}");
+                    self.conn = None;
+                    None
+                }
+            }
+        }
+    }
+
+    fn query_active_class(
+        conn: &RustConnection,
+        screen: usize,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let root = conn.setup().roots[screen].root;
+        let net_active_window = conn
+            .intern_atom(false, b"_NET_ACTIVE_WINDOW")?
+            .reply()?
+            .atom;
+        let active = conn
+            .get_property(false, root, net_active_window, AtomEnum::WINDOW, 0, 1)?
+            .reply()?;
+        let Some(window) = active.value32().and_then(|mut ids| ids.next()) else {
+            return Ok(None);
+        };
+        if window == 0 {
+            return Ok(None);
+        }
+
+        // WM_CLASS holds "instance\0class\0"; match on the class part.
+        let wm_class = conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)?
+            .reply()?;
+        Ok(wm_class
+            .value
+            .split(|&b| b == 0)
+            .nth(1)
+            .map(|class| String::from_utf8_lossy(class).into_owned()))
+    }
+}
+
+#[cfg(not(feature = "x11"))]
+mod imp {
+    /// Placeholder for builds without the `x11` feature.
+    #[derive(Default)]
+    pub struct FocusQuery {
+        _private: (),
+    }
+
+    impl FocusQuery {
+        /// Always `None`: this build cannot query X11.
+        pub fn active_class(&mut self) -> Option<String> {
+            None
+        }
+    }
+}
+
+pub use imp::FocusQuery;
@@ -0,0 +1,248 @@
+//! Live gestures and counters over HTTP (`[global] http_addr`).
+//!
+//! `GET /gestures` is a server-sent-events stream with one `gesture` event
+//! per dispatched gesture; `GET /stats` returns the gesture counts as JSON.
+//! The server is a few threads on `std::net`, only compiled in with the
+//! `http` cargo feature.  A client that falls behind misses events rather
+//! than stalling a device thread, and a client that disconnects is dropped
+//! on the next event or keep-alive.
+
+use std::io::{BufRead, BufReader, Read};
+
+use crate::recognizer::GestureType;
+use crate::stats::GestureStats;
+
+/// Most bytes of request line and headers read from a client.
+pub const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// Path of a `GET` request read from `request`, after its headers.  `None`
+/// for other methods, and for a request whose line or headers are cut
+/// short - by the client or by [`MAX_REQUEST_BYTES`].
+pub fn request_path(request: impl Read) -> Option<String> {
+    let mut reader = BufReader::new(request.take(MAX_REQUEST_BYTES));
+    let mut read_line = |line: &mut String| {
+        line.clear();
+        let complete = reader.read_line(line).ok()? > 0 && line.ends_with('\n');
+        complete.then_some(())
+    };
+    let mut request_line = String::new();
+    read_line(&mut request_line)?;
+    let mut header = String::new();
+    loop {
+        read_line(&mut header)?;
+        if header.trim_end().is_empty() {
+            break;
+        }
+    }
+    let mut parts = request_line.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let target = parts.next()?;
+    Some(target.split('?').next().unwrap_or(target).to_string())
+}
+
+/// One server-sent event for `gesture` on `device_id`, blank line included.
+pub fn sse_event(device_id: &str, gesture: GestureType) -> String {
+    let data = serde_json::json!({
+        "device": device_id,
+        "gesture": gesture.to_string(),
+    });
+    format!("event: gesture\ndata: {data}\n\n")
+}
+
+/// Body of `GET /stats`: the counts keyed by gesture name.
+pub fn stats_json(stats: &GestureStats) -> String {
+    serde_json::to_string(&stats.counts).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[cfg(feature = "http")]
+mod imp {
+    use std::io::{self, Write};
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use log::{debug, info, warn};
+
+    use super::{request_path, sse_event, stats_json};
+    use crate::recognizer::GestureType;
+    use crate::stats::GestureStats;
+
+    /// Events buffered per `/gestures` client before it misses some.
+    const CLIENT_BACKLOG: usize = 64;
+    /// Idle time after which an SSE comment is sent to notice closed clients.
+    const KEEP_ALIVE: Duration = Duration::from_secs(15);
+    /// Connections served at once; further ones are closed right away.
+    const MAX_CLIENTS: usize = 32;
+    /// Time a client has to send its request before it is dropped.
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Running HTTP server; shared by all device threads.
+    pub struct HttpServer {
+        /// One sender per connected `/gestures` client.
+        subscribers: Mutex<Vec<SyncSender<String>>>,
+        stats: Option<Arc<Mutex<GestureStats>>>,
+    }
+
+    impl HttpServer {
+        /// Listen on `addr`, serving `/stats` from `stats` when counted.
+        pub fn start(
+            addr: SocketAddr,
+            stats: Option<Arc<Mutex<GestureStats>>>,
+        ) -> io::Result<Arc<Self>> {
+            let listener = TcpListener::bind(addr)?;
+            let server = Arc::new(Self {
+                subscribers: Mutex::default(),
+                stats,
+            });
+            let accepting = Arc::clone(&server);
+            let clients = Arc::new(AtomicUsize::new(0));
+            thread::Builder::new().name("http".into()).spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(_) if clients.load(Ordering::Relaxed) >= MAX_CLIENTS => {
+                            warn!("HTTP client limit of {MAX_CLIENTS} reached, closing connection");
+                        }
+                        Ok(stream) => {
+                            let server = Arc::clone(&accepting);
+                            let served = Arc::clone(&clients);
+                            served.fetch_add(1, Ordering::Relaxed);
+                            let spawned = thread::Builder::new().name("http-client".into()).spawn(
+                                move || {
+                                    server.serve(stream);
+                                    served.fetch_sub(1, Ordering::Relaxed);
+                                },
+                            );
+                            if let Err(e) = spawned {
+                                clients.fetch_sub(1, Ordering::Relaxed);
+                                warn!("Failed to spawn HTTP client thread: {e}");
+                            }
+                        }
+                        Err(e) => warn!("HTTP accept failed: {e}"),
+                    }
+                }
+            })?;
+            info!("Serving live gestures on http://{addr}/gestures");
+            Ok(server)
+        }
+
+        /// Send `gesture` on `device_id` to every `/gestures` client.
+        pub fn publish(&self, device_id: &str, gesture: GestureType) {
+            let Ok(mut subscribers) = self.subscribers.lock() else {
+                return;
+            };
+            let event = sse_event(device_id, gesture);
+            subscribers.retain(|client| match client.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    debug!("HTTP client behind - dropped {device_id}/{gesture}");
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        }
+
+        /// Answer one request; `/gestures` streams until the client leaves.
+        fn serve(&self, stream: TcpStream) {
+            if let Err(e) = stream.set_read_timeout(Some(REQUEST_TIMEOUT)) {
+                debug!("HTTP client dropped: {e}");
+                return;
+            }
+            let Some(path) = request_path(&stream) else {
+                return;
+            };
+            let mut stream = stream;
+            let result = match path.as_str() {
+                "/gestures" => self.stream_gestures(&mut stream),
+                "/stats" => {
+                    let snapshot = self
+                        .stats
+                        .as_deref()
+                        .and_then(|stats| stats.lock().ok().map(|s| s.clone()))
+                        .unwrap_or_default();
+                    respond(
+                        &mut stream,
+                        "200 OK",
+                        "application/json",
+                        &stats_json(&snapshot),
+                    )
+                }
+                _ => respond(&mut stream, "404 Not Found", "text/plain", "not found\n"),
+            };
+            if let Err(e) = result {
+                debug!("HTTP client {path} closed: {e}");
+            }
+        }
+
+        /// Stream events to a `/gestures` client until a write fails.
+        fn stream_gestures(&self, stream: &mut TcpStream) -> io::Result<()> {
+            stream.write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                  Cache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+            )?;
+            let (sender, events) = mpsc::sync_channel(CLIENT_BACKLOG);
+            if let Ok(mut subscribers) = self.subscribers.lock() {
+                subscribers.push(sender);
+            }
+            loop {
+                match events.recv_timeout(KEEP_ALIVE) {
+                    Ok(event) => stream.write_all(event.as_bytes())?,
+                    Err(RecvTimeoutError::Timeout) => stream.write_all(b": keep-alive\n\n")?,
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Write a complete response with `body`.
+    fn respond(
+        stream: &mut TcpStream,
+        status: &str,
+        content_type: &str,
+        body: &str,
+    ) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+}
+
+#[cfg(not(feature = "http"))]
+mod imp {
+    use std::io;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+
+    use log::warn;
+
+    use crate::recognizer::GestureType;
+    use crate::stats::GestureStats;
+
+    /// Placeholder for builds without the `http` feature.
+    pub struct HttpServer {
+        _private: (),
+    }
+
+    impl HttpServer {
+        /// Warn that `http_addr` is ignored by this build.
+        pub fn start(
+            _addr: SocketAddr,
+            _stats: Option<Arc<Mutex<GestureStats>>>,
+        ) -> io::Result<Arc<Self>> {
+            warn!("http_addr is set but bodgestr was built without the http feature");
+            Ok(Arc::new(Self { _private: () }))
+        }
+
+        /// No-op: this build serves nothing.
+        pub fn publish(&self, _device_id: &str, _gesture: GestureType) {}
+    }
+}
+
+pub use imp::HttpServer;
@@ -0,0 +1,110 @@
+//! Log recognized gestures to the systemd journal with structured fields.
+//!
+//! Each gesture becomes a journal entry carrying `GESTURE=` and `DEVICE=`
+//! next to the usual `MESSAGE=`, so `journalctl GESTURE=swipe_left` finds
+//! them.  Entries are written over journald's native socket protocol, which
+//! is only compiled in with the `journald` cargo feature, and only used when
+//! stderr is connected to the journal (i.e. running as a systemd service).
+
+use crate::recognizer::GestureType;
+
+/// Journal priority of gesture entries (`LOG_INFO`).
+const PRIORITY_INFO: &str = "6";
+
+/// Fields of the journal entry for `gesture` recognized on `device_id`.
+pub fn journal_fields(device_id: &str, gesture: GestureType) -> Vec<(&'static str, String)> {
+    vec![
+        ("MESSAGE", format!("{device_id}: {gesture}")),
+        ("GESTURE", gesture.to_string()),
+        ("DEVICE", device_id.to_string()),
+        ("PRIORITY", PRIORITY_INFO.to_string()),
+        ("SYSLOG_IDENTIFIER", "bodgestr".to_string()),
+    ]
+}
+
+/// Encode `fields` as one datagram of journald's native protocol.
+///
+/// Values are written as `KEY=value` lines; a value containing a newline
+/// is written as `KEY`, a newline, its little-endian 64-bit length, the raw
+/// value and a newline.
+pub fn encode_fields(fields: &[(&str, String)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (key, value) in fields {
+        out.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            out.push(b'\n');
+            out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            out.push(b'=');
+        }
+        out.extend_from_slice(value.as_bytes());
+        out.push(b'\n');
+    }
+    out
+}
+
+#[cfg(feature = "journald")]
+mod imp {
+    use std::os::unix::net::UnixDatagram;
+
+    use log::{debug, warn};
+
+    use super::{encode_fields, journal_fields};
+    use crate::recognizer::GestureType;
+
+    /// journald's socket for the native protocol.
+    const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+    /// Connection to the journal; cheap to share across device threads.
+    pub struct JournalSender {
+        socket: UnixDatagram,
+    }
+
+    impl JournalSender {
+        /// Connect when stderr goes to the journal (`JOURNAL_STREAM` is set
+        /// by systemd), so interactive runs keep their plain stderr lines.
+        pub fn connect() -> Option<Self> {
+            std::env::var_os("JOURNAL_STREAM")?;
+            let socket = UnixDatagram::unbound()
+                .and_then(|socket| socket.connect(JOURNAL_SOCKET).map(|()| socket))
+                .map_err(|e| warn!("Cannot connect to the journal at {JOURNAL_SOCKET}: {e}"))
+                .ok()?;
+            debug!("Logging gestures to the journal");
+            Some(Self { socket })
+        }
+
+        /// Write the entry for `gesture` on `device_id`; `false` if it could
+        /// not be sent.
+        pub fn send(&self, device_id: &str, gesture: GestureType) -> bool {
+            let entry = encode_fields(&journal_fields(device_id, gesture));
+            self.socket
+                .send(&entry)
+                .map_err(|e| debug!("Failed to write to the journal: {e}"))
+                .is_ok()
+        }
+    }
+}
+
+#[cfg(not(feature = "journald"))]
+mod imp {
+    use crate::recognizer::GestureType;
+
+    /// Placeholder for builds without the `journald` feature.
+    pub struct JournalSender {
+        _private: (),
+    }
+
+    impl JournalSender {
+        /// Always `None`: this build logs gestures as plain lines.
+        pub fn connect() -> Option<Self> {
+            None
+        }
+
+        /// Never called, as no sender can be constructed.
+        pub fn send(&self, _device_id: &str, _gesture: GestureType) -> bool {
+            false
+        }
+    }
+}
+
+pub use imp::JournalSender;
@@ -1,6 +1,49 @@
 //! bodgestr – Gesture recognition for Linux touchscreens.
 
 pub mod config;
+pub mod control;
 pub mod event;
+pub mod fifo;
+pub mod focus;
+pub mod http;
+pub mod journal;
+pub mod logfile;
 pub mod manager;
+pub mod mqtt;
 pub mod recognizer;
+pub mod stats;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod uinput;
+
+use recognizer::GestureType;
+
+/// Cargo features compiled into this build, in `Cargo.toml` order.
+pub const FEATURES: &[(&str, bool)] = &[
+    ("uinput", cfg!(feature = "uinput")),
+    ("x11", cfg!(feature = "x11")),
+    ("mqtt", cfg!(feature = "mqtt")),
+    ("journald", cfg!(feature = "journald")),
+    ("http", cfg!(feature = "http")),
+    ("async", cfg!(feature = "async")),
+];
+
+/// Version, compiled-in features and gesture names, for bug reports.
+pub fn build_info() -> String {
+    let features: Vec<&str> = FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    let gestures: Vec<&str> = GestureType::all().map(<&str>::from).collect();
+    format!(
+        "bodgestr {}\nfeatures: {}\ngestures: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        if features.is_empty() {
+            "none".to_string()
+        } else {
+            features.join(", ")
+        },
+        gestures.join(", ")
+    )
+}
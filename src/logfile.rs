@@ -0,0 +1,102 @@
+//! The `[global] log_file`, rotated by size when `log_max_bytes` is set.
+//!
+//! Once a line would push the file past `log_max_bytes`, `bodgestr.log`
+//! becomes `bodgestr.log.1`, older files shift up by one, the file beyond
+//! `log_rotate_keep` is deleted, and a fresh `bodgestr.log` is opened.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Historical files kept when `log_max_bytes` is set without `log_rotate_keep`.
+pub const DEFAULT_ROTATE_KEEP: usize = 3;
+
+/// Path of the `n`th most recent rotated file, e.g. `bodgestr.log.1`.
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{n}"));
+    PathBuf::from(rotated)
+}
+
+/// Whether writing `len` more bytes to a file of `size` bytes crosses
+/// `max_bytes`.  A line is never split, and an empty file always takes it.
+pub fn exceeds_limit(size: u64, len: usize, max_bytes: Option<u64>) -> bool {
+    max_bytes.is_some_and(|max| size > 0 && size + len as u64 > max)
+}
+
+/// Append-only log file that rotates itself past `max_bytes`.
+pub struct LogFile {
+    path: PathBuf,
+    file: File,
+    /// Current size of `file`.
+    size: u64,
+    max_bytes: Option<u64>,
+    keep: usize,
+}
+
+impl LogFile {
+    /// Open `path` for appending; `max_bytes` of `None` never rotates.
+    pub fn open(path: &Path, max_bytes: Option<u64>, keep: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+            keep,
+        })
+    }
+
+    /// Append `line`, rotating first if it would cross `max_bytes`.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if exceeds_limit(self.size, line.len(), self.max_bytes) {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Flush buffered output to disk.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Shift the rotated files up by one and start an empty log file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep == 0 {
+            remove_if_exists(&self.path)?;
+        } else {
+            remove_if_exists(&rotated_path(&self.path, self.keep))?;
+            for n in (1..self.keep).rev() {
+                rename_if_exists(
+                    &rotated_path(&self.path, n),
+                    &rotated_path(&self.path, n + 1),
+                )?;
+            }
+            rename_if_exists(&self.path, &rotated_path(&self.path, 1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
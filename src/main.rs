@@ -2,9 +2,8 @@
 //!
 //! CLI entry point.
 
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::sync::Mutex;
 use std::sync::atomic::Ordering;
@@ -13,14 +12,28 @@ use std::time::SystemTime;
 use clap::Parser;
 use log::{Level, LevelFilter, Log, Metadata, Record};
 
-use bodgestr::manager::{GestureManager, list_touch_devices};
+use bodgestr::config::{default_config_path, diff_configs, parse_config_file, target_level};
+use bodgestr::event::format_bindings;
+use bodgestr::logfile::LogFile;
+use bodgestr::manager::{
+    GestureManager, calibrate_device, install_dump_handler, list_touch_devices,
+    parse_threshold_override, run_single_gesture,
+};
 
 #[derive(Parser)]
-#[command(name = "bodgestr", about = "Gesture recognition for touchscreens")]
+#[command(
+    name = "bodgestr",
+    version,
+    about = "Gesture recognition for touchscreens"
+)]
 struct Cli {
-    /// Path to configuration file
-    #[arg(default_value = "/etc/bodgestr/gestures.toml")]
-    config: PathBuf,
+    /// Path to configuration file (default: /etc/bodgestr/gestures.toml, or
+    /// bodgestr/gestures.toml under $XDG_CONFIG_HOME or ~/.config)
+    config: Option<PathBuf>,
+
+    /// Print the version, compiled-in features and supported gestures and exit
+    #[arg(long)]
+    build_info: bool,
 
     /// Enable verbose logging
     #[arg(short, long)]
@@ -29,17 +42,62 @@ struct Cli {
     /// List available touchscreen devices and exit
     #[arg(short, long)]
     list_devices: bool,
+
+    /// Print recognized gestures with kinematics instead of running actions
+    #[arg(short, long)]
+    monitor: bool,
+
+    /// Print a config stub for each recognized gesture that has no binding
+    /// yet, and a tally of them on exit, instead of running actions
+    #[arg(long, conflicts_with = "monitor")]
+    learn: bool,
+
+    /// Record sample gestures on a device (event node, path or USB ID) and
+    /// print suggested thresholds
+    #[arg(long, value_name = "DEVICE")]
+    calibrate: Option<String>,
+
+    /// Print the fully resolved configuration of FILE and exit
+    #[arg(long, value_name = "FILE")]
+    dump_config: Option<PathBuf>,
+
+    /// Dump the configuration as JSON instead of TOML
+    #[arg(long, requires = "dump_config")]
+    json: bool,
+
+    /// Print the settings, devices and bindings that differ between the
+    /// resolved OLD and NEW configurations and exit
+    #[arg(long, num_args = 2, value_names = ["OLD", "NEW"])]
+    diff: Option<Vec<PathBuf>>,
+
+    /// Print each device's resolved gesture bindings from FILE and exit
+    #[arg(long, value_name = "FILE")]
+    show_bindings: Option<PathBuf>,
+
+    /// Run the action bound to GESTURE on DEVICE (ID, USB ID or event path)
+    /// once and exit
+    #[arg(long, num_args = 2, value_names = ["DEVICE", "GESTURE"])]
+    run_gesture: Option<Vec<String>>,
+
+    /// Override a threshold for all devices, e.g. swipe_time_max=1.2
+    /// (repeatable)
+    #[arg(long, value_name = "NAME=VALUE", value_parser = parse_threshold_override)]
+    set_threshold: Vec<(String, f64)>,
 }
 
 /// Simple logger that writes to stderr and optionally to a log file.
 struct BodgestrLogger {
     level: LevelFilter,
-    file: Option<Mutex<std::fs::File>>,
+    /// Per-module overrides of `level`, from `[global] log_targets`.
+    targets: BTreeMap<String, LevelFilter>,
+    file: Option<Mutex<LogFile>>,
 }
 
 impl Log for BodgestrLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level && metadata.target().starts_with("bodgestr")
+        let target = metadata.target();
+        target.starts_with("bodgestr")
+            && metadata.level() <= target_level(&self.targets, self.level, target)
     }
 
     fn log(&self, record: &Record) {
@@ -66,7 +124,7 @@ impl Log for BodgestrLogger {
         // Write to log file if configured
         if let Some(ref file_mutex) = self.file {
             if let Ok(mut f) = file_mutex.lock() {
-                let _ = f.write_all(line.as_bytes());
+                let _ = f.write_line(&line);
             }
         }
     }
@@ -80,16 +138,104 @@ impl Log for BodgestrLogger {
     }
 }
 
+/// Print the resolved (merged) configuration as TOML or JSON.
+fn dump_config(path: &Path, json: bool) -> ExitCode {
+    let config = match parse_config_file(path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let dumped = if json {
+        serde_json::to_string_pretty(&config).map_err(|e| e.to_string())
+    } else {
+        toml::to_string_pretty(&config).map_err(|e| e.to_string())
+    };
+    match dumped {
+        Ok(text) => {
+            println!("{text}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: cannot serialize config: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print every device's resolved gesture bindings.
+fn show_bindings(path: &Path) -> ExitCode {
+    match parse_config_file(path) {
+        Ok(config) => {
+            print!("{}", format_bindings(&config));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print what changed between two resolved configurations.
+fn diff(old: &Path, new: &Path) -> ExitCode {
+    let parsed = parse_config_file(old).and_then(|old| Ok((old, parse_config_file(new)?)));
+    match parsed {
+        Ok((old, new)) => {
+            let changes = diff_configs(&old, &new);
+            if changes.is_empty() {
+                println!("No differences");
+            }
+            for change in changes {
+                println!("{change}");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    let config_path = cli.config.clone().unwrap_or_else(default_config_path);
+
+    if cli.build_info {
+        print!("{}", bodgestr::build_info());
+        return ExitCode::SUCCESS;
+    }
 
     if cli.list_devices {
         return list_touch_devices();
     }
 
+    if let Some(device) = &cli.calibrate {
+        return calibrate_device(device);
+    }
+
+    if let Some(path) = &cli.dump_config {
+        return dump_config(path, cli.json);
+    }
+
+    if let Some([old, new]) = cli.diff.as_deref() {
+        return diff(old, new);
+    }
+
+    if let Some(path) = &cli.show_bindings {
+        return show_bindings(path);
+    }
+
+    if let Some([device, gesture]) = cli.run_gesture.as_deref() {
+        return run_single_gesture(&config_path, device, gesture);
+    }
+
     // Parse config first (before logger init) so we can read the configured log level.
-    let mut manager = match GestureManager::new(&cli.config) {
-        Ok(m) => m,
+    let overrides: BTreeMap<String, f64> = cli.set_threshold.iter().cloned().collect();
+    let mut manager = match GestureManager::new(&config_path) {
+        Ok(m) => m.with_threshold_overrides(&overrides),
         Err(e) => {
             eprintln!(
                 "Error: {e}\n\n\
@@ -110,8 +256,9 @@ fn main() -> ExitCode {
             .unwrap_or(LevelFilter::Info)
     };
 
+    let (max_bytes, keep) = manager.config_log_rotation();
     let log_file = manager.config_log_file().and_then(|path| {
-        match OpenOptions::new().create(true).append(true).open(path) {
+        match LogFile::open(Path::new(path), max_bytes, keep) {
             Ok(file) => Some(Mutex::new(file)),
             Err(e) => {
                 eprintln!("Warning: cannot open log file '{path}': {e}");
@@ -120,12 +267,16 @@ fn main() -> ExitCode {
         }
     });
 
+    let targets = manager.config_log_targets().clone();
+    // The global filter must let through the most verbose target.
+    let max_level = targets.values().copied().fold(log_level, Ord::max);
     let logger = BodgestrLogger {
         level: log_level,
+        targets,
         file: log_file,
     };
     log::set_boxed_logger(Box::new(logger)).expect("Failed to set logger");
-    log::set_max_level(log_level);
+    log::set_max_level(max_level);
 
     // Set up signal handling for graceful shutdown
     let running = manager.running_flag();
@@ -133,9 +284,18 @@ fn main() -> ExitCode {
         running.store(false, Ordering::Relaxed);
     })
     .expect("Error setting Ctrl-C handler");
+    if let Err(e) = install_dump_handler() {
+        log::warn!("Cannot handle SIGUSR2 for config dumps: {e}");
+    }
 
-    log::info!("Loading configuration from: {}", cli.config.display());
-    manager.start();
+    log::info!("Loading configuration from: {}", config_path.display());
+    if cli.monitor {
+        manager.monitor();
+    } else if cli.learn {
+        manager.learn();
+    } else {
+        manager.start();
+    }
 
     ExitCode::SUCCESS
 }
@@ -1,29 +1,109 @@
 //! Multi-device gesture manager and device discovery (I/O layer).
 //!
 //! Pure event-processing logic lives in [`crate::event`].
-use std::process::{Command, ExitCode};
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
-use std::time::Duration;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
 
-use evdev::{AbsoluteAxisType, Device};
-use log::{debug, error, info, warn};
+use chrono::{Local, Timelike};
+use evdev::{AbsoluteAxisType, AttributeSetRef, Device, InputEvent, Key};
+use log::{Level, LevelFilter, debug, error, info, log, warn};
+use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal, sigaction};
 
-use crate::config::{AppConfig, BodgestrError, DeviceConfig, parse_config_file};
-use crate::recognizer::{GestureRecognizer, GestureType};
+use crate::config::{
+    AppConfig, BodgestrError, CoordinateSpace, DeviceConfig, DeviceLocator, PrimaryFinger,
+    THRESHOLD_NAMES, ValidatedThresholds, YAxis, parse_config_file,
+};
+use crate::control::{self, RuntimeBindings};
+use crate::fifo::FifoWriter;
+use crate::focus::{FocusCache, FocusQuery, focus_allows};
+use crate::http::HttpServer;
+use crate::journal::JournalSender;
+use crate::mqtt::MqttPublisher;
+use crate::recognizer::{GestureDetails, GestureRecognizer, GestureType, TouchPoint};
+use crate::stats::GestureStats;
+use crate::uinput::{VirtualInput, wheel_axis};
 
 // Re-export event symbols so existing `use bodgestr::manager::*` keeps working.
 pub use crate::event::{
-    TouchEvent, classify_event, parse_usb_id, process_touch_events, resolve_action,
+    ReleaseSignal, TouchEvent, WakeGate, binds_double_tap, classify_event, is_record_only,
+    max_tap_count, parse_usb_id, process_batch, process_touch_events,
+    process_touch_events_detailed, resolve_action, resolve_finger_action, resolve_key,
+    resolve_scroll, skip_initial_gestures, tap_count_matches, within_active_hours,
 };
 
+/// What a device loop does with recognized gestures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunMode {
+    /// Execute the configured actions.
+    Normal,
+    /// Print gestures with kinematics to stdout; never execute actions.
+    Monitor,
+    /// Print binding stubs for unbound gestures; never execute actions.
+    Learn,
+}
+
+/// State shared by every device loop for the lifetime of a run.
+#[derive(Clone)]
+struct LoopContext {
+    running: Arc<AtomicBool>,
+    /// Opens configured devices, at start and on reconnect or restart.
+    open: Arc<DeviceOpener>,
+    /// Longest a device loop waits for input before checking `running` and
+    /// beating its heartbeat.
+    poll_interval: Duration,
+    mode: RunMode,
+    stats: Option<Arc<Mutex<GestureStats>>>,
+    uinput: Option<Arc<Mutex<VirtualInput>>>,
+    /// Runs actions, wrapped for `serialize_actions` and `action_queue_capacity`.
+    executor: Arc<dyn ActionExecutor>,
+    /// Broker connection when `[global.mqtt]` is configured.
+    mqtt: Option<MqttPublisher>,
+    /// Structured journal entries for dispatched gestures, under systemd.
+    journal: Option<Arc<JournalSender>>,
+    /// Named pipe from `[global] fifo_path`.
+    fifo: Option<Arc<FifoWriter>>,
+    /// Live gesture stream at `[global] http_addr`.
+    http: Option<Arc<HttpServer>>,
+    /// Actions bound through `[global] control_socket`.
+    bindings: Option<Arc<RuntimeBindings>>,
+    /// `[global] instant_tap`: taps never wait for a double tap.
+    instant_tap: bool,
+    /// `[global] pass_trajectory`: actions read the touch points on stdin.
+    pass_trajectory: bool,
+    /// Gestures no device recognizes, from `[global] disabled_gestures`.
+    disabled_gestures: Vec<GestureType>,
+    /// Unbound gestures tallied in learn mode.
+    learned: Option<Arc<Mutex<LearnedGestures>>>,
+    /// `[global] feedback_command`, run through the unwrapped executor so a
+    /// beep never waits behind queued or serialized actions.
+    feedback: Option<(String, Arc<dyn ActionExecutor>)>,
+    /// Worker spacing this device's actions by `post_action_delay_ms`; set
+    /// by each device loop that configures a delay.
+    pacer: Option<ActionPacer>,
+}
+
 // -- GestureManager (top-level orchestrator) ------------------
 
 /// Manages gesture recognition across multiple touch devices.
 pub struct GestureManager {
     config: AppConfig,
     running: Arc<AtomicBool>,
+    executor: Arc<dyn ActionExecutor>,
+    /// Opens configured devices; their evdev nodes unless replaced by
+    /// [`with_device_opener`](Self::with_device_opener).
+    open: Arc<DeviceOpener>,
+    /// Unbound gestures tallied while [`learn`](Self::learn) runs.
+    learned: Option<Arc<Mutex<LearnedGestures>>>,
 }
 
 impl GestureManager {
@@ -31,48 +111,274 @@ impl GestureManager {
         Ok(Self {
             config: parse_config_file(config_path.as_ref())?,
             running: Arc::new(AtomicBool::new(false)),
+            executor: Arc::new(ShellExecutor::default()),
+            open: Arc::new(|device_id: &str, config: &DeviceConfig| {
+                find_device(device_id, config)
+                    .map(|device| Box::new(device) as Box<dyn TouchSource>)
+            }),
+            learned: None,
         })
     }
 
-    /// Start listening to all configured devices.
+    /// Run actions through `executor` instead of the default [`ShellExecutor`].
+    pub fn with_executor(mut self, executor: impl ActionExecutor + 'static) -> Self {
+        self.executor = Arc::new(executor);
+        self
+    }
+
+    /// Read devices from the sources `open` returns instead of their evdev
+    /// nodes.
+    pub fn with_device_opener(
+        mut self,
+        open: impl Fn(&str, &DeviceConfig) -> Option<Box<dyn TouchSource>> + Send + Sync + 'static,
+    ) -> Self {
+        self.open = Arc::new(open);
+        self
+    }
+
+    /// Override thresholds of every device (and the global ones used when
+    /// none is configured), as from `--set-threshold`.
+    pub fn with_threshold_overrides(mut self, overrides: &BTreeMap<String, f64>) -> Self {
+        for device in self.config.devices.values_mut() {
+            apply_threshold_overrides(&mut device.thresholds, overrides);
+            let oriented = [
+                &mut device.portrait_thresholds,
+                &mut device.landscape_thresholds,
+            ];
+            for thresholds in oriented.into_iter().flatten() {
+                apply_threshold_overrides(thresholds, overrides);
+            }
+        }
+        if let Some(thresholds) = &mut self.config.global_thresholds {
+            apply_threshold_overrides(thresholds, overrides);
+        }
+        self
+    }
+
+    /// Start listening to all configured devices; blocks until they stop.
     pub fn start(&mut self) {
-        if self.config.devices.is_empty() {
+        self.start_background().join();
+    }
+
+    /// Start listening to all configured devices on background threads and
+    /// return immediately, leaving the calling thread free.
+    pub fn start_background(&mut self) -> ManagerHandle {
+        self.spawn(RunMode::Normal)
+    }
+
+    /// Print every recognized gesture with its kinematics instead of running
+    /// actions.  Monitors all configured devices, or - if none are configured -
+    /// every touch device using the global thresholds.
+    pub fn monitor(&mut self) {
+        println!("bodgestr: monitor mode, actions disabled");
+        self.spawn(RunMode::Monitor).join();
+    }
+
+    /// Like [`monitor`](Self::monitor), but print a ready-to-paste binding
+    /// stub the first time each unbound gesture is recognized, and how often
+    /// each one occurred once the devices stop.
+    pub fn learn(&mut self) {
+        println!("bodgestr: learn mode, actions disabled");
+        let learned = Arc::new(Mutex::new(LearnedGestures::default()));
+        self.learned = Some(Arc::clone(&learned));
+        self.spawn(RunMode::Learn).join();
+        self.learned = None;
+        if let Ok(learned) = learned.lock() {
+            print!("{}", learned.summary());
+        }
+    }
+
+    /// Spawn a thread per found device (plus the stats flusher).  The handle
+    /// is idle - `join` returns at once - if no device could be started.
+    fn spawn(&mut self, mode: RunMode) -> ManagerHandle {
+        let mut handle = ManagerHandle {
+            running: Arc::clone(&self.running),
+            devices: Vec::new(),
+            flusher: None,
+            dumper: None,
+        };
+
+        let devices = if let Some(template) = &self.config.auto_device {
+            expand_auto_devices(
+                &self.config.devices,
+                template,
+                &found_touch_devices(template.single_touch),
+            )
+        } else if mode != RunMode::Normal && self.config.devices.is_empty() {
+            let Some(thresholds) = &self.config.global_thresholds else {
+                error!("No devices configured and global thresholds are incomplete");
+                return handle;
+            };
+            discover_touch_devices(thresholds, self.config.y_axis)
+        } else {
+            self.config.devices.clone()
+        };
+
+        if devices.is_empty() {
             error!("No devices configured");
-            return;
+            log_permission_denied();
+            return handle;
         }
 
         self.running.store(true, Ordering::Relaxed);
         info!("Starting gesture manager");
 
-        let mut handles = Vec::new();
-
-        for (device_id, device_config) in &self.config.devices {
-            if let Some(device) = find_device(device_id, device_config) {
-                let device_id = device_id.clone();
-                let config = device_config.clone();
-                let running = Arc::clone(&self.running);
-
-                handles.push(
-                    thread::Builder::new()
-                        .name(format!("gesture-{device_id}"))
-                        .spawn(move || {
-                            run_device_loop(&device_id, device, &config, &running);
-                        })
-                        .expect("Failed to spawn device thread"),
-                );
+        if mode == RunMode::Normal && self.config.selftest {
+            for (device_id, config) in &devices {
+                if selftest(&config.thresholds) {
+                    info!("Self-test {device_id}: PASS");
+                } else {
+                    error!("Self-test {device_id}: FAIL - thresholds cannot recognize a swipe");
+                }
+            }
+        }
+
+        // Monitor and learn mode are diagnostic only and must not skew persisted counts.
+        let stats_file = match mode {
+            RunMode::Normal => self.config.stats_file.as_deref().map(PathBuf::from),
+            RunMode::Monitor | RunMode::Learn => None,
+        };
+        // Without a stats file, counts are kept in memory for `SIGUSR2` dumps.
+        let stats = (mode == RunMode::Normal).then(|| {
+            let loaded = stats_file.as_deref().map(|path| {
+                GestureStats::load(path).unwrap_or_else(|e| {
+                    warn!("{e} - starting with empty stats");
+                    GestureStats::default()
+                })
+            });
+            Arc::new(Mutex::new(loaded.unwrap_or_default()))
+        });
+
+        let feedback = match mode {
+            RunMode::Normal => self
+                .config
+                .feedback_command
+                .clone()
+                .map(|command| (command, Arc::clone(&self.executor))),
+            RunMode::Monitor | RunMode::Learn => None,
+        };
+        let mut executor = Arc::clone(&self.executor);
+        if self.config.serialize_actions {
+            executor = Arc::new(SerializedExecutor {
+                inner: executor,
+                lock: Arc::new(Mutex::new(())),
+            });
+        }
+        if let (RunMode::Normal, Some(capacity)) = (mode, self.config.action_queue_capacity) {
+            let inner = executor;
+            executor = Arc::new(ActionQueue::start(capacity, move |job| {
+                job.run(inner.as_ref(), true);
+            }));
+        }
+
+        let ctx = LoopContext {
+            running: Arc::clone(&self.running),
+            open: Arc::clone(&self.open),
+            poll_interval: self
+                .config
+                .watchdog_timeout
+                .map_or(DEVICE_POLL_INTERVAL, |timeout| {
+                    (timeout / 2).min(DEVICE_POLL_INTERVAL)
+                }),
+            mode,
+            stats: stats.clone(),
+            uinput: match mode {
+                RunMode::Normal => open_uinput(&devices),
+                RunMode::Monitor | RunMode::Learn => None,
+            },
+            executor,
+            mqtt: match mode {
+                RunMode::Normal => self.config.mqtt.as_ref().map(MqttPublisher::connect),
+                RunMode::Monitor | RunMode::Learn => None,
+            },
+            journal: match mode {
+                RunMode::Normal => JournalSender::connect().map(Arc::new),
+                RunMode::Monitor | RunMode::Learn => None,
+            },
+            fifo: match mode {
+                RunMode::Normal => self.config.fifo_path.as_deref().and_then(open_fifo),
+                RunMode::Monitor | RunMode::Learn => None,
+            },
+            http: match mode {
+                RunMode::Normal => self
+                    .config
+                    .http_addr
+                    .and_then(|addr| start_http(addr, stats.clone())),
+                RunMode::Monitor | RunMode::Learn => None,
+            },
+            bindings: match mode {
+                RunMode::Normal => self
+                    .config
+                    .control_socket
+                    .as_deref()
+                    .and_then(|path| open_control_socket(path, devices.keys().cloned())),
+                RunMode::Monitor | RunMode::Learn => None,
+            },
+            instant_tap: self.config.instant_tap,
+            pass_trajectory: self.config.pass_trajectory,
+            disabled_gestures: self.config.disabled_gestures.clone(),
+            learned: self.learned.clone(),
+            feedback,
+            pacer: None,
+        };
+
+        let mut threads = Vec::new();
+        let mut found = HashMap::new();
+        for (device_id, device_config) in devices {
+            if let Some(device) = (ctx.open)(&device_id, &device_config) {
+                threads.push(spawn_device_thread(
+                    device_id.clone(),
+                    device,
+                    device_config.clone(),
+                    &ctx,
+                ));
+                found.insert(device_id, device_config);
             } else {
                 warn!("Device not found: {device_id}");
             }
         }
 
-        if handles.is_empty() {
+        if threads.is_empty() {
             error!("No devices found, exiting");
-            return;
+            log_permission_denied();
+            self.running.store(false, Ordering::Relaxed);
+            return handle;
         }
 
-        for handle in handles {
-            let _ = handle.join();
+        match self.config.watchdog_timeout {
+            // The watchdog owns the device threads and outlives them.
+            Some(timeout) => handle.devices.push(
+                thread::Builder::new()
+                    .name("watchdog".into())
+                    .spawn(move || watchdog_loop(threads, timeout, &ctx))
+                    .expect("Failed to spawn watchdog thread"),
+            ),
+            None => handle
+                .devices
+                .extend(threads.into_iter().map(|worker| worker.handle)),
         }
+
+        let active = AppConfig {
+            devices: found,
+            ..self.config.clone()
+        };
+        let dump_stats = stats.clone();
+        let running = Arc::clone(&self.running);
+        handle.dumper = Some(
+            thread::Builder::new()
+                .name("config-dump".into())
+                .spawn(move || dump_loop(&active, dump_stats.as_deref(), &running))
+                .expect("Failed to spawn config dump thread"),
+        );
+
+        handle.flusher = stats_file.zip(stats).map(|(path, stats)| {
+            let running = Arc::clone(&self.running);
+            thread::Builder::new()
+                .name("stats-flush".into())
+                .spawn(move || stats_flush_loop(&path, &stats, &running))
+                .expect("Failed to spawn stats thread")
+        });
+        handle
     }
 
     /// Stop listening to devices.
@@ -92,43 +398,455 @@ impl GestureManager {
         &self.config.log_level
     }
 
+    /// Get the per-module log level overrides from the parsed configuration.
+    pub fn config_log_targets(&self) -> &BTreeMap<String, LevelFilter> {
+        &self.config.log_targets
+    }
+
     /// Get the optional log file path from the parsed configuration.
     pub fn config_log_file(&self) -> Option<&str> {
         self.config.log_file.as_deref()
     }
+
+    /// Get the log file size limit and number of rotated files to keep.
+    pub fn config_log_rotation(&self) -> (Option<u64>, usize) {
+        (self.config.log_max_bytes, self.config.log_rotate_keep)
+    }
+}
+
+/// Threads of a manager started with
+/// [`start_background`](GestureManager::start_background).
+pub struct ManagerHandle {
+    running: Arc<AtomicBool>,
+    devices: Vec<JoinHandle<()>>,
+    flusher: Option<JoinHandle<()>>,
+    /// Logs [`config_dump`] on `SIGUSR2`.
+    dumper: Option<JoinHandle<()>>,
+}
+
+impl ManagerHandle {
+    /// Ask all device loops to stop; returns without waiting for them.
+    /// An idle loop notices within half a second.
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+        info!("Gesture manager stopped");
+    }
+
+    /// Wait for every device thread to exit, then let the stats flusher
+    /// write its final snapshot.
+    pub fn join(self) {
+        for device in self.devices {
+            let _ = device.join();
+        }
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(flusher) = self.flusher {
+            let _ = flusher.join();
+        }
+        if let Some(dumper) = self.dumper {
+            let _ = dumper.join();
+        }
+    }
 }
 
 // -- Device I/O -----------------------------------------------
 
-/// Check if a device has multi-touch capabilities.
-fn is_touch_device(device: &Device) -> bool {
+/// Opens the [`TouchSource`] of a configured device; `None` if it is missing.
+pub type DeviceOpener = dyn Fn(&str, &DeviceConfig) -> Option<Box<dyn TouchSource>> + Send + Sync;
+
+/// A touch device as a device loop reads it: an evdev [`Device`], or a stand-in
+/// installed with [`GestureManager::with_device_opener`].
+pub trait TouchSource: Send {
+    /// The X and Y ranges of the position axes.
+    fn axis_ranges(&self, single_touch: bool) -> std::io::Result<((f64, f64), (f64, f64))>;
+
+    /// The absolute axes the device reports.
+    fn supported_absolute_axes(&self) -> Option<&AttributeSetRef<AbsoluteAxisType>>;
+
+    /// The keys and buttons the device reports.
+    fn supported_keys(&self) -> Option<&AttributeSetRef<Key>>;
+
+    /// Wait up to `timeout` for the device to have events; `false` on timeout.
+    fn wait_readable(&self, timeout: Duration) -> bool;
+
+    /// Append the next batch of events to `events`, blocking until it arrives.
+    fn fetch_events(&mut self, events: &mut Vec<InputEvent>) -> std::io::Result<()>;
+}
+
+impl TouchSource for Device {
+    fn axis_ranges(&self, single_touch: bool) -> std::io::Result<((f64, f64), (f64, f64))> {
+        let abs = self.get_abs_state()?;
+        let (x_axis, y_axis) = position_axes(single_touch);
+        let x = &abs[x_axis.0 as usize];
+        let y = &abs[y_axis.0 as usize];
+        Ok((
+            (x.minimum as f64, x.maximum as f64),
+            (y.minimum as f64, y.maximum as f64),
+        ))
+    }
+
+    fn supported_absolute_axes(&self) -> Option<&AttributeSetRef<AbsoluteAxisType>> {
+        Device::supported_absolute_axes(self)
+    }
+
+    fn supported_keys(&self) -> Option<&AttributeSetRef<Key>> {
+        Device::supported_keys(self)
+    }
+
+    /// A failed wait counts as readable, so the following read reports the error.
+    fn wait_readable(&self, timeout: Duration) -> bool {
+        use std::os::fd::{AsRawFd, BorrowedFd};
+
+        use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+
+        // SAFETY: the descriptor belongs to `self`, which outlives the borrow.
+        let fd = unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) };
+        let millis = u16::try_from(timeout.as_millis()).unwrap_or(u16::MAX);
+        let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+        poll(&mut fds, PollTimeout::from(millis)).map_or(true, |ready| ready > 0)
+    }
+
+    fn fetch_events(&mut self, events: &mut Vec<InputEvent>) -> std::io::Result<()> {
+        events.extend(Device::fetch_events(self)?);
+        Ok(())
+    }
+}
+
+/// Check if a device has multi-touch capabilities - or, with
+/// `single_touch`, at least the legacy `ABS_X`/`ABS_Y` axes.
+fn is_touch_device(device: &Device, single_touch: bool) -> bool {
     let Some(abs_axes) = device.supported_absolute_axes() else {
         return false;
     };
-    abs_axes.contains(AbsoluteAxisType::ABS_MT_POSITION_X)
-        && abs_axes.contains(AbsoluteAxisType::ABS_MT_POSITION_Y)
+    let (x, y) = position_axes(single_touch);
+    abs_axes.contains(x) && abs_axes.contains(y)
+}
+
+/// The axes a device reports contact positions on.
+fn position_axes(single_touch: bool) -> (AbsoluteAxisType, AbsoluteAxisType) {
+    if single_touch {
+        (AbsoluteAxisType::ABS_X, AbsoluteAxisType::ABS_Y)
+    } else {
+        (
+            AbsoluteAxisType::ABS_MT_POSITION_X,
+            AbsoluteAxisType::ABS_MT_POSITION_Y,
+        )
+    }
+}
+
+/// Suggested fix for an error opening an input device node, by error kind.
+pub fn device_access_hint(kind: ErrorKind) -> Option<&'static str> {
+    match kind {
+        ErrorKind::PermissionDenied => Some(
+            "add the user to the 'input' group (sudo usermod -aG input $USER, then log in \
+             again) or run as root",
+        ),
+        _ => None,
+    }
+}
+
+/// Explain that `denied` event nodes could not be opened, if any.
+pub fn permission_denied_message(denied: usize) -> Option<String> {
+    let hint = device_access_hint(ErrorKind::PermissionDenied)?;
+    (denied > 0).then(|| {
+        format!("{denied} input device(s) under /dev/input could not be opened: permission denied - {hint}")
+    })
+}
+
+/// Count event nodes that exist but cannot be opened for lack of permission;
+/// `evdev::enumerate` skips these silently.
+fn permission_denied_nodes() -> usize {
+    let Ok(entries) = std::fs::read_dir("/dev/input") else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+        .filter(|entry| {
+            File::open(entry.path()).is_err_and(|e| e.kind() == ErrorKind::PermissionDenied)
+        })
+        .count()
+}
+
+/// Log why devices may be missing when some event nodes are unreadable.
+fn log_permission_denied() {
+    if let Some(message) = permission_denied_message(permission_denied_nodes()) {
+        error!("{message}");
+    }
+}
+
+/// Build an ad-hoc config for every attached touch device, keyed by its
+/// event node name (e.g. `event5`).  Used by monitor mode when the config
+/// file registers no devices.
+fn discover_touch_devices(
+    thresholds: &ValidatedThresholds,
+    y_axis: YAxis,
+) -> HashMap<String, DeviceConfig> {
+    found_touch_devices(false)
+        .into_iter()
+        .map(|found| {
+            let config = DeviceConfig {
+                device_usb_id: found.usb_id.clone(),
+                device_path: Some(found.path.clone()),
+                device_phys: None,
+                single_touch: false,
+                coordinate_space: CoordinateSpace::default(),
+                skip_initial_touches: 0,
+                y_axis,
+                primary_finger: PrimaryFinger::default(),
+                action_retries: 0,
+                wake_on_idle: None,
+                wake_action: None,
+                long_press_drag: false,
+                corner_gestures: false,
+                swipe_then_tap_ms: None,
+                hold_before_swipe_ms: None,
+                normalize_coordinates: false,
+                require_both_axes: false,
+                suppress_repeat: false,
+                swipe_once_per_contact: false,
+                max_track_points: None,
+                max_fingers: None,
+                id_merge_ms: None,
+                stale_touch_timeout_ms: None,
+                release_timeout_ms: None,
+                max_events_per_sec: None,
+                post_action_delay_ms: None,
+                reference_resolution: None,
+                only_when_class: None,
+                active_hours: None,
+                gestures: HashMap::new(),
+                thresholds: thresholds.clone(),
+                portrait_thresholds: None,
+                landscape_thresholds: None,
+            };
+            (found.device_id(), config)
+        })
+        .collect()
+}
+
+/// An attached touch device, as enumerated.
+#[derive(Debug, Clone)]
+pub struct FoundDevice {
+    pub path: PathBuf,
+    /// `vendor:product` in lowercase hex.
+    pub usb_id: String,
+    pub phys: Option<String>,
+    /// Whether it reports multi-touch positions; if not, only a
+    /// `single_touch` config can read it.
+    pub multi_touch: bool,
+}
+
+impl FoundDevice {
+    /// Event node name (e.g. `event5`), used as the id of an ad-hoc config.
+    pub fn device_id(&self) -> String {
+        self.path.file_name().map_or_else(
+            || self.path.display().to_string(),
+            |n| n.to_string_lossy().into(),
+        )
+    }
+
+    /// Whether the configured device `config` refers to this one.
+    fn is_claimed_by(&self, config: &DeviceConfig) -> bool {
+        match config.locator() {
+            DeviceLocator::Path(path) => path == self.path,
+            DeviceLocator::Phys(phys) => self.phys.as_deref() == Some(phys),
+            DeviceLocator::UsbId(usb_id) => {
+                parse_usb_id(usb_id).is_some() && parse_usb_id(usb_id) == parse_usb_id(&self.usb_id)
+            }
+        }
+    }
+}
+
+/// Every attached multi-touch device, plus those reporting only the legacy
+/// `ABS_X`/`ABS_Y` axes with `single_touch`.
+fn found_touch_devices(single_touch: bool) -> Vec<FoundDevice> {
+    evdev::enumerate()
+        .filter(|(_, device)| is_touch_device(device, single_touch))
+        .map(|(path, device)| FoundDevice {
+            multi_touch: is_touch_device(&device, false),
+            usb_id: format!(
+                "{:04x}:{:04x}",
+                device.input_id().vendor(),
+                device.input_id().product()
+            ),
+            phys: device.physical_path().map(str::to_string),
+            path,
+        })
+        .collect()
+}
+
+/// `configured` plus a copy of the `[device."*"]` `template` for each of
+/// the `found` devices no configured entry refers to, keyed by event node
+/// name and located by its path.  Devices without multi-touch axes are only
+/// added when the template is `single_touch`.
+pub fn expand_auto_devices(
+    configured: &HashMap<String, DeviceConfig>,
+    template: &DeviceConfig,
+    found: &[FoundDevice],
+) -> HashMap<String, DeviceConfig> {
+    let mut devices = configured.clone();
+    for device in found {
+        if !(device.multi_touch || template.single_touch)
+            || configured
+                .values()
+                .any(|config| device.is_claimed_by(config))
+        {
+            continue;
+        }
+        let config = DeviceConfig {
+            device_usb_id: device.usb_id.clone(),
+            device_path: Some(device.path.clone()),
+            device_phys: None,
+            ..template.clone()
+        };
+        devices.entry(device.device_id()).or_insert(config);
+    }
+    devices
+}
+
+/// Create the uinput device if any gesture binds a key or a scroll axis.
+fn open_uinput(devices: &HashMap<String, DeviceConfig>) -> Option<Arc<Mutex<VirtualInput>>> {
+    let bindings = || devices.values().flat_map(|d| d.gestures.values());
+    let keys: Vec<_> = bindings().filter_map(|g| g.key).collect();
+    let wheels: Vec<_> = bindings()
+        .filter_map(|g| g.scroll.map(wheel_axis))
+        .collect();
+    if keys.is_empty() && wheels.is_empty() {
+        return None;
+    }
+    match VirtualInput::open(keys, wheels) {
+        Ok(uinput) => {
+            info!("Created uinput virtual device");
+            Some(Arc::new(Mutex::new(uinput)))
+        }
+        Err(e) => {
+            error!("{e}; key and scroll bindings are disabled");
+            None
+        }
+    }
+}
+
+/// Create or reuse the `fifo_path` pipe.
+fn open_fifo(path: &Path) -> Option<Arc<FifoWriter>> {
+    match FifoWriter::create(path) {
+        Ok(fifo) => {
+            info!("Writing gestures to FIFO {}", path.display());
+            Some(Arc::new(fifo))
+        }
+        Err(e) => {
+            error!("Cannot use FIFO {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Serve live gestures and `stats` at `http_addr`.
+fn start_http(
+    addr: SocketAddr,
+    stats: Option<Arc<Mutex<GestureStats>>>,
+) -> Option<Arc<HttpServer>> {
+    HttpServer::start(addr, stats)
+        .map_err(|e| error!("Cannot serve HTTP on {addr}: {e}"))
+        .ok()
+}
+
+/// Listen on the `control_socket` for commands binding the running `devices`.
+fn open_control_socket(
+    path: &Path,
+    devices: impl IntoIterator<Item = String>,
+) -> Option<Arc<RuntimeBindings>> {
+    let bindings = Arc::new(RuntimeBindings::new(devices));
+    match control::listen(path, Arc::clone(&bindings)) {
+        Ok(()) => {
+            info!("Accepting commands on {}", path.display());
+            Some(bindings)
+        }
+        Err(e) => {
+            error!("Cannot listen on control socket {}: {e}", path.display());
+            None
+        }
+    }
 }
 
-/// Find a touchscreen device by USB vendor:product ID.
+/// Find a touchscreen device by its configured path, phys or USB ID.
 fn find_device(device_id: &str, config: &DeviceConfig) -> Option<Device> {
-    let Some((vendor, product)) = parse_usb_id(&config.device_usb_id) else {
+    match config.locator() {
+        DeviceLocator::Path(path) => open_device_path(device_id, path, config.single_touch),
+        DeviceLocator::Phys(_) | DeviceLocator::UsbId(_) => {
+            find_enumerated_device(device_id, config)
+        }
+    }
+}
+
+/// Open a device node directly and check that it is a touch device.
+fn open_device_path(device_id: &str, path: &Path, single_touch: bool) -> Option<Device> {
+    let device = match Device::open(path) {
+        Ok(device) => device,
+        Err(e) => {
+            let hint = device_access_hint(e.kind()).map_or_else(String::new, |h| format!(" - {h}"));
+            warn!(
+                "Device {device_id}: cannot open {}: {e}{hint}",
+                path.display()
+            );
+            return None;
+        }
+    };
+    if !is_touch_device(&device, single_touch) {
+        warn!(
+            "Device {device_id}: {} is not a {} device",
+            path.display(),
+            if single_touch { "touch" } else { "multi-touch" }
+        );
+        return None;
+    }
+    info!(
+        "Found device for {device_id} by path: {} ({})",
+        device.name().unwrap_or("unknown"),
+        path.display()
+    );
+    Some(device)
+}
+
+/// Whether an enumerated device with USB `vendor:product` ID `input_id`
+/// and physical path `phys` is the one `config` describes.
+///
+/// `device_phys` and `device_usb_id` must both match when both are set.
+/// An unparsable USB ID matches nothing.
+pub fn enumerated_device_matches(
+    config: &DeviceConfig,
+    input_id: (u16, u16),
+    phys: Option<&str>,
+) -> bool {
+    let usb_id_matches =
+        config.device_usb_id.is_empty() || parse_usb_id(&config.device_usb_id) == Some(input_id);
+    let phys_matches = config
+        .device_phys
+        .as_deref()
+        .is_none_or(|wanted| phys == Some(wanted));
+    usb_id_matches && phys_matches
+}
+
+/// Find a touchscreen device by phys and/or USB vendor:product ID.
+fn find_enumerated_device(device_id: &str, config: &DeviceConfig) -> Option<Device> {
+    if !config.device_usb_id.is_empty() && parse_usb_id(&config.device_usb_id).is_none() {
         warn!(
             "Device {device_id}: invalid USB ID format '{}' (expected vendor:product)",
             config.device_usb_id
         );
         return None;
-    };
+    }
+    let locator = config.locator();
 
     for (path, device) in evdev::enumerate() {
-        if !is_touch_device(&device) {
+        if !is_touch_device(&device, config.single_touch) {
             continue;
         }
         let id = device.input_id();
-        if id.vendor() == vendor && id.product() == product {
+        if enumerated_device_matches(config, (id.vendor(), id.product()), device.physical_path()) {
             info!(
-                "Found device for {} by USB ID {}: {} ({})",
+                "Found device for {} by {}: {} ({})",
                 device_id,
-                config.device_usb_id,
+                locator,
                 device.name().unwrap_or("unknown"),
                 path.display()
             );
@@ -136,68 +854,496 @@ fn find_device(device_id: &str, config: &DeviceConfig) -> Option<Device> {
         }
     }
 
-    warn!(
-        "Device {}: no touch device with USB ID {} found",
-        device_id, config.device_usb_id
-    );
+    warn!("Device {device_id}: no touch device with {locator} found");
+    log_permission_denied();
     None
 }
 
+/// Whether the device's node, phys or USB ID is currently present, without
+/// keeping it open.
+fn device_present(config: &DeviceConfig) -> bool {
+    match config.locator() {
+        DeviceLocator::Path(path) => path.exists(),
+        DeviceLocator::Phys(_) | DeviceLocator::UsbId(_) => {
+            evdev::enumerate().any(|(_, device)| {
+                let id = device.input_id();
+                is_touch_device(&device, config.single_touch)
+                    && enumerated_device_matches(
+                        config,
+                        (id.vendor(), id.product()),
+                        device.physical_path(),
+                    )
+            })
+        }
+    }
+}
+
+/// A device loop running on its own thread.
+struct DeviceThread {
+    device_id: String,
+    config: DeviceConfig,
+    heartbeat: Arc<Heartbeat>,
+    handle: JoinHandle<()>,
+}
+
+/// Start `run_device_loop` for an opened device on a new thread.
+fn spawn_device_thread(
+    device_id: String,
+    device: Box<dyn TouchSource>,
+    config: DeviceConfig,
+    ctx: &LoopContext,
+) -> DeviceThread {
+    let heartbeat = Arc::new(Heartbeat::default());
+    let handle = {
+        let (device_id, config) = (device_id.clone(), config.clone());
+        let (heartbeat, ctx) = (Arc::clone(&heartbeat), ctx.clone());
+        thread::Builder::new()
+            .name(format!("gesture-{device_id}"))
+            .spawn(move || run_device_loop(&device_id, device, &config, &ctx, &heartbeat))
+            .expect("Failed to spawn device thread")
+    };
+    DeviceThread {
+        device_id,
+        config,
+        heartbeat,
+        handle,
+    }
+}
+
 /// Initialize recognizer from device axis info and start the event loop.
 fn run_device_loop(
     device_id: &str,
-    mut device: Device,
+    mut device: Box<dyn TouchSource>,
     config: &DeviceConfig,
-    running: &Arc<AtomicBool>,
+    ctx: &LoopContext,
+    heartbeat: &Heartbeat,
 ) {
-    let abs = match device.get_abs_state() {
-        Ok(state) => state,
-        Err(e) => {
-            error!("Device {device_id} failed to get abs state: {e}");
-            return;
-        }
+    let ranges = config
+        .coordinate_space
+        .fixed_ranges()
+        .or_else(|| axis_ranges(device_id, device.as_ref(), config.single_touch));
+    let Some((x_range, y_range)) = ranges else {
+        return;
     };
 
-    let x = &abs[AbsoluteAxisType::ABS_MT_POSITION_X.0 as usize];
-    let y = &abs[AbsoluteAxisType::ABS_MT_POSITION_Y.0 as usize];
-
     info!(
-        "Started processing device: {device_id} (USB {})",
-        config.device_usb_id
+        "Started processing device: {device_id} ({})",
+        config.locator()
     );
     debug!(
         "  X range: {}..{}, Y range: {}..{}",
-        x.minimum, x.maximum, y.minimum, y.maximum
+        x_range.0, x_range.1, y_range.0, y_range.1
+    );
+
+    if config.only_when_class.is_some() && !cfg!(feature = "x11") {
+        warn!(
+            "Device {device_id}: only_when_class needs the 'x11' feature; \
+             its gestures will be ignored"
+        );
+    }
+    if ctx.instant_tap && binds_double_tap(&config.gestures, &ctx.disabled_gestures) {
+        warn!("Device {device_id}: double_tap is bound but never fires with instant_tap");
+    }
+
+    let base = config.thresholds_for(x_range, y_range);
+    let mut thresholds = base.clone();
+    let mut gesture_thresholds = config.gesture_thresholds(base);
+    if let Some(reference) = config.reference_resolution {
+        let factor = resolution_scale(reference, x_range, y_range);
+        debug!("  Scaling pixel thresholds by {factor:.3}");
+        thresholds = scale_px_thresholds(&thresholds, factor);
+        for (_, th) in &mut gesture_thresholds {
+            *th = scale_px_thresholds(th, factor);
+        }
+    }
+    let mut recognizer = GestureRecognizer::new(thresholds, x_range, y_range)
+        .with_gesture_thresholds(gesture_thresholds)
+        .with_y_axis(config.y_axis)
+        .with_primary_finger(config.primary_finger)
+        .with_long_press_drag(config.long_press_drag)
+        .with_corner_gestures(config.corner_gestures)
+        .with_swipe_then_tap(config.swipe_then_tap_ms.map(Duration::from_millis))
+        .with_hold_before_swipe(config.hold_before_swipe_ms.map(Duration::from_millis))
+        .with_normalized_coordinates(config.normalize_coordinates)
+        .with_require_both_axes(config.require_both_axes)
+        .with_suppress_repeat(config.suppress_repeat)
+        .with_swipe_once_per_contact(config.swipe_once_per_contact)
+        .with_tap_count_max(max_tap_count(&config.gestures))
+        // Monitor and learn mode show double taps whether or not they are
+        // bound, unless `instant_tap` rules them out everywhere.
+        .with_immediate_tap(
+            ctx.instant_tap
+                || (ctx.mode == RunMode::Normal
+                    && !binds_double_tap(&config.gestures, &ctx.disabled_gestures)),
+        )
+        .with_max_track_points(config.max_track_points)
+        .with_max_fingers(config.max_fingers)
+        .with_id_merge(config.id_merge_ms.map(Duration::from_millis))
+        .with_stale_touch_timeout(config.stale_touch_timeout_ms.map(Duration::from_millis))
+        .with_disabled_gestures(&ctx.disabled_gestures)
+        .with_touch_events(binds_touch_events(config))
+        .with_trajectory(ctx.pass_trajectory && ctx.mode == RunMode::Normal);
+
+    // Delayed actions fire from their own worker, so recognition never waits.
+    let paced;
+    let ctx = if ctx.mode == RunMode::Normal && config.paces_actions() {
+        paced = LoopContext {
+            pacer: Some(ActionPacer::start(device_id, Arc::clone(&ctx.executor))),
+            ..ctx.clone()
+        };
+        &paced
+    } else {
+        ctx
+    };
+
+    event_loop(
+        device_id,
+        &mut device,
+        &mut recognizer,
+        config,
+        ctx,
+        heartbeat,
     );
+}
+
+/// Whether the device binds `touch_down` or `touch_up`, which are only
+/// reported when something listens for them.
+fn binds_touch_events(config: &DeviceConfig) -> bool {
+    [GestureType::TouchDown, GestureType::TouchUp]
+        .into_iter()
+        .any(|gesture| {
+            let name: &str = gesture.into();
+            config.gestures.get(name).is_some_and(|gc| gc.enabled)
+        })
+}
+
+/// Factor from the `reference` (width, height) the pixel thresholds were
+/// written for to the device's actual axis spans, by diagonal so it does
+/// not depend on the orientation.
+pub fn resolution_scale(
+    [width, height]: [f64; 2],
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+) -> f64 {
+    (x_range.1 - x_range.0).hypot(y_range.1 - y_range.0) / width.hypot(height)
+}
+
+/// Multiply the thresholds measured in device units - tap, double-tap and
+/// minimum pinch distances - by `factor`.  Percentages, angles and times
+/// already scale with the panel.
+pub fn scale_px_thresholds(thresholds: &ValidatedThresholds, factor: f64) -> ValidatedThresholds {
+    ValidatedThresholds {
+        tap_distance_max: thresholds.tap_distance_max * factor,
+        double_tap_distance_max: thresholds.double_tap_distance_max * factor,
+        pinch_threshold_min_px: thresholds.pinch_threshold_min_px.map(|px| px * factor),
+        swipe_distance_min_px: thresholds.swipe_distance_min_px.map(|px| px * factor),
+        long_press_drift_max: thresholds.long_press_drift_max.map(|px| px * factor),
+        ..thresholds.clone()
+    }
+}
+
+/// Parse a `--set-threshold` argument of the form `name=value`, rejecting
+/// names that are not thresholds and values that are not finite numbers.
+pub fn parse_threshold_override(arg: &str) -> Result<(String, f64), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{arg}'"))?;
+    let name = name.trim();
+    if !THRESHOLD_NAMES.contains(&name) {
+        return Err(format!(
+            "unknown threshold '{name}' (expected one of: {})",
+            THRESHOLD_NAMES.join(", ")
+        ));
+    }
+    let value: f64 = value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|v: &f64| v.is_finite())
+        .ok_or_else(|| format!("invalid value '{}' for {name}", value.trim()))?;
+    Ok((name.to_string(), value))
+}
+
+/// Set each threshold named in `overrides` to its value.
+pub fn apply_threshold_overrides(
+    thresholds: &mut ValidatedThresholds,
+    overrides: &BTreeMap<String, f64>,
+) {
+    for (name, &value) in overrides {
+        if !thresholds.set(name, value) {
+            warn!("Ignoring override of unknown threshold '{name}'");
+        }
+    }
+}
+
+/// Read the X and Y coordinate ranges of a device's position axes.
+fn axis_ranges(
+    device_id: &str,
+    device: &dyn TouchSource,
+    single_touch: bool,
+) -> Option<((f64, f64), (f64, f64))> {
+    device
+        .axis_ranges(single_touch)
+        .inspect_err(|e| error!("Device {device_id} failed to get abs state: {e}"))
+        .ok()
+}
+
+/// Silence that ends a contact on devices reporting no release, unless
+/// `release_timeout_ms` is set.
+pub const DEFAULT_RELEASE_TIMEOUT: Duration = Duration::from_millis(150);
 
-    let mut recognizer = GestureRecognizer::new(
-        config.thresholds.clone(),
-        (x.minimum as f64, x.maximum as f64),
-        (y.minimum as f64, y.maximum as f64),
+/// How the device signals lifted contacts, logging a fallback.
+fn release_signal(
+    device_id: &str,
+    device: &dyn TouchSource,
+    config: &DeviceConfig,
+) -> ReleaseSignal {
+    let timeout = config
+        .release_timeout_ms
+        .map_or(DEFAULT_RELEASE_TIMEOUT, Duration::from_millis);
+    let release = ReleaseSignal::select(
+        device.supported_absolute_axes(),
+        device.supported_keys(),
+        config.single_touch,
+        timeout,
     );
+    match release {
+        ReleaseSignal::TrackingId => {}
+        ReleaseSignal::BtnTouch if config.single_touch => {}
+        ReleaseSignal::BtnTouch => {
+            info!("Device {device_id} reports no tracking IDs; contacts end on BTN_TOUCH release")
+        }
+        ReleaseSignal::Timeout(timeout) => warn!(
+            "Device {device_id} reports neither tracking IDs nor BTN_TOUCH; \
+             contacts end after {}ms without events",
+            timeout.as_millis()
+        ),
+    }
+    release
+}
+
+/// Longest a device loop blocks waiting for input, so it notices a stop
+/// request and, with `watchdog_timeout_secs`, keeps its heartbeat fresh.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Initial capacity of the event loop's reusable read buffers.
+const EVENT_BATCH_CAPACITY: usize = 64;
+
+/// Token bucket for `max_events_per_sec`: a device loop processes at most
+/// that many input events per second, with bursts up to one second's worth,
+/// and drops the rest so a panel in an event storm cannot pin the CPU.
+///
+/// Events are admitted or dropped a whole `SYN_REPORT` frame at a time, and
+/// releases always pass, so no contact is left down.
+#[derive(Debug)]
+pub struct EventRateLimit {
+    per_sec: f64,
+    tokens: f64,
+    refilled: Instant,
+    /// Events dropped since the last warning.
+    dropped: u64,
+    last_reported: Option<Instant>,
+}
+
+impl EventRateLimit {
+    /// Minimum time between two warnings about dropped events.
+    pub const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// A full bucket of `per_sec` events at `now`.
+    pub fn new(per_sec: u32, now: Instant) -> Self {
+        Self {
+            per_sec: f64::from(per_sec),
+            tokens: f64::from(per_sec),
+            refilled: now,
+            dropped: 0,
+            last_reported: None,
+        }
+    }
+
+    /// Whether a frame of `count` events read at `now` may be processed;
+    /// if not, its events are counted as dropped.
+    pub fn admit(&mut self, count: usize, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.per_sec);
+        self.refilled = now;
+        let admitted = count as f64 <= self.tokens;
+        if admitted {
+            self.tokens -= count as f64;
+        } else {
+            self.dropped += count as u64;
+        }
+        admitted
+    }
 
-    event_loop(device_id, &mut device, &mut recognizer, config, running);
+    /// Drop the frames of `events` read at `now` that exceed the rate.  A
+    /// dropped frame keeps its slot switches and releases, closed by its
+    /// `SYN_REPORT`, so the recognizer still sees every finger lift.
+    pub fn admit_frames(&mut self, events: &mut Vec<InputEvent>, now: Instant) {
+        let mut kept = 0;
+        let mut start = 0;
+        while start < events.len() {
+            let end = events[start..]
+                .iter()
+                .position(|event| {
+                    matches!(
+                        classify_event(event),
+                        Some(TouchEvent::SynReport | TouchEvent::SynReportAt(_))
+                    )
+                })
+                .map_or(events.len(), |i| start + i + 1);
+            if self.admit(end - start, now) {
+                events.copy_within(start..end, kept);
+                kept += end - start;
+            } else {
+                let released = events[start..end]
+                    .iter()
+                    .any(|event| classify_event(event) == Some(TouchEvent::FingerUp));
+                for i in start..end {
+                    let keep = match classify_event(&events[i]) {
+                        Some(TouchEvent::Slot(_) | TouchEvent::FingerUp) => true,
+                        Some(TouchEvent::SynReport | TouchEvent::SynReportAt(_)) => released,
+                        _ => false,
+                    };
+                    if keep {
+                        events[kept] = events[i];
+                        kept += 1;
+                        self.dropped -= 1;
+                    }
+                }
+            }
+            start = end;
+        }
+        events.truncate(kept);
+    }
+
+    /// Events dropped since the last report, if any were and it is time to
+    /// report again.
+    pub fn report(&mut self, now: Instant) -> Option<u64> {
+        let due = self
+            .last_reported
+            .is_none_or(|at| now.saturating_duration_since(at) >= Self::REPORT_INTERVAL);
+        if self.dropped == 0 || !due {
+            return None;
+        }
+        self.last_reported = Some(now);
+        Some(std::mem::take(&mut self.dropped))
+    }
 }
 
-/// Blocking event loop - reads from the device and dispatches gestures.
+/// Event loop - reads from the device and dispatches gestures.
+///
+/// Waits at most `poll_interval` for input, so it notices a stop request and
+/// beats the heartbeat while the panel is idle; only a read that hangs
+/// leaves the heartbeat stale.
 fn event_loop(
     device_id: &str,
-    device: &mut Device,
+    device: &mut Box<dyn TouchSource>,
     recognizer: &mut GestureRecognizer,
     config: &DeviceConfig,
-    running: &Arc<AtomicBool>,
+    ctx: &LoopContext,
+    heartbeat: &Heartbeat,
 ) {
+    let running = &ctx.running;
+
+    // Reset on every (re)open - phantom contacts follow each device open.
+    let mut skip_remaining = config.skip_initial_touches;
+    let mut wake_gate = WakeGate::new(config.wake_on_idle, Instant::now());
+    let mut focus_cache = FocusCache::default();
+    let mut focus = FocusQuery::default();
+    // Reused across reads so high-rate panels do not allocate per batch.
+    let mut events = Vec::with_capacity(EVENT_BATCH_CAPACITY);
+    let mut touch_events = Vec::with_capacity(EVENT_BATCH_CAPACITY);
+    let release = release_signal(device_id, device.as_ref(), config);
+    let mut rate_limit = config
+        .max_events_per_sec
+        .map(|per_sec| EventRateLimit::new(per_sec, Instant::now()));
+
     while running.load(Ordering::Relaxed) {
-        match device.fetch_events().map(|iter| iter.collect::<Vec<_>>()) {
-            Ok(events) => {
-                for event in &events {
+        // Without a release event, a contact ends when the device falls silent.
+        let release_wait = match release {
+            ReleaseSignal::Timeout(timeout) if recognizer.touch_start.is_some() => Some(timeout),
+            _ => None,
+        };
+        let readable = device.wait_readable(release_wait.unwrap_or(ctx.poll_interval));
+        let timed_out = !readable && release_wait.is_some();
+        if !readable && !timed_out {
+            heartbeat.beat(Instant::now());
+            if heartbeat.is_retired() {
+                debug!("Device {device_id}: thread replaced by the watchdog, exiting");
+                break;
+            }
+            continue;
+        }
+        events.clear();
+        let fetched = if timed_out {
+            Ok(())
+        } else {
+            device.fetch_events(&mut events)
+        };
+        if let Some(limit) = rate_limit.as_mut() {
+            let now = Instant::now();
+            limit.admit_frames(&mut events, now);
+            if let Some(dropped) = limit.report(now) {
+                warn!(
+                    "Device {device_id}: over max_events_per_sec, dropped {dropped} input event(s)"
+                );
+            }
+        }
+        heartbeat.beat(Instant::now());
+        if heartbeat.is_retired() {
+            debug!("Device {device_id}: thread replaced by the watchdog, exiting");
+            break;
+        }
+        match fetched {
+            Ok(()) => {
+                let recognized = if timed_out {
+                    process_touch_events_detailed(recognizer, &[TouchEvent::FingerUp])
+                } else {
+                    process_batch(recognizer, &events, &mut touch_events, config.single_touch)
+                };
+                let fired = skip_initial_gestures(recognized, &mut skip_remaining);
+                for details in fired {
                     if !running.load(Ordering::Relaxed) {
                         break;
                     }
-                    if let Some(te) = classify_event(event) {
-                        let fired = process_touch_events(recognizer, &[te]);
-                        for gesture in fired {
-                            execute_gesture(device_id, gesture, config);
+                    if counts_gesture(device_id, details.gesture, config, ctx.bindings.as_deref()) {
+                        if let Some(Ok(mut stats)) = ctx.stats.as_deref().map(Mutex::lock) {
+                            stats.record(details.gesture);
+                        }
+                    }
+                    if let Some((command, executor)) = &ctx.feedback {
+                        run_feedback(command, details.gesture, executor.as_ref());
+                    }
+                    match ctx.mode {
+                        RunMode::Normal => {
+                            if wake_gate.consume_as_wake(Instant::now()) {
+                                execute_wake(device_id, details.gesture, config, ctx);
+                            } else if !within_active_hours(
+                                details.gesture,
+                                config,
+                                local_minute_of_day(),
+                            ) {
+                                debug!(
+                                    "{device_id}: {} ignored outside active_hours",
+                                    details.gesture
+                                );
+                            } else if !focus_permits(config, &mut focus_cache, &mut focus) {
+                                debug!(
+                                    "{device_id}: {} ignored, window class not focused",
+                                    details.gesture
+                                );
+                            } else {
+                                execute_gesture(device_id, &details, config, ctx);
+                            }
+                        }
+                        RunMode::Monitor => print_gesture(device_id, &details),
+                        RunMode::Learn => {
+                            let stub = ctx.learned.as_deref().and_then(|learned| {
+                                learned.lock().ok()?.record(device_id, &details, config)
+                            });
+                            if let Some(stub) = stub {
+                                println!("{stub}");
+                            }
                         }
                     }
                 }
@@ -205,7 +1351,7 @@ fn event_loop(
             Err(e) => {
                 if running.load(Ordering::Relaxed) {
                     warn!("Device {device_id} disconnected: {e}");
-                    attempt_reconnect(device_id, device, recognizer, config, running);
+                    attempt_reconnect(device_id, device, recognizer, config, ctx, heartbeat);
                 }
                 break;
             }
@@ -213,40 +1359,888 @@ fn event_loop(
     }
 }
 
-/// Spawn the shell command for a recognized gesture.
-fn execute_gesture(device_id: &str, gesture: GestureType, config: &DeviceConfig) {
+/// Spawn the shell command and/or inject the key or scroll for a recognized gesture.
+fn execute_gesture(
+    device_id: &str,
+    details: &GestureDetails,
+    config: &DeviceConfig,
+    ctx: &LoopContext,
+) {
+    let gesture = details.gesture;
     let gesture_name: &str = gesture.into();
-    if let Some(action) = resolve_action(gesture, &config.gestures) {
-        match Command::new("sh").arg("-c").arg(action).spawn() {
-            Ok(_) => debug!("Spawned action: {action}"),
-            Err(e) => error!("Failed to execute action '{action}': {e}"),
-        }
-        info!("{device_id}: {gesture_name}");
-    }
-}
+    // A tap run of another length than `tap_count` is not this binding.
+    let bound = tap_count_matches(details, &config.gestures);
+    let key = bound
+        .then(|| resolve_key(gesture, &config.gestures))
+        .flatten();
+    let scroll = bound
+        .then(|| resolve_scroll(gesture, &config.gestures))
+        .flatten();
 
-/// Attempt to reconnect to a device after it disconnects.
-fn attempt_reconnect(
+    if let Some(mqtt) = &ctx.mqtt {
+        mqtt.publish(device_id, gesture);
+    }
+    if let Some(fifo) = &ctx.fifo {
+        fifo.send(device_id, gesture);
+    }
+    if let Some(http) = &ctx.http {
+        http.publish(device_id, gesture);
+    }
+    let bindings = ctx.bindings.as_deref();
+    let action = match &ctx.pacer {
+        Some(pacer) => run_bound_action(
+            device_id,
+            details,
+            config,
+            bindings,
+            &pacer.with_delay(config.post_action_delay(gesture)),
+        ),
+        None => run_bound_action(device_id, details, config, bindings, ctx.executor.as_ref()),
+    };
+    if let Some(Ok(mut uinput)) = ctx.uinput.as_deref().map(Mutex::lock) {
+        if let Some(key) = key {
+            match uinput.tap(key) {
+                Ok(()) => debug!("Injected key: {key:?}"),
+                Err(e) => error!("Failed to inject key {key:?}: {e}"),
+            }
+        }
+        if let Some((axis, delta)) = scroll {
+            match uinput.scroll(axis, delta) {
+                Ok(()) => debug!("Injected scroll: {axis:?} {delta:+}"),
+                Err(e) => error!("Failed to inject scroll {axis:?}: {e}"),
+            }
+        }
+    }
+    let Some(level) = dispatch_log_level(gesture, config) else {
+        return;
+    };
+    let dispatched = action.is_some() || key.is_some() || scroll.is_some();
+    if dispatched || is_record_only(gesture, &config.gestures) {
+        if level != Level::Info {
+            log!(level, "{device_id}: {gesture_name}");
+            return;
+        }
+        let journaled = ctx
+            .journal
+            .as_deref()
+            .is_some_and(|journal| journal.send(device_id, gesture));
+        if !journaled {
+            info!("{device_id}: {gesture_name}");
+        }
+    } else {
+        // Tells "recognized but unbound" apart from "not recognized".
+        debug!("{device_id}: {gesture_name} recognized (no action)");
+    }
+}
+
+/// Whether `gesture` on `device_id` is added to the stats: it has an enabled
+/// binding, record-only ones included, or an action bound at runtime.
+/// Gestures nobody bound are recognized but not counted.
+pub fn counts_gesture(
     device_id: &str,
-    device: &mut Device,
+    gesture: GestureType,
+    config: &DeviceConfig,
+    bindings: Option<&RuntimeBindings>,
+) -> bool {
+    let name: &str = gesture.into();
+    config.gestures.get(name).is_some_and(|gc| gc.enabled)
+        || bindings.is_some_and(|b| b.action(device_id, gesture).is_some())
+}
+
+/// Level a dispatched `gesture` is logged at: info unless its binding sets
+/// `log_level`, `None` with `log = false`.
+pub fn dispatch_log_level(gesture: GestureType, config: &DeviceConfig) -> Option<Level> {
+    let name: &str = gesture.into();
+    config
+        .gestures
+        .get(name)
+        .and_then(|binding| binding.log_level)
+        .unwrap_or(LevelFilter::Info)
+        .to_level()
+}
+
+/// Minutes after midnight in local time, for `active_hours`.
+fn local_minute_of_day() -> u16 {
+    let now = Local::now();
+    (now.hour() * 60 + now.minute()) as u16
+}
+
+/// Check `only_when_class` against the (cached) focused X11 window class.
+fn focus_permits(config: &DeviceConfig, cache: &mut FocusCache, focus: &mut FocusQuery) -> bool {
+    let Some(wanted) = config.only_when_class.as_deref() else {
+        return true;
+    };
+    let focused = cache.focused_class(Instant::now(), || focus.active_class());
+    focus_allows(Some(wanted), focused)
+}
+
+/// Consume a gesture as a wake event, running the optional `wake_action`.
+fn execute_wake(device_id: &str, gesture: GestureType, config: &DeviceConfig, ctx: &LoopContext) {
+    if let Some(action) = &config.wake_action {
+        ctx.executor.execute(action, config.action_retries, false);
+    }
+    info!("{device_id}: wake (suppressed {gesture})");
+}
+
+/// Environment variable holding the gesture name for `feedback_command`.
+pub const FEEDBACK_GESTURE_VAR: &str = "BODGESTR_GESTURE";
+
+/// Start `[global] feedback_command` for a recognized `gesture`, whether or
+/// not it is bound, without waiting for it.
+pub fn run_feedback(command: &str, gesture: GestureType, executor: &dyn ActionExecutor) {
+    executor.execute_with_env(command, &[(FEEDBACK_GESTURE_VAR, gesture.into())]);
+}
+
+/// Resolve the action bound to a recognized gesture (honouring
+/// `by_fingers` and `tap_count`) and hand it to `executor`.  Returns the
+/// action, if any.
+pub fn run_gesture_action<'a>(
+    details: &GestureDetails,
+    config: &'a DeviceConfig,
+    executor: &dyn ActionExecutor,
+) -> Option<&'a str> {
+    if !tap_count_matches(details, &config.gestures) {
+        return None;
+    }
+    let action = resolve_finger_action(details.gesture, details.finger_count, &config.gestures)?;
+    spawn_action(action, details, config, executor);
+    Some(action)
+}
+
+/// Start `action` for `details`, writing its trajectory to the action's
+/// stdin when one was recorded (`pass_trajectory`).
+fn spawn_action(
+    action: &str,
+    details: &GestureDetails,
+    config: &DeviceConfig,
+    executor: &dyn ActionExecutor,
+) {
+    match &details.trajectory {
+        Some(points) => executor.execute_with_input(
+            action,
+            config.action_retries,
+            false,
+            trajectory_json(points).as_bytes(),
+        ),
+        None => executor.execute(action, config.action_retries, false),
+    }
+}
+
+/// The touch points of a gesture as a JSON array for `pass_trajectory`:
+/// `[{"x":..,"y":..,"t_ms":..,"id":..}, ...]` in device units, with
+/// milliseconds since the first point and the point's tracking ID.
+pub fn trajectory_json(points: &[TouchPoint]) -> String {
+    let Some(first) = points.first() else {
+        return "[]".to_string();
+    };
+    let points: Vec<serde_json::Value> = points
+        .iter()
+        .map(|p| {
+            serde_json::json!({
+                "x": p.x,
+                "y": p.y,
+                "t_ms": p.time.saturating_duration_since(first.time).as_secs_f64() * 1000.0,
+                "id": p.tracking_id,
+            })
+        })
+        .collect();
+    serde_json::Value::from(points).to_string()
+}
+
+/// [`run_gesture_action`], except that an action bound for `device_id`
+/// through the control socket replaces the configured one.
+pub fn run_bound_action(
+    device_id: &str,
+    details: &GestureDetails,
+    config: &DeviceConfig,
+    bindings: Option<&RuntimeBindings>,
+    executor: &dyn ActionExecutor,
+) -> Option<String> {
+    let Some(action) = bindings.and_then(|b| b.action(device_id, details.gesture)) else {
+        return run_gesture_action(details, config, executor).map(str::to_string);
+    };
+    if !tap_count_matches(details, &config.gestures) {
+        return None;
+    }
+    spawn_action(&action, details, config, executor);
+    Some(action)
+}
+
+/// Find the action `--run-gesture` runs: the one bound to `gesture` on the
+/// device configured as `device` (its ID, USB ID or event path).
+///
+/// Errors name what is missing - the device, a valid gesture name, or a
+/// binding - ready to show to the user.
+pub fn gesture_action<'a>(
+    config: &'a AppConfig,
+    device: &str,
+    gesture: &str,
+) -> Result<(&'a DeviceConfig, &'a str), String> {
+    let (device_id, device_config) = config
+        .devices
+        .iter()
+        .find(|(id, dc)| {
+            id.as_str() == device
+                || dc.device_usb_id == device
+                || dc.device_path.as_deref() == Some(Path::new(device))
+        })
+        .ok_or_else(|| format!("No device '{device}' in the configuration"))?;
+    let gesture: GestureType = gesture
+        .parse()
+        .map_err(|_| format!("Unknown gesture '{gesture}'"))?;
+    if config.disabled_gestures.contains(&gesture) {
+        return Err(format!("Gesture '{gesture}' is disabled globally"));
+    }
+    resolve_action(gesture, &device_config.gestures)
+        .map(|action| (device_config, action))
+        .ok_or_else(|| format!("Gesture '{gesture}' is not bound to an action on {device_id}"))
+}
+
+/// Run the action bound to `gesture` on `device` once, as if it had just
+/// been recognized, and wait for it to finish.
+pub fn run_single_gesture(config_path: &Path, device: &str, gesture: &str) -> ExitCode {
+    let config = match parse_config_file(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match gesture_action(&config, device, gesture) {
+        Ok((device_config, action)) => {
+            println!("Running: {action}");
+            ShellExecutor::default().execute(action, device_config.action_retries, true);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the action strings bound to gestures.
+///
+/// [`ShellExecutor`] is the default; other implementations can record
+/// actions in tests or forward them to another backend.
+pub trait ActionExecutor: Send + Sync {
+    /// Run `action`, with up to `retries` extra attempts if it fails to
+    /// start.  With `wait`, return only once it has finished.
+    fn execute(&self, action: &str, retries: u32, wait: bool);
+
+    /// Start `command` once with `env` added to its environment, without
+    /// waiting.  The default ignores `env` and calls [`execute`](Self::execute).
+    fn execute_with_env(&self, command: &str, env: &[(&str, &str)]) {
+        let _ = env;
+        self.execute(command, 0, false);
+    }
+
+    /// Like [`execute`](Self::execute), with `input` written to the
+    /// action's stdin.  The default ignores `input`.
+    fn execute_with_input(&self, action: &str, retries: u32, wait: bool, input: &[u8]) {
+        let _ = input;
+        self.execute(action, retries, wait);
+    }
+}
+
+/// Runs actions via `sh -c`.
+#[derive(Debug, Clone, Default)]
+pub struct ShellExecutor {
+    failures: Arc<ActionFailures>,
+}
+
+impl ShellExecutor {
+    /// Failures of the actions run so far.
+    pub fn failures(&self) -> &ActionFailures {
+        &self.failures
+    }
+
+    /// Spawn `action` via `sh -c` with `env` added to its environment and
+    /// `input`, if any, on its stdin.
+    fn run(
+        &self,
+        action: &str,
+        env: &[(&str, &str)],
+        input: Option<&[u8]>,
+        retries: u32,
+        wait: bool,
+    ) {
+        const ACTION_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+        match spawn_with_retries(retries, ACTION_RETRY_DELAY, || {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(action).envs(env.iter().copied());
+            if input.is_some() {
+                command.stdin(Stdio::piped());
+            }
+            command.spawn()
+        }) {
+            Ok(mut child) => {
+                debug!("Spawned action: {action}");
+                if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+                    // From a thread, so an action not reading its input
+                    // never blocks the caller on a full pipe.
+                    let input = input.to_vec();
+                    let written = thread::Builder::new()
+                        .name("action-stdin".into())
+                        .spawn(move || stdin.write_all(&input));
+                    if let Err(e) = written {
+                        warn!("Failed to pass input to action '{action}': {e}");
+                    }
+                }
+                if wait {
+                    if let Err(e) = child.wait() {
+                        warn!("Failed to wait for action '{action}': {e}");
+                    }
+                }
+            }
+            Err(source) => {
+                let e = BodgestrError::ActionSpawnFailed {
+                    action: action.to_string(),
+                    source,
+                };
+                match self.failures.record(action, Instant::now()) {
+                    Some(1) => error!("{e}"),
+                    Some(n) => error!("{e} ({n} failures since last reported)"),
+                    None => debug!("{e}"),
+                }
+            }
+        }
+    }
+}
+
+impl ActionExecutor for ShellExecutor {
+    fn execute(&self, action: &str, retries: u32, wait: bool) {
+        self.run(action, &[], None, retries, wait);
+    }
+
+    fn execute_with_env(&self, command: &str, env: &[(&str, &str)]) {
+        self.run(command, env, None, 0, false);
+    }
+
+    fn execute_with_input(&self, action: &str, retries: u32, wait: bool, input: &[u8]) {
+        self.run(action, &[], Some(input), retries, wait);
+    }
+}
+
+/// Counts failed actions and rate-limits their error logs, so an action
+/// that can never start (say, `xdotool` is missing) is reported about once
+/// a minute instead of on every gesture.
+#[derive(Debug, Default)]
+pub struct ActionFailures {
+    by_action: Mutex<HashMap<String, FailureCount>>,
+}
+
+#[derive(Debug, Default)]
+struct FailureCount {
+    total: u64,
+    unreported: u64,
+    last_reported: Option<Instant>,
+}
+
+impl ActionFailures {
+    /// Minimum time between two error logs for the same action.
+    pub const REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Count a failure of `action` at `now`.  Returns the failures since
+    /// the last report, this one included, if it is time to report again.
+    pub fn record(&self, action: &str, now: Instant) -> Option<u64> {
+        let mut by_action = self
+            .by_action
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let count = by_action.entry(action.to_string()).or_default();
+        count.total += 1;
+        count.unreported += 1;
+        let due = count
+            .last_reported
+            .is_none_or(|at| now.saturating_duration_since(at) >= Self::REPORT_INTERVAL);
+        if !due {
+            return None;
+        }
+        count.last_reported = Some(now);
+        Some(std::mem::take(&mut count.unreported))
+    }
+
+    /// Total failures of `action` so far.
+    pub fn count(&self, action: &str) -> u64 {
+        self.by_action
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(action)
+            .map_or(0, |count| count.total)
+    }
+}
+
+/// Records actions instead of running them - for tests and dry runs.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingExecutor {
+    actions: Arc<Mutex<Vec<String>>>,
+}
+
+impl RecordingExecutor {
+    /// Actions executed so far, oldest first.
+    pub fn actions(&self) -> Vec<String> {
+        self.actions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl ActionExecutor for RecordingExecutor {
+    fn execute(&self, action: &str, _retries: u32, _wait: bool) {
+        self.actions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(action.to_string());
+    }
+
+    /// Recorded in shell syntax, e.g. `NAME=value command`.
+    fn execute_with_env(&self, command: &str, env: &[(&str, &str)]) {
+        let prefix: String = env.iter().map(|(k, v)| format!("{k}={v} ")).collect();
+        self.execute(&format!("{prefix}{command}"), 0, false);
+    }
+}
+
+/// Spawns each action while holding `lock`, shared by all device threads,
+/// so actions start one at a time (`serialize_actions`).  The lock is held
+/// only for the spawn, never while a child runs, so recognition goes on.
+struct SerializedExecutor {
+    inner: Arc<dyn ActionExecutor>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl ActionExecutor for SerializedExecutor {
+    fn execute(&self, action: &str, retries: u32, wait: bool) {
+        run_serialized(Some(&self.lock), || {
+            self.inner.execute(action, retries, wait)
+        });
+    }
+
+    fn execute_with_input(&self, action: &str, retries: u32, wait: bool, input: &[u8]) {
+        run_serialized(Some(&self.lock), || {
+            self.inner.execute_with_input(action, retries, wait, input)
+        });
+    }
+}
+
+/// A shell action waiting in the [`ActionQueue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionJob {
+    pub action: String,
+    /// Extra spawn attempts, from the originating device's `action_retries`.
+    pub retries: u32,
+    /// Written to the action's stdin (`pass_trajectory`).
+    pub input: Option<Vec<u8>>,
+}
+
+impl ActionJob {
+    /// Run this job through `executor`.
+    pub fn run(&self, executor: &dyn ActionExecutor, wait: bool) {
+        match &self.input {
+            Some(input) => executor.execute_with_input(&self.action, self.retries, wait, input),
+            None => executor.execute(&self.action, self.retries, wait),
+        }
+    }
+}
+
+/// Bounded FIFO of actions run one after another by a dedicated worker
+/// thread, decoupling recognition from action latency.
+#[derive(Clone)]
+pub struct ActionQueue {
+    tx: SyncSender<ActionJob>,
+}
+
+impl ActionQueue {
+    /// Start the worker thread, which passes each job to `execute` in order.
+    /// The worker exits once every queue handle is dropped.
+    pub fn start(capacity: usize, mut execute: impl FnMut(ActionJob) + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::sync_channel::<ActionJob>(capacity);
+        thread::Builder::new()
+            .name("action-worker".into())
+            .spawn(move || rx.into_iter().for_each(&mut execute))
+            .expect("Failed to spawn action worker thread");
+        Self { tx }
+    }
+
+    /// Enqueue `job` without blocking.  Returns `false` and logs if the
+    /// queue is full and the job was dropped.
+    pub fn submit(&self, job: ActionJob) -> bool {
+        match self.tx.try_send(job) {
+            Ok(()) => true,
+            Err(TrySendError::Full(job)) => {
+                warn!("Action queue full, dropping '{}'", job.action);
+                false
+            }
+            Err(TrySendError::Disconnected(job)) => {
+                error!("Action worker stopped, dropping '{}'", job.action);
+                false
+            }
+        }
+    }
+}
+
+/// Enqueues actions for the worker thread (`action_queue_capacity`).
+impl ActionExecutor for ActionQueue {
+    fn execute(&self, action: &str, retries: u32, _wait: bool) {
+        self.submit(ActionJob {
+            action: action.to_string(),
+            retries,
+            input: None,
+        });
+    }
+
+    fn execute_with_input(&self, action: &str, retries: u32, _wait: bool, input: &[u8]) {
+        self.submit(ActionJob {
+            action: action.to_string(),
+            retries,
+            input: Some(input.to_vec()),
+        });
+    }
+}
+
+/// An action waiting in an [`ActionPacer`] and the pause that follows it.
+struct PacedJob {
+    job: ActionJob,
+    delay: Duration,
+}
+
+/// Worker thread running one device's actions in order, pausing after each
+/// for its `post_action_delay_ms` before starting the next.
+#[derive(Clone)]
+pub struct ActionPacer {
+    tx: mpsc::Sender<PacedJob>,
+}
+
+impl ActionPacer {
+    /// Start the worker for `device_id`, which runs actions through
+    /// `inner`.  The worker exits once every pacer handle is dropped.
+    pub fn start(device_id: &str, inner: Arc<dyn ActionExecutor>) -> Self {
+        let (tx, rx) = mpsc::channel::<PacedJob>();
+        thread::Builder::new()
+            .name(format!("actions-{device_id}"))
+            .spawn(move || {
+                for PacedJob { job, delay } in rx {
+                    job.run(inner.as_ref(), false);
+                    thread::sleep(delay);
+                }
+            })
+            .expect("Failed to spawn action pacer thread");
+        Self { tx }
+    }
+
+    /// Executor handing actions to the worker, each followed by `delay`.
+    pub fn with_delay(&self, delay: Duration) -> PacedExecutor<'_> {
+        PacedExecutor { pacer: self, delay }
+    }
+}
+
+/// Submits actions to an [`ActionPacer`] with a fixed trailing delay.
+pub struct PacedExecutor<'a> {
+    pacer: &'a ActionPacer,
+    delay: Duration,
+}
+
+impl PacedExecutor<'_> {
+    /// Hand `job` to the pacer's worker.
+    fn submit(&self, job: ActionJob) {
+        if let Err(mpsc::SendError(PacedJob { job, .. })) = self.pacer.tx.send(PacedJob {
+            job,
+            delay: self.delay,
+        }) {
+            error!("Action pacer stopped, dropping '{}'", job.action);
+        }
+    }
+}
+
+impl ActionExecutor for PacedExecutor<'_> {
+    fn execute(&self, action: &str, retries: u32, _wait: bool) {
+        self.submit(ActionJob {
+            action: action.to_string(),
+            retries,
+            input: None,
+        });
+    }
+
+    fn execute_with_input(&self, action: &str, retries: u32, _wait: bool, input: &[u8]) {
+        self.submit(ActionJob {
+            action: action.to_string(),
+            retries,
+            input: Some(input.to_vec()),
+        });
+    }
+}
+
+/// Run `f` while holding `lock`, if one is given.  A poisoned lock is
+/// still acquired - it guards no data.
+pub fn run_serialized<T>(lock: Option<&Mutex<()>>, f: impl FnOnce() -> T) -> T {
+    let _guard = lock.map(|lock| lock.lock().unwrap_or_else(PoisonError::into_inner));
+    f()
+}
+
+/// Call `spawn` until it succeeds, retrying up to `retries` more times with
+/// `delay` in between.  Returns the last error if every attempt fails.
+pub fn spawn_with_retries<T, E: Display>(
+    retries: u32,
+    delay: Duration,
+    mut spawn: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut attempt = 0;
+    loop {
+        match spawn() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < retries => {
+                attempt += 1;
+                warn!("Action spawn failed ({e}), retry {attempt}/{retries}");
+                thread::sleep(delay);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Feed a synthetic full-width swipe left through a recognizer built from
+/// `thresholds`, without touching hardware.  `false` means the thresholds
+/// cannot recognize even the clearest swipe (e.g. `swipe_distance_min_pct`
+/// above 1).
+pub fn selftest(thresholds: &ValidatedThresholds) -> bool {
+    const RANGE: (f64, f64) = (0.0, 1000.0);
+    let mut recognizer = GestureRecognizer::new(thresholds.clone(), RANGE, RANGE);
+    let swipe = [
+        TouchEvent::TrackingId(0),
+        TouchEvent::PositionX(RANGE.1),
+        TouchEvent::PositionY(RANGE.1 / 2.0),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(RANGE.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+    ];
+    process_touch_events(&mut recognizer, &swipe).contains(&GestureType::SwipeLeft)
+}
+
+/// Print a recognized gesture and its kinematics (monitor mode).
+fn print_gesture(device_id: &str, details: &GestureDetails) {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    let pinch = details.pinch.map_or_else(String::new, |pinch| {
+        format!(
+            " center=({:.1},{:.1}) scale={:.3}",
+            pinch.center.0, pinch.center.1, pinch.scale
+        )
+    });
+    println!(
+        "[{now:.3}] {device_id}: {} angle={:.1}° distance={:.1} duration={:.3}s fingers={}{pinch}",
+        details.gesture,
+        details.angle_deg,
+        details.distance,
+        details.duration,
+        details.finger_count,
+    );
+}
+
+/// Set by the `SIGUSR2` handler; cleared once the dump is logged.
+static DUMP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request_dump(_signal: nix::libc::c_int) {
+    DUMP_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Log a [`config_dump`] whenever the process receives `SIGUSR2`.
+pub fn install_dump_handler() -> nix::Result<()> {
+    let action = SigAction::new(
+        SigHandler::Handler(request_dump),
+        SaFlags::SA_RESTART,
+        SigSet::empty(),
+    );
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe { sigaction(Signal::SIGUSR2, &action) }.map(|_| ())
+}
+
+/// Summary of the running configuration for live debugging: each active
+/// device with its locator, thresholds in effect and enabled bindings,
+/// then the gesture counts so far.
+pub fn config_dump(config: &AppConfig, stats: Option<&GestureStats>) -> String {
+    let mut lines = vec![format!("{} active device(s)", config.devices.len())];
+    let mut devices: Vec<_> = config.devices.iter().collect();
+    devices.sort_by_key(|(id, _)| *id);
+    for (device_id, device) in devices {
+        lines.push(format!("device {device_id} ({})", device.locator()));
+        let thresholds = match serde_json::to_value(&device.thresholds) {
+            Ok(serde_json::Value::Object(fields)) => fields
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => String::new(),
+        };
+        lines.push(format!("  thresholds: {thresholds}"));
+        let mut gestures: Vec<_> = device.gestures.iter().filter(|(_, g)| g.enabled).collect();
+        gestures.sort_by_key(|(name, _)| *name);
+        for (name, gesture) in gestures {
+            let mut bound: Vec<String> = gesture.action.iter().cloned().collect();
+            bound.extend(
+                gesture
+                    .by_fingers
+                    .iter()
+                    .map(|(fingers, action)| format!("{fingers} fingers: {action}")),
+            );
+            bound.extend(gesture.key.map(|key| format!("key {key:?}")));
+            bound.extend(gesture.scroll.map(|axis| format!("scroll {axis:?}")));
+            lines.push(format!("  {name} -> {}", bound.join(", ")));
+        }
+    }
+    if let Some(stats) = stats {
+        let counts: Vec<String> = stats
+            .counts
+            .iter()
+            .map(|(name, count)| format!("{name}={count}"))
+            .collect();
+        lines.push(format!("gesture counts: {}", counts.join(" ")));
+    }
+    lines.join("\n")
+}
+
+/// Log [`config_dump`] each time `SIGUSR2` arrives, until the manager stops.
+fn dump_loop(config: &AppConfig, stats: Option<&Mutex<GestureStats>>, running: &AtomicBool) {
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    while running.load(Ordering::Relaxed) {
+        thread::sleep(POLL_INTERVAL);
+        if DUMP_REQUESTED.swap(false, Ordering::Relaxed) {
+            let snapshot = stats.and_then(|stats| stats.lock().ok().map(|s| s.clone()));
+            info!("{}", config_dump(config, snapshot.as_ref()));
+        }
+    }
+}
+
+/// Persist `stats` to `path` every minute while `running` is set, and once
+/// more on shutdown.
+fn stats_flush_loop(path: &Path, stats: &Mutex<GestureStats>, running: &AtomicBool) {
+    const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    let flush = || {
+        let snapshot = match stats.lock() {
+            Ok(stats) => stats.clone(),
+            Err(_) => return,
+        };
+        match snapshot.save(path) {
+            Ok(()) => debug!("Flushed gesture stats to {}", path.display()),
+            Err(e) => warn!("{e}"),
+        }
+    };
+
+    let mut last_flush = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        thread::sleep(POLL_INTERVAL);
+        if last_flush.elapsed() >= FLUSH_INTERVAL {
+            flush();
+            last_flush = Instant::now();
+        }
+    }
+    flush();
+}
+
+// -- Watchdog -------------------------------------------------
+
+/// A device thread's last sign of life, checked by the watchdog.
+#[derive(Debug, Default)]
+pub struct Heartbeat {
+    last: Mutex<Option<Instant>>,
+    retired: AtomicBool,
+}
+
+impl Heartbeat {
+    /// Record that the thread was active at `now`.
+    pub fn beat(&self, now: Instant) {
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner) = Some(now);
+    }
+
+    /// Whether the thread has beaten before but not within `timeout` before
+    /// `now`.  Device loops beat after every read and every empty poll, so
+    /// an idle panel stays fresh and only a loop stuck in a read goes stale.
+    pub fn is_stale(&self, now: Instant, timeout: Duration) -> bool {
+        self.last
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some_and(|last| now.saturating_duration_since(last) > timeout)
+    }
+
+    /// Mark the thread as replaced; it exits after its next read or poll.
+    pub fn retire(&self) {
+        self.retired.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`retire`](Self::retire) was called.
+    pub fn is_retired(&self) -> bool {
+        self.retired.load(Ordering::Relaxed)
+    }
+}
+
+/// Replace device threads that stay silent beyond `timeout` while their
+/// device is still present, until all threads exit or the manager stops.
+///
+/// A stuck thread cannot be killed; it is retired and left detached.
+fn watchdog_loop(mut threads: Vec<DeviceThread>, timeout: Duration, ctx: &LoopContext) {
+    let poll_interval = (timeout / 4).min(Duration::from_secs(1));
+
+    while ctx.running.load(Ordering::Relaxed)
+        && threads.iter().any(|worker| !worker.handle.is_finished())
+    {
+        thread::sleep(poll_interval);
+        let now = Instant::now();
+        for worker in &mut threads {
+            if worker.handle.is_finished()
+                || !worker.heartbeat.is_stale(now, timeout)
+                || !device_present(&worker.config)
+            {
+                continue;
+            }
+            warn!(
+                "Device {}: no activity for {timeout:?}, restarting its thread",
+                worker.device_id
+            );
+            let Some(device) = (ctx.open)(&worker.device_id, &worker.config) else {
+                continue;
+            };
+            worker.heartbeat.retire();
+            *worker =
+                spawn_device_thread(worker.device_id.clone(), device, worker.config.clone(), ctx);
+        }
+    }
+    for worker in threads {
+        let _ = worker.handle.join();
+    }
+}
+
+/// Attempt to reconnect to a device after it disconnects.
+fn attempt_reconnect(
+    device_id: &str,
+    device: &mut Box<dyn TouchSource>,
     recognizer: &mut GestureRecognizer,
     config: &DeviceConfig,
-    running: &Arc<AtomicBool>,
+    ctx: &LoopContext,
+    heartbeat: &Heartbeat,
 ) {
     const MAX_RETRIES: usize = 10;
     const RETRY_INTERVAL: Duration = Duration::from_secs(5);
 
     for attempt in 1..=MAX_RETRIES {
-        if !running.load(Ordering::Relaxed) {
+        if !ctx.running.load(Ordering::Relaxed) {
             return;
         }
         info!("Reconnect attempt {attempt}/{MAX_RETRIES} for {device_id}...");
         thread::sleep(RETRY_INTERVAL);
+        heartbeat.beat(Instant::now());
 
-        if let Some(new_device) = find_device(device_id, config) {
+        if let Some(new_device) = (ctx.open)(device_id, config) {
             info!("Reconnected to {device_id}");
             *device = new_device;
-            event_loop(device_id, device, recognizer, config, running);
+            event_loop(device_id, device, recognizer, config, ctx, heartbeat);
             return;
         }
     }
@@ -259,7 +2253,7 @@ pub fn list_touch_devices() -> ExitCode {
     let mut touch_count = 0;
 
     for (path, device) in evdev::enumerate() {
-        if !is_touch_device(&device) {
+        if !is_touch_device(&device, false) {
             continue;
         }
 
@@ -279,9 +2273,12 @@ pub fn list_touch_devices() -> ExitCode {
     }
 
     if touch_count == 0 {
+        println!("No multi-touch devices found.\n");
+        if let Some(message) = permission_denied_message(permission_denied_nodes()) {
+            println!("{message}\n");
+        }
         println!(
-            "No multi-touch devices found.\n\n\
-             Troubleshooting:\n\
+            "Troubleshooting:\n\
              \x20 - Check if touchscreen is connected\n\
              \x20 - Run 'libinput list-devices' to see all devices\n\
              \x20 - Run as root if devices are not visible"
@@ -298,3 +2295,296 @@ pub fn list_touch_devices() -> ExitCode {
     );
     ExitCode::SUCCESS
 }
+
+// -- Learn mode -----------------------------------------------
+
+/// Unbound gestures recognized in learn mode, tallied per device.
+#[derive(Debug, Clone, Default)]
+pub struct LearnedGestures {
+    counts: BTreeMap<(String, &'static str), u64>,
+}
+
+impl LearnedGestures {
+    /// Tally `details` on `device_id` unless `config` already binds its
+    /// gesture to an action, key or scroll.  Returns the binding stub the
+    /// first time each unbound gesture is seen on a device.
+    pub fn record(
+        &mut self,
+        device_id: &str,
+        details: &GestureDetails,
+        config: &DeviceConfig,
+    ) -> Option<String> {
+        let gesture = details.gesture;
+        let bound = resolve_finger_action(gesture, details.finger_count, &config.gestures)
+            .is_some()
+            || resolve_key(gesture, &config.gestures).is_some()
+            || resolve_scroll(gesture, &config.gestures).is_some();
+        if bound {
+            return None;
+        }
+        let count = self
+            .counts
+            .entry((device_id.to_string(), gesture.into()))
+            .or_default();
+        *count += 1;
+        (*count == 1).then(|| binding_stub(device_id, gesture))
+    }
+
+    /// How often `gesture` was seen unbound on `device_id`.
+    pub fn count(&self, device_id: &str, gesture: GestureType) -> u64 {
+        self.counts
+            .get(&(device_id.to_string(), gesture.into()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// One line per device and unbound gesture with its tally, sorted by
+    /// device and gesture name.
+    pub fn summary(&self) -> String {
+        if self.counts.is_empty() {
+            return "No unbound gestures seen\n".into();
+        }
+        let mut out = String::from("Unbound gestures seen:\n");
+        for ((device_id, gesture), count) in &self.counts {
+            out.push_str(&format!("  {device_id}: {gesture} x{count}\n"));
+        }
+        out
+    }
+}
+
+/// A `[device.<id>.gestures.<name>]` section binding `gesture`, ready to
+/// paste into the config.
+pub fn binding_stub(device_id: &str, gesture: GestureType) -> String {
+    format!(
+        "[device.{device_id}.gestures.{gesture}]\n\
+         action = \"echo {gesture}\"   # replace with the command to run\n\
+         enabled = true\n"
+    )
+}
+
+// -- Calibration ----------------------------------------------
+
+/// Number of samples recorded per calibration step.
+const CALIBRATION_TAPS: usize = 5;
+const CALIBRATION_SWIPES: usize = 5;
+const CALIBRATION_LONG_PRESSES: usize = 3;
+
+/// Kinematics of one touch recorded during calibration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationSample {
+    /// Start-to-end distance: device units for taps and long presses, a
+    /// fraction of the swiped axis span for swipes.
+    pub distance: f64,
+    /// Seconds between the first and last touch point.
+    pub duration: f64,
+}
+
+/// Samples recorded for each calibration step.
+#[derive(Debug, Clone, Default)]
+pub struct CalibrationSamples {
+    pub taps: Vec<CalibrationSample>,
+    pub swipes: Vec<CalibrationSample>,
+    pub long_presses: Vec<CalibrationSample>,
+}
+
+/// Thresholds suggested from calibration samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedThresholds {
+    pub swipe_time_max: f64,
+    pub swipe_distance_min_pct: f64,
+    pub tap_time_max: f64,
+    pub tap_distance_max: f64,
+    pub long_press_time_min: f64,
+}
+
+impl SuggestedThresholds {
+    /// Render as a `[device.<id>.thresholds]` block ready to paste into the config.
+    pub fn to_toml(&self, device_id: &str) -> String {
+        format!(
+            "[device.{device_id}.thresholds]\n\
+             swipe_time_max = {:.2}\n\
+             swipe_distance_min_pct = {:.3}\n\
+             tap_time_max = {:.2}\n\
+             tap_distance_max = {:.1}\n\
+             long_press_time_min = {:.2}\n",
+            self.swipe_time_max,
+            self.swipe_distance_min_pct,
+            self.tap_time_max,
+            self.tap_distance_max,
+            self.long_press_time_min,
+        )
+    }
+}
+
+/// Derive thresholds that comfortably accept every recorded sample.
+///
+/// Time and distance limits get 50 % (taps: 100 %) headroom over the
+/// slowest or widest sample, the swipe distance accepts half the shortest
+/// swipe, and the tap/long-press boundary sits halfway between the slowest
+/// tap and the quickest long press.  Returns `None` if a step has no samples.
+pub fn suggest_thresholds(samples: &CalibrationSamples) -> Option<SuggestedThresholds> {
+    fn max_of(samples: &[CalibrationSample], f: fn(&CalibrationSample) -> f64) -> Option<f64> {
+        samples.iter().map(f).reduce(f64::max)
+    }
+    fn min_of(samples: &[CalibrationSample], f: fn(&CalibrationSample) -> f64) -> Option<f64> {
+        samples.iter().map(f).reduce(f64::min)
+    }
+
+    let slowest_tap = max_of(&samples.taps, |s| s.duration)?;
+    let widest_tap = max_of(&samples.taps, |s| s.distance)?;
+    let slowest_swipe = max_of(&samples.swipes, |s| s.duration)?;
+    let shortest_swipe = min_of(&samples.swipes, |s| s.distance)?;
+    let quickest_hold = min_of(&samples.long_presses, |s| s.duration)?;
+
+    let long_press_time_min = (slowest_tap + quickest_hold) / 2.0;
+    Some(SuggestedThresholds {
+        swipe_time_max: slowest_swipe * 1.5,
+        swipe_distance_min_pct: shortest_swipe * 0.5,
+        tap_time_max: (slowest_tap * 1.5).min(long_press_time_min),
+        tap_distance_max: (widest_tap * 2.0).max(1.0),
+        long_press_time_min,
+    })
+}
+
+/// Thresholds that turn every touch into a gesture with kinematics: any
+/// movement into a swipe when `swipes` is set, otherwise every single-finger
+/// touch into a long press.
+fn calibration_thresholds(swipes: bool) -> ValidatedThresholds {
+    ValidatedThresholds {
+        swipe_time_max: f64::INFINITY,
+        swipe_distance_min_pct: if swipes { 0.0 } else { f64::INFINITY },
+        angle_tolerance_deg: 45.0,
+        tap_time_max: 0.0,
+        long_press_time_min: 0.0,
+        double_tap_interval: 0.0,
+        tap_distance_max: f64::INFINITY,
+        double_tap_distance_max: 0.0,
+        pinch_threshold_pct: f64::INFINITY,
+        swipe_commit_distance_pct: None,
+        swipe_reversal_pct: None,
+        swipe_velocity_min: None,
+        pinch_threshold_min_px: None,
+        swipe_distance_min_px: None,
+        pinch_repeat_interval: None,
+        circle_turn_min: None,
+        circle_radius_tolerance_pct: None,
+        swipe_reject_band_deg: None,
+        long_press_drift_max: None,
+        long_swipe_distance_pct: None,
+    }
+}
+
+/// Guide the user through sample taps, swipes and long presses on `device`
+/// (event node name, path or USB ID) and print suggested thresholds.
+pub fn calibrate_device(device: &str) -> ExitCode {
+    let Some((device_id, config)) =
+        discover_touch_devices(&calibration_thresholds(true), YAxis::Down)
+            .into_iter()
+            .find(|(id, config)| {
+                id == device
+                    || config.device_usb_id == device
+                    || config.device_path.as_deref() == Some(Path::new(device))
+            })
+    else {
+        eprintln!(
+            "No touch device '{device}' found.\n\n\
+             To find available touchscreen devices, run:\n\
+             \x20 bodgestr --list-devices"
+        );
+        return ExitCode::FAILURE;
+    };
+    let Some(mut dev) = find_device(&device_id, &config) else {
+        eprintln!("Cannot open touch device '{device}'");
+        return ExitCode::FAILURE;
+    };
+    let ranges = config
+        .coordinate_space
+        .fixed_ranges()
+        .or_else(|| axis_ranges(&device_id, &dev, false));
+    let Some(ranges) = ranges else {
+        eprintln!("Cannot read the axis ranges of '{device}'");
+        return ExitCode::FAILURE;
+    };
+
+    println!("\n=== bodgestr: Calibrating {device_id} ===\n");
+    let samples = match record_calibration(&mut dev, ranges) {
+        Ok(samples) => samples,
+        Err(e) => {
+            eprintln!("Failed to read from '{device}': {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let Some(suggested) = suggest_thresholds(&samples) else {
+        return ExitCode::FAILURE;
+    };
+    println!(
+        "\nSuggested thresholds (rename '{device_id}' to your device id):\n\n{}",
+        suggested.to_toml(&device_id)
+    );
+    ExitCode::SUCCESS
+}
+
+/// Run the tap, swipe and long-press steps in turn.
+fn record_calibration(
+    device: &mut Device,
+    ranges: ((f64, f64), (f64, f64)),
+) -> std::io::Result<CalibrationSamples> {
+    Ok(CalibrationSamples {
+        taps: record_samples(device, ranges, "Tap the screen", CALIBRATION_TAPS, false)?,
+        swipes: record_samples(
+            device,
+            ranges,
+            "Swipe in any direction",
+            CALIBRATION_SWIPES,
+            true,
+        )?,
+        long_presses: record_samples(
+            device,
+            ranges,
+            "Press and hold",
+            CALIBRATION_LONG_PRESSES,
+            false,
+        )?,
+    })
+}
+
+/// Prompt for `count` touches and record their kinematics.
+fn record_samples(
+    device: &mut Device,
+    (x_range, y_range): ((f64, f64), (f64, f64)),
+    prompt: &str,
+    count: usize,
+    swipes: bool,
+) -> std::io::Result<Vec<CalibrationSample>> {
+    let mut recognizer = GestureRecognizer::new(calibration_thresholds(swipes), x_range, y_range);
+    let mut samples = Vec::with_capacity(count);
+    println!("{prompt} {count} times...");
+
+    while samples.len() < count {
+        let events: Vec<_> = device.fetch_events()?.collect();
+        for te in events.iter().filter_map(classify_event) {
+            for details in process_touch_events_detailed(&mut recognizer, &[te]) {
+                if samples.len() == count {
+                    break;
+                }
+                let span = match details.gesture {
+                    GestureType::SwipeLeft | GestureType::SwipeRight => x_range.1 - x_range.0,
+                    GestureType::SwipeUp | GestureType::SwipeDown => y_range.1 - y_range.0,
+                    _ => 1.0,
+                };
+                samples.push(CalibrationSample {
+                    distance: details.distance / span,
+                    duration: details.duration,
+                });
+                println!(
+                    "  {}/{count}: distance {:.1}, {:.2}s",
+                    samples.len(),
+                    details.distance,
+                    details.duration
+                );
+            }
+        }
+    }
+    Ok(samples)
+}
@@ -0,0 +1,108 @@
+//! Publish recognized gestures to an MQTT broker (`[global.mqtt]`).
+//!
+//! Each gesture is published as `<device>/<gesture>` on the configured
+//! topic, for home-automation dashboards.  The client comes from `rumqttc`,
+//! which is only available with the `mqtt` cargo feature.  An unreachable
+//! broker is logged and retried in the background; it never stalls or
+//! stops a device thread.
+
+use crate::recognizer::GestureType;
+
+/// Topic and payload published for `gesture` on `device_id`.
+pub fn gesture_message(topic: &str, device_id: &str, gesture: GestureType) -> (String, String) {
+    (topic.to_string(), format!("{device_id}/{gesture}"))
+}
+
+#[cfg(feature = "mqtt")]
+mod imp {
+    use std::thread;
+    use std::time::Duration;
+
+    use log::{debug, warn};
+    use rumqttc::{Client, MqttOptions, QoS};
+
+    use super::gesture_message;
+    use crate::config::MqttConfig;
+    use crate::recognizer::GestureType;
+
+    /// How long to wait before reconnecting to an unreachable broker.
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+    /// Publishes buffered while the broker is unreachable.
+    const REQUEST_CAPACITY: usize = 16;
+
+    /// Handle for publishing gestures; cheap to clone across device threads.
+    #[derive(Clone)]
+    pub struct MqttPublisher {
+        client: Client,
+        topic: String,
+    }
+
+    impl MqttPublisher {
+        /// Start a background connection to the configured broker.
+        pub fn connect(config: &MqttConfig) -> Self {
+            let client_id = format!("bodgestr-{}", std::process::id());
+            let mut options = MqttOptions::new(client_id, &config.broker, config.port);
+            options.set_keep_alive(Duration::from_secs(30));
+            let (client, mut connection) = Client::new(options, REQUEST_CAPACITY);
+
+            let broker = format!("{}:{}", config.broker, config.port);
+            thread::Builder::new()
+                .name("mqtt".into())
+                .spawn(move || {
+                    for event in connection.iter() {
+                        match event {
+                            Ok(event) => debug!("MQTT: {event:?}"),
+                            Err(e) => {
+                                warn!("MQTT broker {broker} unavailable: {e}");
+                                thread::sleep(RECONNECT_DELAY);
+                            }
+                        }
+                    }
+                })
+                .expect("Failed to spawn MQTT thread");
+
+            Self {
+                client,
+                topic: config.topic.clone(),
+            }
+        }
+
+        /// Queue `device_id/gesture` for publishing without blocking.
+        pub fn publish(&self, device_id: &str, gesture: GestureType) {
+            let (topic, payload) = gesture_message(&self.topic, device_id, gesture);
+            if let Err(e) = self
+                .client
+                .try_publish(topic, QoS::AtMostOnce, false, payload)
+            {
+                warn!("Failed to publish {device_id}/{gesture} over MQTT: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "mqtt"))]
+mod imp {
+    use log::warn;
+
+    use crate::config::MqttConfig;
+    use crate::recognizer::GestureType;
+
+    /// Placeholder for builds without the `mqtt` feature.
+    #[derive(Clone)]
+    pub struct MqttPublisher {
+        _private: (),
+    }
+
+    impl MqttPublisher {
+        /// Warn that `[global.mqtt]` is ignored by this build.
+        pub fn connect(_config: &MqttConfig) -> Self {
+            warn!("[global.mqtt] is set but bodgestr was built without the mqtt feature");
+            Self { _private: () }
+        }
+
+        /// No-op: this build cannot publish.
+        pub fn publish(&self, _device_id: &str, _gesture: GestureType) {}
+    }
+}
+
+pub use imp::MqttPublisher;
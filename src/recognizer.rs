@@ -1,13 +1,22 @@
 //! Gesture recognition engine for touch input events.
-use std::collections::HashMap;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
-use strum::{Display, EnumString, IntoStaticStr};
+use log::{debug, warn};
+use strum::{Display, EnumCount, EnumString, FromRepr, IntoStaticStr};
 
-use crate::config::ValidatedThresholds;
+use crate::config::{PrimaryFinger, ValidatedThresholds, YAxis};
 
 /// Supported gesture types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, IntoStaticStr)]
+///
+/// Parsing via `FromStr` is case-insensitive (`"Swipe_Left"` → `SwipeLeft`).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumCount, EnumString, FromRepr, IntoStaticStr,
+)]
+#[strum(ascii_case_insensitive)]
 pub enum GestureType {
     #[strum(serialize = "swipe_left")]
     SwipeLeft,
@@ -27,6 +36,188 @@ pub enum GestureType {
     PinchIn,
     #[strum(serialize = "pinch_out")]
     PinchOut,
+    #[strum(serialize = "long_press_drag_start")]
+    LongPressDragStart,
+    #[strum(serialize = "long_press_drag_end")]
+    LongPressDragEnd,
+    #[strum(serialize = "swipe_begin")]
+    SwipeBegin,
+    #[strum(serialize = "swipe_cancel")]
+    SwipeCancel,
+    #[strum(serialize = "touch_down")]
+    TouchDown,
+    #[strum(serialize = "touch_up")]
+    TouchUp,
+    #[strum(serialize = "held_tap_secondary")]
+    HeldTapSecondary,
+    #[strum(serialize = "pinch_out_repeat")]
+    PinchOutRepeat,
+    #[strum(serialize = "circle_clockwise")]
+    CircleClockwise,
+    #[strum(serialize = "circle_counter_clockwise")]
+    CircleCounterClockwise,
+    #[strum(serialize = "corner_down_right")]
+    CornerDownRight,
+    #[strum(serialize = "corner_down_left")]
+    CornerDownLeft,
+    #[strum(serialize = "corner_up_right")]
+    CornerUpRight,
+    #[strum(serialize = "corner_up_left")]
+    CornerUpLeft,
+    #[strum(serialize = "corner_left_down")]
+    CornerLeftDown,
+    #[strum(serialize = "corner_left_up")]
+    CornerLeftUp,
+    #[strum(serialize = "corner_right_down")]
+    CornerRightDown,
+    #[strum(serialize = "corner_right_up")]
+    CornerRightUp,
+    #[strum(serialize = "swipe_left_then_tap")]
+    SwipeLeftThenTap,
+    #[strum(serialize = "swipe_right_then_tap")]
+    SwipeRightThenTap,
+    #[strum(serialize = "swipe_up_then_tap")]
+    SwipeUpThenTap,
+    #[strum(serialize = "swipe_down_then_tap")]
+    SwipeDownThenTap,
+    #[strum(serialize = "hold_swipe_left")]
+    HoldSwipeLeft,
+    #[strum(serialize = "hold_swipe_right")]
+    HoldSwipeRight,
+    #[strum(serialize = "hold_swipe_up")]
+    HoldSwipeUp,
+    #[strum(serialize = "hold_swipe_down")]
+    HoldSwipeDown,
+    #[strum(serialize = "long_swipe_left")]
+    LongSwipeLeft,
+    #[strum(serialize = "long_swipe_right")]
+    LongSwipeRight,
+    #[strum(serialize = "long_swipe_up")]
+    LongSwipeUp,
+    #[strum(serialize = "long_swipe_down")]
+    LongSwipeDown,
+}
+
+/// Number of [`GestureType`] variants, for fixed-size per-gesture arrays.
+pub const NUM_GESTURES: usize = GestureType::COUNT;
+
+// `GestureRecognizer::disabled` keeps one bit per gesture.
+const _: () = assert!(NUM_GESTURES <= u64::BITS as usize);
+
+/// How far a mapped event timestamp may stray from the recognizer's clock
+/// before the timestamp-to-clock anchor is reset.
+pub const MAX_EVENT_CLOCK_DRIFT: Duration = Duration::from_secs(1);
+
+impl GestureType {
+    /// Position of this variant, contiguous in `0..NUM_GESTURES`.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Inverse of [`index`](Self::index); `None` if `index >= NUM_GESTURES`.
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::from_repr(index)
+    }
+
+    /// Every gesture type, in index order.
+    pub fn all() -> impl Iterator<Item = GestureType> {
+        (0..NUM_GESTURES).filter_map(Self::from_index)
+    }
+
+    /// The compound of this swipe followed by a tap; `None` for non-swipes.
+    pub fn then_tap(self) -> Option<GestureType> {
+        match self {
+            GestureType::SwipeLeft => Some(GestureType::SwipeLeftThenTap),
+            GestureType::SwipeRight => Some(GestureType::SwipeRightThenTap),
+            GestureType::SwipeUp => Some(GestureType::SwipeUpThenTap),
+            GestureType::SwipeDown => Some(GestureType::SwipeDownThenTap),
+            _ => None,
+        }
+    }
+
+    /// This swipe started after a pause; `None` for non-swipes.
+    pub fn after_hold(self) -> Option<GestureType> {
+        match self {
+            GestureType::SwipeLeft => Some(GestureType::HoldSwipeLeft),
+            GestureType::SwipeRight => Some(GestureType::HoldSwipeRight),
+            GestureType::SwipeUp => Some(GestureType::HoldSwipeUp),
+            GestureType::SwipeDown => Some(GestureType::HoldSwipeDown),
+            _ => None,
+        }
+    }
+
+    /// This swipe carried on past `long_swipe_distance_pct`; `None` for
+    /// non-swipes.
+    pub fn long_swipe(self) -> Option<GestureType> {
+        match self {
+            GestureType::SwipeLeft => Some(GestureType::LongSwipeLeft),
+            GestureType::SwipeRight => Some(GestureType::LongSwipeRight),
+            GestureType::SwipeUp => Some(GestureType::LongSwipeUp),
+            GestureType::SwipeDown => Some(GestureType::LongSwipeDown),
+            _ => None,
+        }
+    }
+
+    /// A completed swipe in one of the four directions.
+    pub fn is_swipe(self) -> bool {
+        matches!(
+            self,
+            GestureType::SwipeLeft
+                | GestureType::SwipeRight
+                | GestureType::SwipeUp
+                | GestureType::SwipeDown
+        )
+    }
+}
+
+/// Progress of a single-finger touch toward a long-press drag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum DragPhase {
+    /// Finger is down and has stayed within `tap_distance_max`.
+    #[default]
+    Holding,
+    /// Held in place past `long_press_time_min` - movement now starts a drag.
+    Armed,
+    /// Moved after arming; `LongPressDragStart` not yet reported.
+    Started,
+    /// Drag start reported; release reports `LongPressDragEnd`.
+    Dragging,
+    /// Moved before the long-press time - an ordinary touch.
+    Moved,
+}
+
+/// A recognized gesture together with the kinematics of the touch that
+/// produced it - used for diagnostics such as monitor mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GestureDetails {
+    pub gesture: GestureType,
+    /// Direction from start to end point in degrees (0° = right, 90° = down
+    /// on the visible screen).
+    pub angle_deg: f64,
+    /// Straight-line distance from start to end point, in device units.
+    pub distance: f64,
+    /// Seconds between the first and last touch point.
+    pub duration: f64,
+    /// Number of distinct fingers seen during the touch.
+    pub finger_count: usize,
+    /// Consecutive taps a `Tap` stands for (2 for `DoubleTap`, 1 for
+    /// everything else).
+    pub tap_count: usize,
+    /// Center and scale of a pinch; `None` for other gestures.
+    pub pinch: Option<PinchDetails>,
+    /// Every recorded point of the touch, in device units; only kept with
+    /// [`with_trajectory`](GestureRecognizer::with_trajectory).
+    pub trajectory: Option<Vec<TouchPoint>>,
+}
+
+/// Where two fingers pinched and by how much - what a zoom needs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinchDetails {
+    /// Midpoint between the two fingers' latest points, in device units.
+    pub center: (f64, f64),
+    /// Latest finger distance over the first: above 1 when spreading,
+    /// below 1 when pinching in.
+    pub scale: f64,
 }
 
 /// Represents a single touch point.
@@ -44,10 +235,293 @@ impl TouchPoint {
     }
 }
 
+/// Classify the movement from `start` to `end` as a swipe, if it is one.
+///
+/// Pure geometry: the movement must finish within `swipe_time_max`, cover at
+/// least `swipe_distance_min_pct` of the axis span (or
+/// `swipe_distance_min_px` device units, when set), and stay within
+/// `angle_tolerance_deg` of that axis.  With `swipe_velocity_min` set it must
+/// also average at least that many axis spans per second, and with
+/// `swipe_reject_band_deg` set its angle must stay that far from 45°.  Y is
+/// assumed to grow downward.
+pub fn classify_swipe(
+    start: TouchPoint,
+    end: TouchPoint,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    thresholds: &ValidatedThresholds,
+) -> Option<GestureType> {
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let dt = end.time.duration_since(start.time).as_secs_f64();
+    let th = thresholds;
+
+    if dt >= th.swipe_time_max {
+        return None;
+    }
+
+    let x_span = x_range.1 - x_range.0;
+    let y_span = y_range.1 - y_range.0;
+    let far_enough = |travel: f64, span: f64| {
+        travel
+            >= th
+                .swipe_distance_min_px
+                .unwrap_or(span * th.swipe_distance_min_pct)
+    };
+    let fast_enough = |travel: f64, span: f64| {
+        th.swipe_velocity_min
+            .is_none_or(|min| dt <= 0.0 || travel / span / dt >= min)
+    };
+
+    // Dead zone around the diagonals: ambiguous motions are no swipe at all.
+    let angle = dy.abs().atan2(dx.abs()).to_degrees();
+    if th
+        .swipe_reject_band_deg
+        .is_some_and(|band| (angle - 45.0).abs() < band)
+    {
+        return None;
+    }
+
+    // Horizontal swipe
+    if far_enough(dx.abs(), x_span)
+        && dy.abs().atan2(dx.abs()).to_degrees() <= th.angle_tolerance_deg
+        && fast_enough(dx.abs(), x_span)
+    {
+        return Some(if dx > 0.0 {
+            GestureType::SwipeRight
+        } else {
+            GestureType::SwipeLeft
+        });
+    }
+
+    // Vertical swipe
+    if far_enough(dy.abs(), y_span)
+        && dx.abs().atan2(dy.abs()).to_degrees() <= th.angle_tolerance_deg
+        && fast_enough(dy.abs(), y_span)
+    {
+        return Some(if dy > 0.0 {
+            GestureType::SwipeDown
+        } else {
+            GestureType::SwipeUp
+        });
+    }
+
+    None
+}
+
+/// Largest distance of a corner leg's points from the straight line between
+/// its ends, as a fraction of the leg's length.
+const CORNER_STRAIGHTNESS: f64 = 0.15;
+
+/// The corner gesture for a swipe along `first` followed by one along
+/// `second`, or `None` unless one leg is horizontal and the other vertical.
+fn corner_gesture(first: GestureType, second: GestureType) -> Option<GestureType> {
+    use GestureType::*;
+    Some(match (first, second) {
+        (SwipeDown, SwipeRight) => CornerDownRight,
+        (SwipeDown, SwipeLeft) => CornerDownLeft,
+        (SwipeUp, SwipeRight) => CornerUpRight,
+        (SwipeUp, SwipeLeft) => CornerUpLeft,
+        (SwipeLeft, SwipeDown) => CornerLeftDown,
+        (SwipeLeft, SwipeUp) => CornerLeftUp,
+        (SwipeRight, SwipeDown) => CornerRightDown,
+        (SwipeRight, SwipeUp) => CornerRightUp,
+        _ => return None,
+    })
+}
+
+/// Whether every point lies within [`CORNER_STRAIGHTNESS`] of the line
+/// from the first point to the last.
+fn is_straight(points: &[TouchPoint]) -> bool {
+    let (Some(a), Some(b)) = (points.first(), points.last()) else {
+        return false;
+    };
+    let length = a.distance_to(b);
+    length > 0.0
+        && points
+            .iter()
+            .all(|p| distance_from_line(*p, *a, *b) <= length * CORNER_STRAIGHTNESS)
+}
+
+/// Perpendicular distance of `p` from the line through `a` and `b`.
+fn distance_from_line(p: TouchPoint, a: TouchPoint, b: TouchPoint) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    ((p.x - a.x) * dy - (p.y - a.y) * dx).abs() / dx.hypot(dy)
+}
+
+/// Radius spread allowed for a circle when `circle_radius_tolerance_pct` is unset.
+pub const DEFAULT_CIRCLE_RADIUS_TOLERANCE: f64 = 0.25;
+
+/// Fewest points a path needs before it is considered as a circle.
+const CIRCLE_POINTS_MIN: usize = 8;
+
+/// Span assumed for an axis whose declared range is empty or inverted.
+pub const FALLBACK_AXIS_SPAN: f64 = 4096.0;
+
+/// Replace a degenerate axis range (`min >= max`, or not finite) with one
+/// [`FALLBACK_AXIS_SPAN`] wide, so percentage thresholds stay meaningful.
+fn sane_range((min, max): (f64, f64), axis: &str) -> (f64, f64) {
+    if min.is_finite() && max.is_finite() && min < max {
+        return (min, max);
+    }
+    let start = if min.is_finite() { min } else { 0.0 };
+    warn!(
+        "Degenerate {axis} axis range {min}..{max}; assuming {start}..{}",
+        start + FALLBACK_AXIS_SPAN
+    );
+    (start, start + FALLBACK_AXIS_SPAN)
+}
+
+/// Clamp a coordinate into the device's declared `(min, max)` range.
+///
+/// Some panels overshoot their declared range slightly, which would skew the
+/// percentage-based thresholds.  Empty ranges (unknown axis info) are left
+/// unclamped.
+fn clamp_to_range(value: f64, (min, max): (f64, f64), axis: &str) -> f64 {
+    if min >= max || (min..=max).contains(&value) {
+        return value;
+    }
+    let clamped = value.clamp(min, max);
+    debug!("Clamped {axis} {value} to declared range {min}..{max}");
+    clamped
+}
+
+/// Source of the current time for the recognizer.
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The real monotonic clock - the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic tests.
+///
+/// Clones share the same time, so a test can keep one handle and pass
+/// another to [`GestureRecognizer::with_clock`].
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl MockClock {
+    /// Start the clock at an arbitrary fixed instant.
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Shared handle to the recognizer's clock; defaults to [`SystemClock`].
+#[derive(Debug, Clone)]
+struct ClockHandle(Arc<dyn Clock>);
+
+impl Default for ClockHandle {
+    fn default() -> Self {
+        Self(Arc::new(SystemClock))
+    }
+}
+
 /// Recognizes gestures from touch input events.
 #[derive(Default)]
 pub struct GestureRecognizer {
     pub thresholds: ValidatedThresholds,
+    /// Thresholds replacing `thresholds` when deciding on one gesture, in
+    /// [`GestureType::index`] order.
+    gesture_thresholds: Vec<(GestureType, ValidatedThresholds)>,
+    pub y_axis: YAxis,
+    /// Contact single-finger classification follows when several are down.
+    pub primary_finger: PrimaryFinger,
+    /// Report long-press-then-move as `LongPressDragStart`/`End`.
+    pub long_press_drag: bool,
+    /// Report an L-shaped stroke as a `Corner*` gesture.
+    pub corner_gestures: bool,
+    /// Window after a swipe within which a tap near its end is reported as
+    /// `Swipe*ThenTap`; `None` disables the compound.
+    pub swipe_then_tap: Option<Duration>,
+    /// Minimum pause before a swipe for it to be reported as `HoldSwipe*`;
+    /// `None` disables the distinction.
+    pub hold_before_swipe: Option<Duration>,
+    /// Classify swipes in a square 0-1 space instead of device units.
+    pub normalize_coordinates: bool,
+    /// Hold back a new contact's first point until both axes were reported.
+    pub require_both_axes: bool,
+    /// Report every contact as `TouchDown`/`TouchUp`, regardless of classification.
+    pub touch_events: bool,
+    /// Attach the recorded touch points to each gesture's details.
+    pub trajectory: bool,
+    /// Bound on `touch_points`; see [`with_max_track_points`](Self::with_max_track_points).
+    pub max_track_points: Option<usize>,
+    /// Count up to this many consecutive taps into one `Tap`; at most 1
+    /// keeps the plain tap/double-tap behaviour.
+    pub tap_count_max: usize,
+    /// Taps counted so far in the current run, with `tap_count_max`.
+    tap_streak: usize,
+    /// Report `Tap` on finger-up instead of waiting `double_tap_interval`
+    /// for a second tap.
+    pub immediate_tap: bool,
+    /// Hold back a gesture identical to the last one reported until every
+    /// finger has lifted.
+    pub suppress_repeat: bool,
+    /// Report at most one swipe until every finger has lifted.
+    pub swipe_once_per_contact: bool,
+    /// More simultaneous contacts than this are a palm, not a gesture.
+    pub max_fingers: Option<usize>,
+    /// Window for continuing a contact under a tracking ID that replaced
+    /// its own; see [`with_id_merge`](Self::with_id_merge).
+    pub id_merge: Option<Duration>,
+    /// Drop a contact that has not moved or lifted for this long; see
+    /// [`with_stale_touch_timeout`](Self::with_stale_touch_timeout).
+    pub stale_touch_timeout: Option<Duration>,
+    /// Contact whose tracking ID was just replaced in the current slot.
+    merge_candidate: Option<i32>,
+    /// A palm was seen since the last frame without contacts; nothing is
+    /// reported until every finger lifts.
+    palm: bool,
+    /// A second finger landed while a single-finger touch was already
+    /// moving; only multi-finger gestures are reported until every finger
+    /// lifts.
+    interrupted: bool,
+    /// Most contacts down at once since a frame without contacts, so a tap
+    /// whose fingers lift a frame apart still counts every finger.
+    peak_fingers: usize,
+    /// A finger lifted while others stayed down; what remains of the touch
+    /// was already recognized with the first lift.
+    partly_lifted: bool,
+    /// Gestures never reported, one bit per [`GestureType::index`].
+    disabled: u64,
+    /// Last gesture reported since a frame without contacts, for
+    /// `suppress_repeat`.
+    last_reported: Option<GestureType>,
+    /// A swipe was reported since a frame without contacts, for
+    /// `swipe_once_per_contact`.
+    swipe_reported: bool,
+    /// Last reported swipe and where and when it ended, for `swipe_then_tap`.
+    last_swipe: Option<(GestureType, TouchPoint)>,
     x_range: (f64, f64),
     y_range: (f64, f64),
 
@@ -62,20 +536,253 @@ pub struct GestureRecognizer {
     pending_x: Option<f64>,
     pending_y: Option<f64>,
     pending_tracking_id: i32,
+    has_tracking_id: bool,
+    /// Multitouch slot the next events refer to, and each slot's contact.
+    current_slot: i32,
+    slot_ids: HashMap<i32, i32>,
+    /// Slots with a finger down; unlike the touch state this survives
+    /// `reset`, so it tells when every finger has lifted.
+    slots_down: HashSet<i32>,
 
     pub pending_tap: bool,
+    pending_tap_details: Option<GestureDetails>,
+    drag_phase: DragPhase,
+    /// `SwipeBegin` was reported for the current touch.
+    swipe_begun: bool,
+    /// While fingers are spread: when the hold (or last repeat) began and
+    /// the finger distance at that point.
+    pinch_hold: Option<(Instant, f64)>,
+    /// Event time for the next flushed point, from [`set_event_time`](Self::set_event_time).
+    pending_time: Option<Instant>,
+    /// An event timestamp and the clock reading it was first seen at, used to
+    /// map later timestamps onto the clock.
+    time_anchor: Option<(SystemTime, Instant)>,
+    clock: ClockHandle,
 }
 
 impl GestureRecognizer {
+    /// Create a recognizer for a device with the given axis ranges.  An
+    /// empty or inverted range is replaced by one [`FALLBACK_AXIS_SPAN`] wide.
     pub fn new(thresholds: ValidatedThresholds, x_range: (f64, f64), y_range: (f64, f64)) -> Self {
         Self {
             thresholds,
-            x_range,
-            y_range,
+            x_range: sane_range(x_range, "X"),
+            y_range: sane_range(y_range, "Y"),
             ..Default::default()
         }
     }
 
+    /// Set the direction in which device Y grows on the visible screen.
+    pub fn with_y_axis(mut self, y_axis: YAxis) -> Self {
+        self.y_axis = y_axis;
+        self
+    }
+
+    /// Choose which contact single-finger gestures follow.
+    pub fn with_primary_finger(mut self, policy: PrimaryFinger) -> Self {
+        self.primary_finger = policy;
+        self
+    }
+
+    /// Classify swipes with both axes scaled to 0-1, so a diagonal is 45°
+    /// and `angle_tolerance_deg` means the same on any aspect ratio.
+    pub fn with_normalized_coordinates(mut self, enabled: bool) -> Self {
+        self.normalize_coordinates = enabled;
+        self
+    }
+
+    /// Commit a new contact's first point only once both X and Y were seen
+    /// for it, for panels that report the axes in separate frames.  Without
+    /// this the missing axis is borrowed from another finger, or is 0.
+    pub fn with_require_both_axes(mut self, enabled: bool) -> Self {
+        self.require_both_axes = enabled;
+        self
+    }
+
+    /// Enable long-press drag recognition.
+    pub fn with_long_press_drag(mut self, enabled: bool) -> Self {
+        self.long_press_drag = enabled;
+        self
+    }
+
+    /// Report a single-finger stroke of two straight swipes joined at a
+    /// right angle - e.g. down, then right - as a `Corner*` gesture.
+    pub fn with_corner_gestures(mut self, enabled: bool) -> Self {
+        self.corner_gestures = enabled;
+        self
+    }
+
+    /// Report a tap within `window` after a swipe, near where the swipe
+    /// ended, as `Swipe*ThenTap` instead of a tap.
+    pub fn with_swipe_then_tap(mut self, window: Option<Duration>) -> Self {
+        self.swipe_then_tap = window;
+        self
+    }
+
+    /// Report a swipe whose finger first rested within `tap_distance_max`
+    /// of where it landed for at least `hold` as `HoldSwipe*`.
+    pub fn with_hold_before_swipe(mut self, hold: Option<Duration>) -> Self {
+        self.hold_before_swipe = hold;
+        self
+    }
+
+    /// Keep at most `max` touch points per contact (unbounded with `None`).
+    ///
+    /// Beyond the cap the oldest intermediate point is dropped; each finger's
+    /// first and latest points and the start finger's farthest point are
+    /// always kept, so classification sees the same start, end and peak.
+    pub fn with_max_track_points(mut self, max: Option<usize>) -> Self {
+        self.max_track_points = max;
+        self
+    }
+
+    /// Never report any of `gestures` (`[global] disabled_gestures`).
+    pub fn with_disabled_gestures(mut self, gestures: &[GestureType]) -> Self {
+        for gesture in gestures {
+            self.disabled |= 1 << gesture.index();
+        }
+        self
+    }
+
+    /// Report consecutive taps as one `Tap` carrying their count, up to
+    /// `max` taps.  `DoubleTap` is then never reported.
+    pub fn with_tap_count_max(mut self, max: usize) -> Self {
+        self.tap_count_max = max;
+        self
+    }
+
+    /// Report `Tap` as soon as the finger lifts, without waiting
+    /// `double_tap_interval` for a second tap - for devices where nothing
+    /// listens for `DoubleTap`, which is then never reported.  Counting taps
+    /// for `tap_count_max` takes precedence.
+    pub fn with_immediate_tap(mut self, enabled: bool) -> Self {
+        self.immediate_tap = enabled;
+        self
+    }
+
+    /// Report a gesture again only after a frame with no finger down, so the
+    /// same gesture is not reported on consecutive frames of one contact.
+    pub fn with_suppress_repeat(mut self, enabled: bool) -> Self {
+        self.suppress_repeat = enabled;
+        self
+    }
+
+    /// Report only the first swipe of an unbroken contact: fingers that
+    /// lift one at a time, or a finger that keeps sweeping after another
+    /// lifted, add no further swipes until every finger is up.
+    pub fn with_swipe_once_per_contact(mut self, enabled: bool) -> Self {
+        self.swipe_once_per_contact = enabled;
+        self
+    }
+
+    /// Decide on each listed gesture with its own thresholds instead of the
+    /// device's, e.g. a shorter `double_tap_interval` for `DoubleTap` only.
+    pub fn with_gesture_thresholds(
+        mut self,
+        thresholds: impl IntoIterator<Item = (GestureType, ValidatedThresholds)>,
+    ) -> Self {
+        self.gesture_thresholds = thresholds.into_iter().collect();
+        self.gesture_thresholds
+            .sort_by_key(|(gesture, _)| gesture.index());
+        self
+    }
+
+    /// Thresholds deciding on `gesture`.
+    fn thresholds_for(&self, gesture: GestureType) -> &ValidatedThresholds {
+        self.gesture_thresholds
+            .iter()
+            .find(|(g, _)| *g == gesture)
+            .map_or(&self.thresholds, |(_, th)| th)
+    }
+
+    /// Thresholds of the gesture a second tap would complete: `Tap` while
+    /// counting taps for `tap_count`, `DoubleTap` otherwise.
+    fn tap_window(&self) -> &ValidatedThresholds {
+        self.thresholds_for(if self.tap_count_max > 1 {
+            GestureType::Tap
+        } else {
+            GestureType::DoubleTap
+        })
+    }
+
+    /// Treat more than `max` simultaneous contacts as a palm: the touch is
+    /// cancelled and nothing is reported until every finger lifts.
+    pub fn with_max_fingers(mut self, max: Option<usize>) -> Self {
+        self.max_fingers = max;
+        self
+    }
+
+    /// Treat a new tracking ID replacing another in the same slot as the
+    /// same finger when its first point comes within `window` of the old
+    /// contact's last one and within `tap_distance_max` of it.  Keeps
+    /// controllers that churn IDs for one contact from inflating the finger
+    /// count (and faking pinches).
+    pub fn with_id_merge(mut self, window: Option<Duration>) -> Self {
+        self.id_merge = window;
+        self
+    }
+
+    /// Forget a contact whose last update is older than `timeout` when
+    /// another one reports, as if it had lifted.  Recovers from a release
+    /// the panel never sent (e.g. after `SYN_DROPPED`), which would
+    /// otherwise turn every later touch into a multi-finger one.
+    pub fn with_stale_touch_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.stale_touch_timeout = timeout;
+        self
+    }
+
+    /// Note that the finger in the current slot lifted.  Once no finger is
+    /// down, the next gesture is reported even if it repeats the last one,
+    /// and a palm or second-finger interruption is over.
+    pub fn release_contact(&mut self) {
+        self.slots_down.remove(&self.current_slot);
+        if self.slots_down.is_empty() {
+            self.last_reported = None;
+            self.swipe_reported = false;
+            self.palm = false;
+            self.interrupted = false;
+            self.peak_fingers = 0;
+            self.partly_lifted = false;
+        } else {
+            self.partly_lifted = true;
+        }
+    }
+
+    /// Enable `TouchDown`/`TouchUp` reporting.
+    pub fn with_touch_events(mut self, enabled: bool) -> Self {
+        self.touch_events = enabled;
+        self
+    }
+
+    /// Attach the recorded touch points to every [`GestureDetails`].
+    pub fn with_trajectory(mut self, enabled: bool) -> Self {
+        self.trajectory = enabled;
+        self
+    }
+
+    /// Read time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = ClockHandle(Arc::new(clock));
+        self
+    }
+
+    /// Current time according to the recognizer's clock.
+    pub fn now(&self) -> Instant {
+        self.clock.0.now()
+    }
+
+    /// Map a device point into screen orientation, where Y always grows
+    /// toward the bottom of the visible screen.
+    fn to_screen(&self, point: TouchPoint) -> TouchPoint {
+        match self.y_axis {
+            YAxis::Down => point,
+            YAxis::Up => TouchPoint {
+                y: -point.y,
+                ..point
+            },
+        }
+    }
+
     /// Reset touch tracking.
     pub fn reset(&mut self) {
         self.touch_start = None;
@@ -85,6 +792,13 @@ impl GestureRecognizer {
         self.pending_x = None;
         self.pending_y = None;
         self.pending_tracking_id = 0;
+        self.has_tracking_id = false;
+        self.current_slot = 0;
+        self.slot_ids.clear();
+        self.merge_candidate = None;
+        self.drag_phase = DragPhase::default();
+        self.swipe_begun = false;
+        self.pinch_hold = None;
     }
 
     /// Buffer a pending X coordinate until `SYN_REPORT`.
@@ -100,87 +814,718 @@ impl GestureRecognizer {
     /// Set the tracking ID for the next touch point.
     pub fn set_tracking_id(&mut self, id: i32) {
         self.pending_tracking_id = id;
+        self.has_tracking_id = true;
+        let replaced = self.slot_ids.insert(self.current_slot, id);
+        self.merge_candidate = replaced.filter(|&old| self.id_merge.is_some() && old != id);
+        self.slots_down.insert(self.current_slot);
+    }
+
+    /// Continue contact `old` under the tracking ID that replaced it in the
+    /// current slot, if the new point at `time` is close enough to its last
+    /// one in time and space.
+    fn merge_churned_id(&mut self, old: i32, time: Instant) {
+        let Some(window) = self.id_merge else {
+            return;
+        };
+        let Some(last) = self.active_touches.get(&old) else {
+            return;
+        };
+        if self.active_touches.contains_key(&self.pending_tracking_id) {
+            return;
+        }
+        let x = self.pending_x.unwrap_or(last.x);
+        let y = self.pending_y.unwrap_or(last.y);
+        if time.saturating_duration_since(last.time) > window
+            || (x - last.x).hypot(y - last.y) > self.thresholds.tap_distance_max
+        {
+            return;
+        }
+        debug!(
+            "Tracking ID {} continues contact {old}",
+            self.pending_tracking_id
+        );
+        self.slot_ids.insert(self.current_slot, old);
+        self.pending_tracking_id = old;
+    }
+
+    /// Drop contacts other than the pending one that have not been updated
+    /// within `stale_touch_timeout` of `time`.
+    fn evict_stale_touches(&mut self, time: Instant) {
+        let Some(timeout) = self.stale_touch_timeout else {
+            return;
+        };
+        let stale: Vec<i32> = self
+            .active_touches
+            .iter()
+            .filter(|&(&id, last)| {
+                id != self.pending_tracking_id
+                    && time.saturating_duration_since(last.time) > timeout
+            })
+            .map(|(&id, _)| id)
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+        for id in &stale {
+            debug!("Contact {id} silent for over {timeout:?} - treating it as lifted");
+            self.active_touches.remove(id);
+            self.slot_ids.retain(|slot, slot_id| {
+                let keep = slot_id != id;
+                if !keep {
+                    self.slots_down.remove(slot);
+                }
+                keep
+            });
+        }
+        self.touch_points
+            .retain(|p| !stale.contains(&p.tracking_id));
+        self.touch_start = self.touch_points.first().copied();
+        self.touch_current = self.touch_points.last().copied();
+        self.pinch_hold = None;
+
+        let others_down = self
+            .active_touches
+            .keys()
+            .any(|&id| id != self.pending_tracking_id);
+        if !others_down {
+            // As when every finger lifts.
+            self.last_reported = None;
+            self.swipe_reported = false;
+            self.palm = false;
+            self.interrupted = false;
+            self.peak_fingers = 0;
+            self.partly_lifted = false;
+        }
+    }
+
+    /// Switch to multitouch `slot`; following coordinates belong to the
+    /// contact last seen in it.
+    pub fn set_slot(&mut self, slot: i32) {
+        self.current_slot = slot;
+        if let Some(&id) = self.slot_ids.get(&slot) {
+            self.pending_tracking_id = id;
+        }
+    }
+
+    /// Start a contact signalled by `BTN_TOUCH`.  On devices without tracking
+    /// IDs this discards stale touch state left by a missed release; when a
+    /// tracking ID already identifies the contact it is a no-op.  Buffered
+    /// coordinates are kept, since they may belong to the same frame.
+    pub fn begin_touch(&mut self) {
+        if self.has_tracking_id {
+            return;
+        }
+        self.touch_start = None;
+        self.touch_current = None;
+        self.touch_points.clear();
+        self.active_touches.clear();
+        self.drag_phase = DragPhase::default();
+        self.swipe_begun = false;
+        self.pinch_hold = None;
+    }
+
+    /// Stamp the next flushed point with the kernel's event `time` instead
+    /// of the clock's current time.
+    ///
+    /// Event times are mapped onto the clock relative to an anchor, so spacing
+    /// between frames follows the hardware.  The anchor is reset when the
+    /// mapping drifts more than [`MAX_EVENT_CLOCK_DRIFT`] from the clock, e.g.
+    /// after the wall clock jumps.
+    pub fn set_event_time(&mut self, time: SystemTime) {
+        let now = self.now();
+        let mapped = self
+            .time_anchor
+            .and_then(|(at, instant)| match time.duration_since(at) {
+                Ok(ahead) => instant.checked_add(ahead),
+                Err(e) => instant.checked_sub(e.duration()),
+            })
+            .filter(|mapped| {
+                now.saturating_duration_since(*mapped) <= MAX_EVENT_CLOCK_DRIFT
+                    && mapped.saturating_duration_since(now) <= MAX_EVENT_CLOCK_DRIFT
+            });
+        self.pending_time = Some(mapped.unwrap_or_else(|| {
+            self.time_anchor = Some((time, now));
+            now
+        }));
     }
 
     /// Commit buffered X/Y as a complete `TouchPoint` on `SYN_REPORT`.
     pub fn flush_pending(&mut self) {
+        let event_time = self.pending_time.take();
         if self.pending_x.is_none() && self.pending_y.is_none() {
             return;
         }
+        let time = event_time.unwrap_or_else(|| self.now());
+        if let Some(old) = self.merge_candidate.take() {
+            self.merge_churned_id(old, time);
+        }
+        self.evict_stale_touches(time);
 
+        // An axis missing from this frame keeps the contact's last value.
+        let own = self.active_touches.get(&self.pending_tracking_id).copied();
+        if self.require_both_axes
+            && own.is_none()
+            && (self.pending_x.is_none() || self.pending_y.is_none())
+        {
+            // Keep the axis seen so far buffered for the next frame.
+            return;
+        }
+        let previous = own.or(self.touch_current);
         let point = TouchPoint {
             x: self
                 .pending_x
-                .unwrap_or_else(|| self.touch_current.map_or(0.0, |tc| tc.x)),
+                .map(|x| clamp_to_range(x, self.x_range, "X"))
+                .unwrap_or_else(|| previous.map_or(0.0, |p| p.x)),
             y: self
                 .pending_y
-                .unwrap_or_else(|| self.touch_current.map_or(0.0, |tc| tc.y)),
-            time: Instant::now(),
+                .map(|y| clamp_to_range(y, self.y_range, "Y"))
+                .unwrap_or_else(|| previous.map_or(0.0, |p| p.y)),
+            time,
             tracking_id: self.pending_tracking_id,
         };
+        if self.long_press_drag {
+            self.update_drag_phase(point);
+        }
+        if own.is_none() && self.active_touches.len() == 1 && self.single_touch_moved() {
+            debug!("Second finger during a single-finger touch - cancelling it");
+            self.interrupted = true;
+        }
         self.active_touches.insert(self.pending_tracking_id, point);
+        self.peak_fingers = self.peak_fingers.max(self.active_touches.len());
+        if !self.palm
+            && self
+                .max_fingers
+                .is_some_and(|max| self.active_touches.len() > max)
+        {
+            debug!(
+                "{} contacts - palm, ignoring touches until all lift",
+                self.active_touches.len()
+            );
+            self.palm = true;
+        }
         self.touch_points.push(point);
         self.touch_start.get_or_insert(point);
         self.touch_current = Some(point);
+        self.trim_track();
 
         self.pending_x = None;
         self.pending_y = None;
     }
 
+    /// The touch so far has moved beyond `tap_distance_max` from its start.
+    fn single_touch_moved(&self) -> bool {
+        self.touch_start
+            .zip(self.touch_current)
+            .is_some_and(|(start, current)| {
+                start.distance_to(&current) > self.thresholds.tap_distance_max
+            })
+    }
+
+    /// Drop one point once `touch_points` exceeds `max_track_points` - the
+    /// oldest that is neither its finger's first or latest point nor the
+    /// start finger's farthest point from the start.
+    fn trim_track(&mut self) {
+        let (Some(max), Some(start)) = (self.max_track_points, self.touch_start) else {
+            return;
+        };
+        if self.touch_points.len() <= max {
+            return;
+        }
+        let peak = self
+            .touch_points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.tracking_id == start.tracking_id)
+            .max_by(|(_, a), (_, b)| start.distance_to(a).total_cmp(&start.distance_to(b)))
+            .map(|(i, _)| i);
+        let points = &self.touch_points;
+        let removable = (0..points.len()).find(|&i| {
+            let id = points[i].tracking_id;
+            Some(i) != peak
+                && points[..i].iter().any(|p| p.tracking_id == id)
+                && points[i + 1..].iter().any(|p| p.tracking_id == id)
+        });
+        if let Some(i) = removable {
+            self.touch_points.remove(i);
+        }
+    }
+
+    /// Advance the long-press drag state with a newly reported point.
+    fn update_drag_phase(&mut self, point: TouchPoint) {
+        let Some(start) = self.touch_start else {
+            return;
+        };
+        if self.active_touches.len() > 1 || point.tracking_id != start.tracking_id {
+            self.drag_phase = DragPhase::Moved;
+            return;
+        }
+
+        // The finger stayed within the tap radius up to this frame.
+        let held = point.time.duration_since(start.time).as_secs_f64();
+        if self.drag_phase == DragPhase::Holding && held >= self.thresholds.long_press_time_min {
+            self.drag_phase = DragPhase::Armed;
+        }
+
+        // A wobble within the long-press drift allowance is not a drag.
+        let radius = self
+            .thresholds
+            .long_press_drift_max
+            .unwrap_or(self.thresholds.tap_distance_max);
+        if start.distance_to(&point) >= radius {
+            self.drag_phase = match self.drag_phase {
+                DragPhase::Holding => DragPhase::Moved,
+                DragPhase::Armed => DragPhase::Started,
+                phase => phase,
+            };
+        }
+    }
+
+    /// Report `LongPressDragStart` once, on the frame where a long-pressed
+    /// finger first moves beyond `tap_distance_max` (or `long_press_drift_max`).
+    pub fn check_long_press_drag(&mut self) -> Option<GestureType> {
+        self.check_long_press_drag_detailed().map(|d| d.gesture)
+    }
+
+    /// Like [`check_long_press_drag`](Self::check_long_press_drag), but also
+    /// returns the kinematics of the touch so far.
+    pub fn check_long_press_drag_detailed(&mut self) -> Option<GestureDetails> {
+        if self.drag_phase != DragPhase::Started {
+            return None;
+        }
+        self.drag_phase = DragPhase::Dragging;
+        self.report(GestureType::LongPressDragStart)
+    }
+
+    /// Report `SwipeBegin` once, on the frame where a single finger first
+    /// moves a swipe's `swipe_distance_min_pct`.  Only active when
+    /// `swipe_commit_distance_pct` is configured.
+    pub fn check_swipe_begin(&mut self) -> Option<GestureType> {
+        self.check_swipe_begin_detailed().map(|d| d.gesture)
+    }
+
+    /// Like [`check_swipe_begin`](Self::check_swipe_begin), but also returns
+    /// the kinematics of the touch so far.
+    pub fn check_swipe_begin_detailed(&mut self) -> Option<GestureDetails> {
+        self.thresholds.swipe_commit_distance_pct?;
+        // A long-pressed finger that moves is a drag, not a swipe.
+        if self.swipe_begun
+            || self.active_touches.len() != 1
+            || !matches!(self.drag_phase, DragPhase::Holding | DragPhase::Moved)
+        {
+            return None;
+        }
+        let (start, current) = (self.touch_start?, self.touch_current?);
+        self.swipe_past(start, current, self.thresholds.swipe_distance_min_pct)?;
+        self.swipe_begun = true;
+        self.report(GestureType::SwipeBegin)
+    }
+
+    /// Report `PinchOutRepeat` every `pinch_repeat_interval` seconds while
+    /// fingers stay spread past the pinch threshold and hold still (within
+    /// `tap_distance_max` of their distance at the last report).  Only
+    /// active when `pinch_repeat_interval` is configured.
+    pub fn check_pinch_repeat(&mut self) -> Option<GestureType> {
+        self.check_pinch_repeat_detailed().map(|d| d.gesture)
+    }
+
+    /// Like [`check_pinch_repeat`](Self::check_pinch_repeat), but also
+    /// returns the kinematics of the touch so far.
+    pub fn check_pinch_repeat_detailed(&mut self) -> Option<GestureDetails> {
+        let interval = self.thresholds.pinch_repeat_interval?;
+        if self.detect_pinch() != Some(GestureType::PinchOut) {
+            self.pinch_hold = None;
+            return None;
+        }
+        let (_, distance) = self.pinch_distances()?;
+        let now = self.touch_current?.time;
+        match self.pinch_hold {
+            Some((since, held)) if (distance - held).abs() < self.thresholds.tap_distance_max => {
+                if now.saturating_duration_since(since).as_secs_f64() < interval {
+                    return None;
+                }
+                self.pinch_hold = Some((now, held));
+                self.report(GestureType::PinchOutRepeat)
+            }
+            // Still spreading: restart the hold from here.
+            _ => {
+                self.pinch_hold = Some((now, distance));
+                None
+            }
+        }
+    }
+
+    /// Tentative swipe direction of the touch so far, without consuming any
+    /// state - for previewing the target before release.  `None` while the
+    /// motion is below the swipe thresholds or more than one finger is down.
+    pub fn current_direction(&self) -> Option<GestureType> {
+        if self.active_touches.len() != 1 {
+            return None;
+        }
+        let (start, current) = (self.touch_start?, self.touch_current?);
+        self.swipe_past(start, current, self.thresholds.swipe_distance_min_pct)
+    }
+
+    /// Progress of the touch so far toward firing a swipe, for animating a
+    /// preview: the tentative direction and the travel along its axis as a
+    /// fraction of `swipe_commit_distance_pct`, capped at 1.0.  Without a
+    /// commit distance the swipe minimum is the target.
+    ///
+    /// Below the swipe minimum the direction is the dominant axis of the
+    /// motion; from there on it is [`current_direction`](Self::current_direction).
+    /// `None` while the finger has not moved or more than one is down.
+    pub fn current_progress(&self) -> Option<(GestureType, f64)> {
+        if self.active_touches.len() != 1 {
+            return None;
+        }
+        let start = self.to_screen(self.touch_start?);
+        let current = self.to_screen(self.touch_current?);
+        let (dx, dy) = (current.x - start.x, current.y - start.y);
+        let direction = self.current_direction().or(match (dx, dy) {
+            (0.0, 0.0) => None,
+            _ if dx.abs() >= dy.abs() && dx < 0.0 => Some(GestureType::SwipeLeft),
+            _ if dx.abs() >= dy.abs() => Some(GestureType::SwipeRight),
+            _ if dy < 0.0 => Some(GestureType::SwipeUp),
+            _ => Some(GestureType::SwipeDown),
+        })?;
+        let (travel, span) = match direction {
+            GestureType::SwipeLeft | GestureType::SwipeRight => {
+                (dx.abs(), self.x_range.1 - self.x_range.0)
+            }
+            _ => (dy.abs(), self.y_range.1 - self.y_range.0),
+        };
+        let th = &self.thresholds;
+        let target = match th.swipe_commit_distance_pct {
+            Some(commit) => commit.max(th.swipe_distance_min_pct) * span,
+            None => th
+                .swipe_distance_min_px
+                .unwrap_or(th.swipe_distance_min_pct * span),
+        };
+        let progress = if target > 0.0 {
+            (travel / target).min(1.0)
+        } else {
+            1.0
+        };
+        Some((direction, progress))
+    }
+
+    /// Kinematics of the current touch, labelled as `gesture`.
+    pub fn touch_details(&self, gesture: GestureType) -> Option<GestureDetails> {
+        let start = self.to_screen(self.touch_start?);
+        let end = self.to_screen(self.touch_current?);
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        Some(GestureDetails {
+            gesture,
+            angle_deg: dy.atan2(dx).to_degrees(),
+            distance: dx.hypot(dy),
+            duration: end.time.duration_since(start.time).as_secs_f64(),
+            finger_count: match gesture {
+                GestureType::Tap => self.peak_fingers.max(self.active_touches.len()),
+                _ => self.active_touches.len(),
+            },
+            tap_count: match gesture {
+                GestureType::Tap => self.tap_streak.max(1),
+                GestureType::DoubleTap => 2,
+                _ => 1,
+            },
+            pinch: match gesture {
+                GestureType::PinchIn | GestureType::PinchOut | GestureType::PinchOutRepeat => {
+                    self.pinch_details()
+                }
+                _ => None,
+            },
+            trajectory: self.trajectory.then(|| self.touch_points.clone()),
+        })
+    }
+
+    /// Whether `gesture` is switched off by
+    /// [`with_disabled_gestures`](Self::with_disabled_gestures).
+    pub fn is_disabled(&self, gesture: GestureType) -> bool {
+        self.disabled & (1 << gesture.index()) != 0
+    }
+
+    /// Kinematics of the current touch as `gesture`, unless it is disabled
+    /// or a suppressed repeat.
+    fn report(&mut self, gesture: GestureType) -> Option<GestureDetails> {
+        if self.palm {
+            return None;
+        }
+        if self.is_disabled(gesture) {
+            debug!("{gesture} is disabled - ignored");
+            return None;
+        }
+        let details = self.touch_details(gesture)?;
+        if gesture.is_swipe() && self.swipe_once_per_contact {
+            if self.swipe_reported {
+                debug!("{gesture} in a contact that already swiped - suppressed");
+                return None;
+            }
+            self.swipe_reported = true;
+        }
+        if self.swipe_then_tap.is_some() {
+            let now = self.now();
+            self.last_swipe = gesture
+                .is_swipe()
+                .then(|| self.primary_track())
+                .flatten()
+                .map(|(_, end)| (gesture, TouchPoint { time: now, ..end }));
+        }
+        if self.suppress_repeat {
+            if self.last_reported == Some(gesture) {
+                debug!("{gesture} repeated without a lift - suppressed");
+                return None;
+            }
+            self.last_reported = Some(gesture);
+        }
+        Some(details)
+    }
+
     /// Recognize gesture from recorded touch data.
     pub fn recognize_gesture(&mut self) -> Option<GestureType> {
-        let start = self.touch_start?;
-        let current = self.touch_current?;
+        self.recognize_gesture_detailed().map(|d| d.gesture)
+    }
+
+    /// Like [`recognize_gesture`](Self::recognize_gesture), but also returns
+    /// the kinematics of the touch.
+    pub fn recognize_gesture_detailed(&mut self) -> Option<GestureDetails> {
+        if self.palm {
+            debug!("Palm contact - no gesture");
+            return None;
+        }
+        let was_pending = self.pending_tap;
+        let recognized = self.classify();
+        if self.pending_tap && !was_pending {
+            self.pending_tap_details = self.touch_details(GestureType::Tap);
+        }
+        recognized.and_then(|gesture| self.report(gesture))
+    }
+
+    /// Run the detectors in priority order: drag end, pinch, circle, swipe,
+    /// stationary.
+    fn classify(&mut self) -> Option<GestureType> {
+        let (start, current) = self.primary_track()?;
+
+        if self.drag_phase == DragPhase::Dragging {
+            return Some(GestureType::LongPressDragEnd);
+        }
 
         if self.active_touches.len() >= 2 {
+            if let Some(held_tap) = self.detect_held_tap() {
+                return Some(held_tap);
+            }
             if let Some(pinch) = self.detect_pinch() {
                 return Some(pinch);
             }
         }
 
-        if let Some(swipe) = self.detect_swipe(start, current) {
-            return Some(swipe);
+        if self.interrupted {
+            debug!("Single-finger touch interrupted by a second finger - cancelled");
+            return self.swipe_begun.then_some(GestureType::SwipeCancel);
         }
 
-        self.detect_stationary(start, current)
-    }
+        if let Some(circle) = self.detect_circle() {
+            return Some(circle);
+        }
 
-    fn detect_swipe(&self, start: TouchPoint, current: TouchPoint) -> Option<GestureType> {
-        let dx = current.x - start.x;
-        let dy = current.y - start.y;
-        let dt = current.time.duration_since(start.time).as_secs_f64();
-        let th = &self.thresholds;
+        if let Some(corner) = self.detect_corner() {
+            return Some(corner);
+        }
 
-        if dt >= th.swipe_time_max {
-            return None;
+        if self.swipe_reversed(start, current) {
+            debug!("Swipe reversed before release - cancelled");
+            return self.swipe_begun.then_some(GestureType::SwipeCancel);
         }
 
-        let x_span = self.x_range.1 - self.x_range.0;
-        let y_span = self.y_range.1 - self.y_range.0;
+        if let Some(held) = self.held_swipe(start, current) {
+            return Some(held);
+        }
 
-        // Horizontal swipe
-        if dx.abs() >= x_span * th.swipe_distance_min_pct
-            && dy.abs().atan2(dx.abs()).to_degrees() <= th.angle_tolerance_deg
-        {
-            return Some(if dx > 0.0 {
-                GestureType::SwipeRight
-            } else {
-                GestureType::SwipeLeft
-            });
+        if let Some(swipe) = self.detect_swipe(start, current) {
+            return Some(self.long_swipe(swipe, start, current).unwrap_or(swipe));
         }
 
-        // Vertical swipe
-        if dy.abs() >= y_span * th.swipe_distance_min_pct
-            && dx.abs().atan2(dy.abs()).to_degrees() <= th.angle_tolerance_deg
-        {
-            return Some(if dy > 0.0 {
-                GestureType::SwipeDown
-            } else {
-                GestureType::SwipeUp
+        if self.swipe_begun {
+            return Some(GestureType::SwipeCancel);
+        }
+
+        self.detect_stationary(start, current)
+    }
+
+    /// First and latest point of the primary contact.  With one finger that
+    /// is the whole touch; with several, `primary_finger` picks the contact.
+    fn primary_track(&self) -> Option<(TouchPoint, TouchPoint)> {
+        let (start, current) = (self.touch_start?, self.touch_current?);
+        if self.active_touches.len() < 2 {
+            return Some((start, current));
+        }
+        let lowest_id = self.active_touches.keys().min().copied();
+        let id = match self.primary_finger {
+            PrimaryFinger::First => Some(start.tracking_id),
+            PrimaryFinger::LowestId => lowest_id,
+            PrimaryFinger::LowestSlot => self
+                .slot_ids
+                .iter()
+                .filter(|(_, id)| self.active_touches.contains_key(id))
+                .min_by_key(|(slot, _)| **slot)
+                .map(|(_, &id)| id)
+                .or(lowest_id),
+        }?;
+        let first = self.touch_points.iter().find(|p| p.tracking_id == id)?;
+        Some((*first, *self.active_touches.get(&id)?))
+    }
+
+    /// Detect a completed swipe, which must reach the commit distance when
+    /// one is configured.
+    ///
+    /// A direction with its own thresholds is only reported when those
+    /// classify the movement as that direction.
+    fn detect_swipe(&self, start: TouchPoint, current: TouchPoint) -> Option<GestureType> {
+        let committed = |th: &ValidatedThresholds| {
+            let min_pct = th.swipe_distance_min_pct;
+            let swipe = self.swipe_past_with(th, start, current, min_pct)?;
+            // The commit distance is a fraction of the span even when the
+            // minimum is given in pixels.
+            let commit_reached = th.swipe_commit_distance_pct.is_none_or(|commit| {
+                let by_span = ValidatedThresholds {
+                    swipe_distance_min_px: None,
+                    ..th.clone()
+                };
+                self.swipe_past_with(&by_span, start, current, commit.max(min_pct)) == Some(swipe)
             });
+            commit_reached.then_some(swipe)
+        };
+        let overridden = self.gesture_thresholds.iter().filter(|(g, _)| g.is_swipe());
+        for (gesture, th) in overridden {
+            if committed(th) == Some(*gesture) {
+                return Some(*gesture);
+            }
         }
+        committed(&self.thresholds)
+            .filter(|swipe| !self.gesture_thresholds.iter().any(|(g, _)| g == swipe))
+    }
 
-        None
+    /// A `HoldSwipe*` when the finger rested near `start` for
+    /// `hold_before_swipe` before swiping off.
+    ///
+    /// The pause ends at the last point of the contact still within
+    /// `tap_distance_max` of `start`, and the swipe is measured from there,
+    /// so the pause does not count against `swipe_time_max`.
+    fn held_swipe(&self, start: TouchPoint, current: TouchPoint) -> Option<GestureType> {
+        let hold = self.hold_before_swipe?;
+        let rested_until = |radius: f64| {
+            self.touch_points
+                .iter()
+                .filter(|p| p.tracking_id == start.tracking_id)
+                .take_while(|p| start.distance_to(p) < radius)
+                .last()
+                .copied()
+                .filter(|p| p.time.saturating_duration_since(start.time) >= hold)
+        };
+        let rested = rested_until(self.thresholds.tap_distance_max)?;
+        let swipe = self.detect_swipe(rested, current)?;
+        let held = swipe.after_hold()?;
+        // A held swipe with its own thresholds rests within its own radius.
+        let rested = rested_until(self.thresholds_for(held).tap_distance_max)?;
+        (self.detect_swipe(rested, current) == Some(swipe) && !self.is_disabled(held))
+            .then_some(held)
+    }
+
+    /// The `LongSwipe*` variant of `swipe` when it reached
+    /// `long_swipe_distance_pct` of the span.
+    fn long_swipe(
+        &self,
+        swipe: GestureType,
+        start: TouchPoint,
+        current: TouchPoint,
+    ) -> Option<GestureType> {
+        let long = swipe.long_swipe()?;
+        let th = self.thresholds_for(long);
+        let long_pct = th.long_swipe_distance_pct?;
+        // Like the commit distance, a fraction of the span even when the
+        // minimum is given in pixels.
+        let by_span = ValidatedThresholds {
+            swipe_distance_min_px: None,
+            ..th.clone()
+        };
+        let min_pct = long_pct.max(th.swipe_distance_min_pct);
+        let reached = self.swipe_past_with(&by_span, start, current, min_pct) == Some(swipe);
+        (reached && !self.is_disabled(long)).then_some(long)
+    }
+
+    /// Whether a single finger swiped out and then travelled back at least
+    /// `swipe_reversal_pct` of the outbound distance - a zigzag "undo".
+    fn swipe_reversed(&self, start: TouchPoint, current: TouchPoint) -> bool {
+        let Some(reversal_pct) = self.thresholds.swipe_reversal_pct else {
+            return false;
+        };
+        let Some(peak) = self
+            .touch_points
+            .iter()
+            .filter(|p| p.tracking_id == start.tracking_id)
+            .max_by(|a, b| start.distance_to(a).total_cmp(&start.distance_to(b)))
+        else {
+            return false;
+        };
+        let outbound = start.distance_to(peak);
+        self.swipe_past(start, *peak, self.thresholds.swipe_distance_min_pct)
+            .is_some()
+            && peak.distance_to(&current) >= outbound * reversal_pct
+    }
+
+    /// Classify the movement as a swipe of at least `min_pct` of the axis span.
+    fn swipe_past(
+        &self,
+        start: TouchPoint,
+        current: TouchPoint,
+        min_pct: f64,
+    ) -> Option<GestureType> {
+        self.swipe_past_with(&self.thresholds, start, current, min_pct)
+    }
+
+    /// Like [`swipe_past`](Self::swipe_past), with `base` instead of the
+    /// device thresholds.
+    fn swipe_past_with(
+        &self,
+        base: &ValidatedThresholds,
+        start: TouchPoint,
+        current: TouchPoint,
+        min_pct: f64,
+    ) -> Option<GestureType> {
+        let thresholds = ValidatedThresholds {
+            swipe_distance_min_pct: min_pct,
+            ..base.clone()
+        };
+        let (start, current) = (self.to_screen(start), self.to_screen(current));
+        if !self.normalize_coordinates {
+            return classify_swipe(start, current, self.x_range, self.y_range, &thresholds);
+        }
+        let (x_span, y_span) = (
+            self.x_range.1 - self.x_range.0,
+            self.y_range.1 - self.y_range.0,
+        );
+        let unit = |p: TouchPoint| TouchPoint {
+            x: p.x / x_span,
+            y: p.y / y_span,
+            ..p
+        };
+        // A pixel minimum is measured in device units, outside the square.
+        let min_px = thresholds.swipe_distance_min_px;
+        let unit_thresholds = ValidatedThresholds {
+            swipe_distance_min_pct: if min_px.is_some() { 0.0 } else { min_pct },
+            swipe_distance_min_px: None,
+            ..thresholds
+        };
+        let swipe = classify_swipe(
+            unit(start),
+            unit(current),
+            (0.0, 1.0),
+            (0.0, 1.0),
+            &unit_thresholds,
+        )?;
+        let travel = match swipe {
+            GestureType::SwipeLeft | GestureType::SwipeRight => (current.x - start.x).abs(),
+            _ => (current.y - start.y).abs(),
+        };
+        min_px.is_none_or(|px| travel >= px).then_some(swipe)
     }
 
     /// Detect stationary gestures: long press, tap, or double-tap.
@@ -188,19 +1533,34 @@ impl GestureRecognizer {
         let dt = current.time.duration_since(start.time).as_secs_f64();
         let distance = start.distance_to(&current);
 
-        if dt >= self.thresholds.long_press_time_min && distance < self.thresholds.tap_distance_max
-        {
+        let long_press = self.thresholds_for(GestureType::LongPress);
+        if dt >= long_press.long_press_time_min && self.held_in_place(long_press, start, current) {
             return Some(GestureType::LongPress);
         }
 
-        if dt >= self.thresholds.tap_time_max || distance >= self.thresholds.tap_distance_max {
+        let tap = self.thresholds_for(GestureType::Tap);
+        if dt >= tap.tap_time_max || distance >= tap.tap_distance_max {
+            return None;
+        }
+        if self.partly_lifted && self.peak_fingers > 1 {
+            debug!("Last finger of a {}-finger tap lifted", self.peak_fingers);
             return None;
         }
 
-        let now = Instant::now();
+        let now = self.now();
+        if let Some(compound) = self.swipe_then_tap(now, current) {
+            return Some(compound);
+        }
+        if self.tap_count_max > 1 {
+            return self.count_tap(now, current);
+        }
+        if self.immediate_tap {
+            return Some(GestureType::Tap);
+        }
         if let (Some(last_time), Some((lx, ly))) = (self.last_tap_time, self.last_tap_position) {
-            if now.duration_since(last_time).as_secs_f64() < self.thresholds.double_tap_interval
-                && (current.x - lx).hypot(current.y - ly) < self.thresholds.double_tap_distance_max
+            let window = self.tap_window();
+            if now.duration_since(last_time).as_secs_f64() < window.double_tap_interval
+                && (current.x - lx).hypot(current.y - ly) < window.double_tap_distance_max
             {
                 self.pending_tap = false;
                 self.last_tap_time = None;
@@ -215,7 +1575,223 @@ impl GestureRecognizer {
         None
     }
 
+    /// Whether the finger stayed put for a long press: its farthest point
+    /// from `start` within `long_press_drift_max` when set, so a finger that
+    /// wobbles and settles back still counts; otherwise `current` within
+    /// `tap_distance_max`.
+    fn held_in_place(
+        &self,
+        th: &ValidatedThresholds,
+        start: TouchPoint,
+        current: TouchPoint,
+    ) -> bool {
+        let Some(drift_max) = th.long_press_drift_max else {
+            return start.distance_to(&current) < th.tap_distance_max;
+        };
+        self.touch_points
+            .iter()
+            .filter(|p| p.tracking_id == start.tracking_id)
+            .chain([&current])
+            .all(|p| start.distance_to(p) < drift_max)
+    }
+
+    /// The `Swipe*ThenTap` compound when a tap at `current` follows the last
+    /// swipe within the `swipe_then_tap` window and `double_tap_distance_max`
+    /// of its end.  The swipe is consumed either way.
+    fn swipe_then_tap(&mut self, now: Instant, current: TouchPoint) -> Option<GestureType> {
+        let window = self.swipe_then_tap?;
+        let (swipe, end) = self.last_swipe.take()?;
+        let compound = swipe.then_tap()?;
+        let in_time = now.saturating_duration_since(end.time) < window;
+        let near =
+            end.distance_to(&current) < self.thresholds_for(compound).double_tap_distance_max;
+        (in_time && near && !self.is_disabled(compound)).then_some(compound)
+    }
+
+    /// Add a tap to the current run (or start a new one), reporting `Tap`
+    /// once the run reaches `tap_count_max`; shorter runs are reported when
+    /// `double_tap_interval` passes without another tap.
+    fn count_tap(&mut self, now: Instant, current: TouchPoint) -> Option<GestureType> {
+        let window = self.tap_window();
+        let continues = self.pending_tap
+            && self.last_tap_time.is_some_and(|last| {
+                now.duration_since(last).as_secs_f64() < window.double_tap_interval
+            })
+            && self.last_tap_position.is_some_and(|(lx, ly)| {
+                (current.x - lx).hypot(current.y - ly) < window.double_tap_distance_max
+            });
+        self.tap_streak = if continues { self.tap_streak + 1 } else { 1 };
+
+        if self.tap_streak >= self.tap_count_max {
+            self.pending_tap = false;
+            self.pending_tap_details = None;
+            self.last_tap_time = None;
+            self.last_tap_position = None;
+            return Some(GestureType::Tap);
+        }
+        self.last_tap_time = Some(now);
+        self.last_tap_position = Some((current.x, current.y));
+        self.pending_tap = true;
+        None
+    }
+
+    /// Detect a chorded tap: a second finger taps while the first, already
+    /// held for at least `tap_time_max`, stays within `tap_distance_max`.
+    fn detect_held_tap(&self) -> Option<GestureType> {
+        if self.active_touches.len() != 2 {
+            return None;
+        }
+        let th = &self.thresholds;
+        let primary = self.touch_start?;
+        let secondary_id = *self
+            .active_touches
+            .keys()
+            .find(|&&id| id != primary.tracking_id)?;
+        let secondary = *self
+            .touch_points
+            .iter()
+            .find(|p| p.tracking_id == secondary_id)?;
+        let stationary = |start: TouchPoint| {
+            self.touch_points
+                .iter()
+                .filter(|p| p.tracking_id == start.tracking_id)
+                .all(|p| start.distance_to(p) < th.tap_distance_max)
+        };
+
+        let held = secondary.time.saturating_duration_since(primary.time);
+        let tapped = self.now().saturating_duration_since(secondary.time);
+        (held.as_secs_f64() >= th.tap_time_max
+            && tapped.as_secs_f64() < th.tap_time_max
+            && stationary(primary)
+            && stationary(secondary))
+        .then_some(GestureType::HeldTapSecondary)
+    }
+
+    /// Detect an L-shaped stroke: split the path at the point farthest from
+    /// the start-end line; both legs must be straight swipes, one horizontal
+    /// and one vertical, meeting within `angle_tolerance_deg` of a right
+    /// angle so a smooth curve is no corner.
+    fn detect_corner(&self) -> Option<GestureType> {
+        if !self.corner_gestures || self.active_touches.len() != 1 || self.touch_points.len() < 3 {
+            return None;
+        }
+        let points: Vec<TouchPoint> = self
+            .touch_points
+            .iter()
+            .map(|&p| self.to_screen(p))
+            .collect();
+        let (first, last) = (points[0], points[points.len() - 1]);
+        let (corner, _) = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, distance_from_line(*p, first, last)))
+            .filter(|(_, d)| d.is_finite())
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        let (leg_in, leg_out) = (&points[..=corner], &points[corner..]);
+        if !is_straight(leg_in) || !is_straight(leg_out) {
+            return None;
+        }
+        let heading = |leg: &[TouchPoint]| {
+            let (a, b) = (leg[0], leg[leg.len() - 1]);
+            (b.y - a.y).atan2(b.x - a.x)
+        };
+        let turn = (heading(leg_out) - heading(leg_in))
+            .rem_euclid(PI)
+            .to_degrees();
+        if (turn - 90.0).abs() > self.thresholds.angle_tolerance_deg {
+            return None;
+        }
+        let swipe = |leg: &[TouchPoint]| {
+            classify_swipe(
+                leg[0],
+                leg[leg.len() - 1],
+                self.x_range,
+                self.y_range,
+                &self.thresholds,
+            )
+        };
+        corner_gesture(swipe(leg_in)?, swipe(leg_out)?)
+    }
+
+    /// Detect a single-finger loop: the path must turn at least
+    /// `circle_turn_min` of a full revolution around its centroid while its
+    /// distance from the centroid stays within the radius tolerance.
+    fn detect_circle(&self) -> Option<GestureType> {
+        let turn_min = self.thresholds.circle_turn_min?;
+        if self.active_touches.len() != 1 || self.touch_points.len() < CIRCLE_POINTS_MIN {
+            return None;
+        }
+
+        let points: Vec<TouchPoint> = self
+            .touch_points
+            .iter()
+            .map(|&p| self.to_screen(p))
+            .collect();
+        let n = points.len() as f64;
+        let cx = points.iter().map(|p| p.x).sum::<f64>() / n;
+        let cy = points.iter().map(|p| p.y).sum::<f64>() / n;
+
+        let radii: Vec<f64> = points.iter().map(|p| (p.x - cx).hypot(p.y - cy)).collect();
+        let mean_radius = radii.iter().sum::<f64>() / n;
+        if mean_radius < self.thresholds.tap_distance_max {
+            return None;
+        }
+        let spread = (radii.iter().map(|r| (r - mean_radius).powi(2)).sum::<f64>() / n).sqrt();
+        let tolerance = self
+            .thresholds
+            .circle_radius_tolerance_pct
+            .unwrap_or(DEFAULT_CIRCLE_RADIUS_TOLERANCE);
+        if spread > mean_radius * tolerance {
+            return None;
+        }
+
+        // Sum the signed angle between consecutive points, seen from the
+        // centroid.  Screen Y grows downward, so a positive sum is clockwise.
+        let turned: f64 = points
+            .windows(2)
+            .map(|w| {
+                let a = (w[0].y - cy).atan2(w[0].x - cx);
+                let b = (w[1].y - cy).atan2(w[1].x - cx);
+                (b - a + PI).rem_euclid(TAU) - PI
+            })
+            .sum();
+        if turned.abs() < turn_min * TAU {
+            return None;
+        }
+        Some(if turned > 0.0 {
+            GestureType::CircleClockwise
+        } else {
+            GestureType::CircleCounterClockwise
+        })
+    }
+
     fn detect_pinch(&self) -> Option<GestureType> {
+        let (first_dist, last_dist) = self.pinch_distances()?;
+        let threshold = |gesture| {
+            let th = self.thresholds_for(gesture);
+            (first_dist * th.pinch_threshold_pct).max(th.pinch_threshold_min_px.unwrap_or(0.0))
+        };
+        if last_dist < first_dist - threshold(GestureType::PinchIn) {
+            Some(GestureType::PinchIn)
+        } else if last_dist > first_dist + threshold(GestureType::PinchOut) {
+            Some(GestureType::PinchOut)
+        } else {
+            None
+        }
+    }
+
+    /// Distance between two fingers at their first and latest points.
+    fn pinch_distances(&self) -> Option<(f64, f64)> {
+        let ((p1_first, p2_first), (p1_last, p2_last)) = self.pinch_points()?;
+        Some((
+            p1_first.distance_to(&p2_first),
+            p1_last.distance_to(&p2_last),
+        ))
+    }
+
+    /// First and latest points of two fingers.
+    fn pinch_points(&self) -> Option<((TouchPoint, TouchPoint), (TouchPoint, TouchPoint))> {
         if self.touch_points.len() < 4 || self.active_touches.len() < 2 {
             return None;
         }
@@ -224,23 +1800,23 @@ impl GestureRecognizer {
         let p2_first = self.touch_points[1..]
             .iter()
             .find(|p| p.tracking_id != p1_first.tracking_id)?;
-        let first_dist = p1_first.distance_to(p2_first);
 
         let p1_last = self.touch_points.last()?;
         let p2_last = self.touch_points[..self.touch_points.len() - 1]
             .iter()
             .rev()
             .find(|p| p.tracking_id != p1_last.tracking_id)?;
-        let last_dist = p1_last.distance_to(p2_last);
+        Some(((*p1_first, *p2_first), (*p1_last, *p2_last)))
+    }
 
-        let threshold = first_dist * self.thresholds.pinch_threshold_pct;
-        if last_dist < first_dist - threshold {
-            Some(GestureType::PinchIn)
-        } else if last_dist > first_dist + threshold {
-            Some(GestureType::PinchOut)
-        } else {
-            None
-        }
+    /// Center and scale of the current two-finger touch.
+    fn pinch_details(&self) -> Option<PinchDetails> {
+        let (first_dist, last_dist) = self.pinch_distances()?;
+        let (_, (p1_last, p2_last)) = self.pinch_points()?;
+        (first_dist > 0.0).then(|| PinchDetails {
+            center: ((p1_last.x + p2_last.x) / 2.0, (p1_last.y + p2_last.y) / 2.0),
+            scale: last_dist / first_dist,
+        })
     }
 
     /// Check if a tap is pending.
@@ -256,13 +1832,36 @@ impl GestureRecognizer {
     /// If a single tap is pending and the double-tap window has expired,
     /// consume it and return `GestureType::Tap`.
     pub fn check_pending_tap_expired(&mut self) -> Option<GestureType> {
+        self.check_pending_tap_expired_detailed().map(|d| d.gesture)
+    }
+
+    /// Like [`check_pending_tap_expired`](Self::check_pending_tap_expired),
+    /// but returns the kinematics recorded when the tap was first seen.
+    pub fn check_pending_tap_expired_detailed(&mut self) -> Option<GestureDetails> {
         if !self.pending_tap {
             return None;
         }
-        let elapsed = self.last_tap_time?.elapsed().as_secs_f64();
-        if elapsed >= self.thresholds.double_tap_interval {
+        let elapsed = self
+            .now()
+            .saturating_duration_since(self.last_tap_time?)
+            .as_secs_f64();
+        if elapsed >= self.tap_window().double_tap_interval {
             self.pending_tap = false;
-            Some(GestureType::Tap)
+            let details = self.pending_tap_details.take().unwrap_or(GestureDetails {
+                gesture: GestureType::Tap,
+                angle_deg: 0.0,
+                distance: 0.0,
+                duration: 0.0,
+                finger_count: 1,
+                tap_count: 1,
+                pinch: None,
+                trajectory: None,
+            });
+            let details = GestureDetails {
+                tap_count: self.tap_streak.max(1),
+                ..details
+            };
+            (!self.is_disabled(GestureType::Tap)).then_some(details)
         } else {
             None
         }
@@ -0,0 +1,74 @@
+//! Per-gesture usage counters, optionally persisted to disk.
+//!
+//! The stats file is TOML:
+//!
+//! ```toml
+//! [counts]
+//! swipe_left = 12
+//! tap = 40
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::BodgestrError;
+use crate::recognizer::GestureType;
+
+/// Number of times each gesture was recognized, keyed by gesture name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GestureStats {
+    #[serde(default)]
+    pub counts: BTreeMap<String, u64>,
+}
+
+impl GestureStats {
+    /// Count one occurrence of `gesture`.
+    pub fn record(&mut self, gesture: GestureType) {
+        let name: &str = gesture.into();
+        *self.counts.entry(name.to_string()).or_default() += 1;
+    }
+
+    /// Add all counts from `other` to `self`.
+    pub fn merge(&mut self, other: &GestureStats) {
+        for (name, count) in &other.counts {
+            *self.counts.entry(name.clone()).or_default() += count;
+        }
+    }
+
+    /// Load stats from `path`.  A missing file yields empty stats.
+    pub fn load(path: &Path) -> Result<Self, BodgestrError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(BodgestrError::StatsReadError {
+                    path: path.to_path_buf(),
+                    message: e.to_string(),
+                });
+            }
+        };
+        toml::from_str(&content).map_err(|e| BodgestrError::StatsReadError {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Write stats to `path` atomically: write a sibling temp file, then
+    /// rename it over the target so a power loss never leaves a torn file.
+    pub fn save(&self, path: &Path) -> Result<(), BodgestrError> {
+        let write_err = |message: String| BodgestrError::StatsWriteError {
+            path: path.to_path_buf(),
+            message,
+        };
+        let content = toml::to_string(self).map_err(|e| write_err(e.to_string()))?;
+
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, content).map_err(|e| write_err(e.to_string()))?;
+        fs::rename(&tmp, path).map_err(|e| write_err(e.to_string()))
+    }
+}
@@ -0,0 +1,73 @@
+//! Recognized gestures as an async [`Stream`] (`async` cargo feature).
+//!
+//! A background thread feeds touch events from a blocking source - an evdev
+//! device or any iterator of [`TouchEvent`] batches - through a
+//! [`GestureRecognizer`] and sends each gesture over a channel, so tokio or
+//! any other executor can await them without blocking.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+
+use evdev::Device;
+use futures_channel::mpsc::{self, UnboundedReceiver};
+use futures_core::Stream;
+use log::warn;
+
+use crate::event::{TouchEvent, classify_event, process_touch_events_detailed};
+use crate::recognizer::{GestureDetails, GestureRecognizer};
+
+/// Gestures recognized on a background thread; ends when the event source
+/// does.  Dropping the stream stops the thread after its next batch.
+pub struct GestureStream {
+    receiver: UnboundedReceiver<GestureDetails>,
+}
+
+impl GestureStream {
+    /// Recognize gestures in the event batches of `source` with `recognizer`.
+    pub fn spawn<I>(source: I, mut recognizer: GestureRecognizer) -> Self
+    where
+        I: IntoIterator<Item = Vec<TouchEvent>>,
+        I::IntoIter: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::unbounded();
+        let source = source.into_iter();
+        thread::Builder::new()
+            .name("gesture-stream".into())
+            .spawn(move || {
+                for batch in source {
+                    for details in process_touch_events_detailed(&mut recognizer, &batch) {
+                        if sender.unbounded_send(details).is_err() {
+                            return;
+                        }
+                    }
+                }
+            })
+            .expect("Failed to spawn gesture stream thread");
+        Self { receiver }
+    }
+
+    /// Recognize gestures read from `device`; the stream ends on a read error.
+    pub fn from_device(device: Device, recognizer: GestureRecognizer) -> Self {
+        Self::spawn(device_batches(device), recognizer)
+    }
+}
+
+impl Stream for GestureStream {
+    type Item = GestureDetails;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+/// Blocking reads from `device`, one batch of touch events per read.
+fn device_batches(mut device: Device) -> impl Iterator<Item = Vec<TouchEvent>> {
+    std::iter::from_fn(move || match device.fetch_events() {
+        Ok(events) => Some(events.filter_map(|e| classify_event(&e)).collect()),
+        Err(e) => {
+            warn!("Gesture stream: failed to read device: {e}");
+            None
+        }
+    })
+}
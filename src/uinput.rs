@@ -0,0 +1,142 @@
+//! Direct input injection through a `uinput` virtual device.
+//!
+//! Lets gestures emit key presses and wheel scrolls without external tools
+//! such as `xdotool`, which works the same on X11, Wayland and the bare
+//! console.  The virtual device is only available with the `uinput` cargo
+//! feature (on by default); key-name parsing and scroll mapping are always
+//! available so configs validate either way.
+
+use evdev::{Key, RelativeAxisType};
+
+use crate::config::ScrollAxis;
+use crate::recognizer::GestureType;
+
+/// Parse an evdev key name such as `"KEY_LEFT"`.  Case-insensitive; the
+/// `KEY_` prefix is optional (`"left"` → `KEY_LEFT`).
+pub fn parse_key(name: &str) -> Option<Key> {
+    let upper = name.trim().to_uppercase();
+    upper
+        .parse()
+        .or_else(|_| format!("KEY_{upper}").parse())
+        .ok()
+}
+
+/// Relative wheel event a gesture produces when bound to `axis`.
+///
+/// Swipes scroll like a touch panel - the content follows the finger, so a
+/// swipe up scrolls down.  Pinch-out scrolls up (zoom in under `Ctrl`),
+/// pinch-in scrolls down.  Returns `None` for gestures without a direction.
+pub fn scroll_event(gesture: GestureType, axis: ScrollAxis) -> Option<(RelativeAxisType, i32)> {
+    let delta = match gesture {
+        GestureType::PinchOut | GestureType::SwipeDown | GestureType::SwipeLeft => 1,
+        GestureType::PinchIn | GestureType::SwipeUp | GestureType::SwipeRight => -1,
+        _ => return None,
+    };
+    Some((wheel_axis(axis), delta))
+}
+
+/// The evdev wheel a configured `scroll` axis maps to.
+pub fn wheel_axis(axis: ScrollAxis) -> RelativeAxisType {
+    match axis {
+        ScrollAxis::Vertical => RelativeAxisType::REL_WHEEL,
+        ScrollAxis::Horizontal => RelativeAxisType::REL_HWHEEL,
+    }
+}
+
+#[cfg(feature = "uinput")]
+mod imp {
+    use std::io::ErrorKind;
+
+    use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+    use evdev::{AttributeSet, EventType, InputEvent, Key, RelativeAxisType};
+
+    use crate::config::BodgestrError;
+
+    /// A virtual input device able to emit a fixed set of keys and wheels.
+    pub struct VirtualInput {
+        device: VirtualDevice,
+    }
+
+    impl VirtualInput {
+        /// Create the virtual device, registering every key and wheel it may emit.
+        pub fn open(
+            keys: impl IntoIterator<Item = Key>,
+            wheels: impl IntoIterator<Item = RelativeAxisType>,
+        ) -> Result<Self, BodgestrError> {
+            let key_set: AttributeSet<Key> = keys.into_iter().collect();
+            let wheel_set: AttributeSet<RelativeAxisType> = wheels.into_iter().collect();
+            let device = VirtualDeviceBuilder::new()
+                .and_then(|builder| {
+                    builder
+                        .name("bodgestr virtual input")
+                        .with_keys(&key_set)?
+                        .with_relative_axes(&wheel_set)
+                })
+                .and_then(|builder| builder.build())
+                .map_err(|e| {
+                    let hint = match e.kind() {
+                        ErrorKind::PermissionDenied => {
+                            " - grant write access to /dev/uinput (e.g. a udev rule for the \
+                             'input' group) or run as root"
+                        }
+                        ErrorKind::NotFound => " - load the kernel module with 'modprobe uinput'",
+                        _ => "",
+                    };
+                    BodgestrError::UinputError {
+                        message: format!("cannot open /dev/uinput: {e}{hint}"),
+                    }
+                })?;
+            Ok(Self { device })
+        }
+
+        /// Press and release `key`.
+        pub fn tap(&mut self, key: Key) -> std::io::Result<()> {
+            self.device
+                .emit(&[InputEvent::new(EventType::KEY, key.code(), 1)])?;
+            self.device
+                .emit(&[InputEvent::new(EventType::KEY, key.code(), 0)])
+        }
+
+        /// Move the wheel `axis` by `delta` notches.
+        pub fn scroll(&mut self, axis: RelativeAxisType, delta: i32) -> std::io::Result<()> {
+            self.device
+                .emit(&[InputEvent::new(EventType::RELATIVE, axis.0, delta)])
+        }
+    }
+}
+
+#[cfg(not(feature = "uinput"))]
+mod imp {
+    use evdev::{Key, RelativeAxisType};
+
+    use crate::config::BodgestrError;
+
+    /// Placeholder for builds without the `uinput` feature; never constructed.
+    pub struct VirtualInput {
+        _private: (),
+    }
+
+    impl VirtualInput {
+        /// Always fails: this build has no uinput support.
+        pub fn open(
+            _keys: impl IntoIterator<Item = Key>,
+            _wheels: impl IntoIterator<Item = RelativeAxisType>,
+        ) -> Result<Self, BodgestrError> {
+            Err(BodgestrError::UinputError {
+                message: "bodgestr was built without the 'uinput' feature".into(),
+            })
+        }
+
+        /// Unreachable, as the device can never be opened.
+        pub fn tap(&mut self, _key: Key) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        /// Unreachable, as the device can never be opened.
+        pub fn scroll(&mut self, _axis: RelativeAxisType, _delta: i32) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+pub use imp::VirtualInput;
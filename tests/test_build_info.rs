@@ -0,0 +1,37 @@
+//! Tests for `bodgestr::build_info` - the `--build-info` report.
+use bodgestr::recognizer::{GestureType, NUM_GESTURES};
+use bodgestr::{FEATURES, build_info};
+
+#[test]
+fn test_all_gestures_in_index_order() {
+    let all: Vec<GestureType> = GestureType::all().collect();
+    assert_eq!(all.len(), NUM_GESTURES);
+    assert!(all.iter().enumerate().all(|(i, g)| g.index() == i));
+}
+
+#[test]
+fn test_build_info_lists_every_gesture() {
+    let info = build_info();
+    let gestures = info
+        .lines()
+        .find_map(|line| line.strip_prefix("gestures: "))
+        .unwrap();
+    let listed: Vec<&str> = gestures.split(", ").collect();
+    for gesture in GestureType::all() {
+        assert!(listed.contains(&gesture.into()), "{gesture} missing");
+    }
+}
+
+#[test]
+fn test_build_info_starts_with_version_and_features() {
+    let info = build_info();
+    let mut lines = info.lines();
+    assert_eq!(
+        lines.next(),
+        Some(format!("bodgestr {}", env!("CARGO_PKG_VERSION")).as_str())
+    );
+    let features = lines.next().unwrap().strip_prefix("features: ").unwrap();
+    for (name, enabled) in FEATURES {
+        assert_eq!(features.split(", ").any(|f| f == *name), *enabled);
+    }
+}
@@ -1,10 +1,19 @@
 //! Tests for `bodgestr::config` - TOML parsing, threshold merging,
 //! gesture inheritance, device filtering, and error handling.
 
+use std::collections::BTreeMap;
 use std::io::Write;
+use std::path::Path;
 use tempfile::NamedTempFile;
 
-use bodgestr::config::{AppConfig, parse_config_file};
+use bodgestr::config::{
+    ActiveHours, AppConfig, ConfigChange, CoordinateSpace, DeviceLocator, PrimaryFinger,
+    SYSTEM_CONFIG_PATH, ScrollAxis, YAxis, diff_configs, parse_config_file, resolve_config_path,
+    target_level,
+};
+use bodgestr::event::{resolve_action, within_active_hours};
+use bodgestr::recognizer::GestureType;
+use log::LevelFilter;
 
 // ── Helpers ──────────────────────────────────────────────────
 
@@ -85,6 +94,40 @@ fn test_empty_config() {
     assert_eq!(config.log_level, "info");
 }
 
+#[test]
+fn test_global_thresholds_exposed_when_complete() {
+    let config = load("", true);
+    let th = config.global_thresholds.unwrap();
+    assert_eq!(th.swipe_time_max, 0.9);
+}
+
+#[test]
+fn test_global_thresholds_none_when_incomplete() {
+    let config = load(
+        r#"
+[global.thresholds]
+swipe_time_max = 0.9
+"#,
+        false,
+    );
+    assert!(config.global_thresholds.is_none());
+}
+
+#[test]
+fn test_stats_file_parsed() {
+    let config = load(
+        r#"
+[global]
+stats_file = "/var/lib/bodgestr/stats.toml"
+"#,
+        true,
+    );
+    assert_eq!(
+        config.stats_file.as_deref(),
+        Some("/var/lib/bodgestr/stats.toml")
+    );
+}
+
 #[test]
 fn test_global_log_level() {
     let config = load(
@@ -183,413 +226,1662 @@ enabled = true
 }
 
 #[test]
-fn test_multiple_devices() {
+fn test_device_path_without_usb_id_loaded() {
     let config = load(
         r#"
-[device.a]
-device_usb_id = "1111:1111"
-enabled = true
-
-[device.b]
-device_usb_id = "2222:2222"
+[device.d1]
+device_path = "/dev/input/event7"
 enabled = true
 "#,
         true,
     );
-    assert!(config.devices.contains_key("a"));
-    assert!(config.devices.contains_key("b"));
+    let d1 = &config.devices["d1"];
+    assert_eq!(
+        d1.device_path.as_deref(),
+        Some(std::path::Path::new("/dev/input/event7"))
+    );
+    assert_eq!(
+        d1.locator(),
+        DeviceLocator::Path(std::path::Path::new("/dev/input/event7"))
+    );
 }
 
-// ── Threshold merging ────────────────────────────────────────
-
 #[test]
-fn test_complete_thresholds_pass() {
+fn test_device_path_takes_precedence_over_usb_id() {
     let config = load(
         r#"
 [device.d1]
-device_usb_id = "1234:5678"
+device_usb_id = "0000:0000"
+device_path = "/dev/input/event3"
 enabled = true
 "#,
         true,
     );
-    assert!(config.devices.contains_key("d1"));
+    assert_eq!(
+        config.devices["d1"].locator(),
+        DeviceLocator::Path(std::path::Path::new("/dev/input/event3"))
+    );
 }
 
 #[test]
-fn test_device_inherits_global_thresholds() {
+fn test_usb_id_locator_without_path() {
     let config = load(
         r#"
-[global.thresholds]
-swipe_time_max = 2.0
-swipe_distance_min_pct = 0.15
-angle_tolerance_deg = 30.0
-tap_time_max = 0.2
-long_press_time_min = 0.8
-double_tap_interval = 0.3
-tap_distance_max = 80.0
-double_tap_distance_max = 50.0
-pinch_threshold_pct = 0.1
-
 [device.d1]
-device_usb_id = "1234:5678"
+device_usb_id = "1111:2222"
 enabled = true
 "#,
-        false,
+        true,
+    );
+    assert_eq!(
+        config.devices["d1"].locator(),
+        DeviceLocator::UsbId("1111:2222")
     );
-    let th = &config.devices["d1"].thresholds;
-    assert_eq!(th.swipe_time_max, 2.0);
-    assert_eq!(th.tap_distance_max, 80.0);
 }
 
 #[test]
-fn test_device_overrides_global_thresholds() {
+fn test_phys_locator_takes_precedence_over_usb_id() {
     let config = load(
         r#"
 [device.d1]
-device_usb_id = "1234:5678"
+device_usb_id = "1111:2222"
+device_phys = "usb-0000:00:14.0-3/input0"
 enabled = true
 
-[device.d1.thresholds]
-swipe_time_max = 3.0
+[device.d2]
+device_phys = "usb-0000:00:14.0-4/input0"
+enabled = true
 "#,
         true,
     );
-    let th = &config.devices["d1"].thresholds;
-    assert_eq!(th.swipe_time_max, 3.0);
-    assert_eq!(th.tap_time_max, 0.2); // inherited
+    assert_eq!(
+        config.devices["d1"].locator(),
+        DeviceLocator::Phys("usb-0000:00:14.0-3/input0")
+    );
+    // phys alone is enough to register a device.
+    assert_eq!(config.devices["d2"].device_usb_id, "");
 }
 
 #[test]
-fn test_all_threshold_fields() {
+fn test_coordinate_space_normalized_fixes_ranges() {
     let config = load(
         r#"
-[device.d1]
-device_usb_id = "1111:2222"
+[device.trackpad]
+device_path = "/dev/input/event7"
+coordinate_space = "normalized"
 enabled = true
 
-[device.d1.thresholds]
-swipe_time_max = 1.1
-swipe_distance_min_pct = 0.2
-angle_tolerance_deg = 25.0
-tap_time_max = 0.3
-long_press_time_min = 1.0
-double_tap_interval = 0.4
-tap_distance_max = 40.0
-double_tap_distance_max = 55.0
-pinch_threshold_pct = 0.15
+[device.panel]
+device_usb_id = "1111:2222"
+enabled = true
 "#,
         true,
     );
-    let th = &config.devices["d1"].thresholds;
-    assert_eq!(th.swipe_time_max, 1.1);
-    assert_eq!(th.swipe_distance_min_pct, 0.2);
-    assert_eq!(th.angle_tolerance_deg, 25.0);
-    assert_eq!(th.tap_time_max, 0.3);
-    assert_eq!(th.long_press_time_min, 1.0);
-    assert_eq!(th.double_tap_interval, 0.4);
-    assert_eq!(th.tap_distance_max, 40.0);
-    assert_eq!(th.double_tap_distance_max, 55.0);
-    assert_eq!(th.pinch_threshold_pct, 0.15);
+    let trackpad = config.devices["trackpad"].coordinate_space;
+    assert_eq!(trackpad, CoordinateSpace::Normalized);
+    assert_eq!(trackpad.fixed_ranges(), Some(((0.0, 1.0), (0.0, 1.0))));
+    // Device units by default, read from the device.
+    let panel = config.devices["panel"].coordinate_space;
+    assert_eq!(panel, CoordinateSpace::Device);
+    assert_eq!(panel.fixed_ranges(), None);
 }
 
-// ── Gesture configuration ────────────────────────────────────
-
 #[test]
-fn test_device_gesture() {
+fn test_multiple_devices() {
     let config = load(
         r#"
-[device.d1]
-device_usb_id = "1234:5678"
+[device.a]
+device_usb_id = "1111:1111"
 enabled = true
 
-[device.d1.gestures.tap]
-action = "echo tap"
+[device.b]
+device_usb_id = "2222:2222"
 enabled = true
 "#,
         true,
     );
-    let g = &config.devices["d1"].gestures["tap"];
-    assert_eq!(g.action, Some("echo tap".to_string()));
-    assert!(g.enabled);
+    assert!(config.devices.contains_key("a"));
+    assert!(config.devices.contains_key("b"));
 }
 
 #[test]
-fn test_all_gesture_types_configurable() {
-    let names = [
-        "swipe_left",
-        "swipe_right",
-        "swipe_up",
-        "swipe_down",
-        "tap",
-        "double_tap",
-        "long_press",
-        "pinch_in",
-        "pinch_out",
-    ];
-    let gesture_toml: String = names
-        .iter()
-        .map(|g| format!("[device.d1.gestures.{g}]\naction = \"echo {g}\"\nenabled = true\n\n"))
-        .collect();
+fn test_skip_initial_touches_default_zero() {
     let config = load(
-        &format!(
-            r#"
+        r#"
 [device.d1]
 device_usb_id = "1111:2222"
 enabled = true
-
-{gesture_toml}
-"#
-        ),
+"#,
         true,
     );
-    for g in &names {
-        assert!(
-            config.devices["d1"].gestures[*g].enabled,
-            "gesture {g} not enabled"
-        );
-    }
+    assert_eq!(config.devices["d1"].skip_initial_touches, 0);
 }
 
-// ── Global gesture inheritance ───────────────────────────────
-
 #[test]
-fn test_global_gestures_inherited() {
+fn test_skip_initial_touches_parsed() {
     let config = load(
         r#"
-[global.gestures.tap]
-action = "xdotool click 1"
-enabled = true
-
-[global.gestures.swipe_left]
-action = "xdotool key ctrl+shift+Tab"
-enabled = true
-
 [device.d1]
-device_usb_id = "1111:1111"
+device_usb_id = "1111:2222"
 enabled = true
+skip_initial_touches = 2
 "#,
         true,
     );
-    let d1 = &config.devices["d1"];
-    assert_eq!(d1.gestures["tap"].action, Some("xdotool click 1".into()));
-    assert_eq!(
-        d1.gestures["swipe_left"].action,
-        Some("xdotool key ctrl+shift+Tab".into())
-    );
+    assert_eq!(config.devices["d1"].skip_initial_touches, 2);
 }
 
 #[test]
-fn test_device_overrides_global_gesture() {
+fn test_y_axis_defaults_to_down() {
     let config = load(
         r#"
-[global.gestures.tap]
-action = "xdotool click 1"
-enabled = true
-
 [device.d1]
-device_usb_id = "1111:1111"
+device_usb_id = "1111:2222"
 enabled = true
-
-[device.d1.gestures.tap]
-action = "xdotool click 3"
 "#,
         true,
     );
-    assert_eq!(
-        config.devices["d1"].gestures["tap"].action,
-        Some("xdotool click 3".into())
-    );
-    assert!(config.devices["d1"].gestures["tap"].enabled);
+    assert_eq!(config.devices["d1"].y_axis, YAxis::Down);
 }
 
 #[test]
-fn test_device_disables_global_gesture() {
+fn test_y_axis_global_inherited_and_device_override() {
     let config = load(
         r#"
-[global.gestures.tap]
-action = "xdotool click 1"
-enabled = true
+[global]
+y_axis = "up"
 
-[device.d1]
+[device.a]
 device_usb_id = "1111:1111"
 enabled = true
 
-[device.d1.gestures.tap]
-enabled = false
+[device.b]
+device_usb_id = "2222:2222"
+enabled = true
+y_axis = "down"
 "#,
         true,
     );
-    assert!(!config.devices["d1"].gestures["tap"].enabled);
-    assert_eq!(
-        config.devices["d1"].gestures["tap"].action,
-        Some("xdotool click 1".into())
-    );
+    assert_eq!(config.y_axis, YAxis::Up);
+    assert_eq!(config.devices["a"].y_axis, YAxis::Up);
+    assert_eq!(config.devices["b"].y_axis, YAxis::Down);
 }
 
 #[test]
-fn test_device_adds_gesture_beyond_global() {
+fn test_primary_finger_global_inherited_and_device_override() {
     let config = load(
         r#"
-[global.gestures.tap]
-action = "xdotool click 1"
-enabled = true
+[global]
+primary_finger = "lowest_id"
 
-[device.d1]
+[device.a]
 device_usb_id = "1111:1111"
 enabled = true
 
-[device.d1.gestures.long_press]
-action = "xdotool key ctrl+r"
+[device.b]
+device_usb_id = "2222:2222"
 enabled = true
+primary_finger = "lowest_slot"
 "#,
         true,
     );
-    let d1 = &config.devices["d1"];
-    assert!(d1.gestures.contains_key("tap"));
-    assert!(d1.gestures.contains_key("long_press"));
+    assert_eq!(config.devices["a"].primary_finger, PrimaryFinger::LowestId);
+    assert_eq!(
+        config.devices["b"].primary_finger,
+        PrimaryFinger::LowestSlot
+    );
 }
 
 #[test]
-fn test_override_does_not_mutate_other_devices() {
+fn test_y_axis_invalid_value_rejected() {
+    let msg = load_err(
+        r#"
+[global]
+y_axis = "sideways"
+"#,
+    );
+    assert!(msg.contains("Failed to parse config file"));
+}
+
+#[test]
+fn test_action_retries_global_and_device_override() {
     let config = load(
         r#"
-[global.gestures.tap]
-action = "global tap"
-enabled = true
+[global]
+action_retries = 2
 
-[device.d1]
+[device.a]
 device_usb_id = "1111:1111"
 enabled = true
 
-[device.d1.gestures.tap]
-action = "device1 tap"
-
-[device.d2]
+[device.b]
 device_usb_id = "2222:2222"
 enabled = true
+action_retries = 5
 "#,
         true,
     );
-    assert_eq!(
-        config.devices["d1"].gestures["tap"].action,
-        Some("device1 tap".into())
-    );
-    assert_eq!(
-        config.devices["d2"].gestures["tap"].action,
-        Some("global tap".into())
-    );
+    assert_eq!(config.devices["a"].action_retries, 2);
+    assert_eq!(config.devices["b"].action_retries, 5);
 }
 
 #[test]
-fn test_no_global_gestures_fine() {
+fn test_wake_on_idle_parsed() {
     let config = load(
         r#"
-[device.d1]
-device_usb_id = "1111:1111"
-enabled = true
+[global]
+wake_on_idle_secs = 300
+wake_action = "xset dpms force on"
 
-[device.d1.gestures.tap]
-action = "echo tap"
+[device.d1]
+device_usb_id = "1111:2222"
 enabled = true
 "#,
         true,
     );
-    assert_eq!(
-        config.devices["d1"].gestures["tap"].action,
-        Some("echo tap".into())
-    );
+    let d1 = &config.devices["d1"];
+    assert_eq!(d1.wake_on_idle, Some(std::time::Duration::from_secs(300)));
+    assert_eq!(d1.wake_action.as_deref(), Some("xset dpms force on"));
 }
 
-// ── Global-only configs (no auto-device creation) ────────────
-
 #[test]
-fn test_global_only_gestures_no_device() {
+fn test_wake_on_idle_disabled_by_default() {
     let config = load(
         r#"
-[global.gestures.tap]
-action = "xdotool click 1"
+[device.d1]
+device_usb_id = "1111:2222"
 enabled = true
 "#,
         true,
     );
-    assert!(config.devices.is_empty());
+    assert_eq!(config.devices["d1"].wake_on_idle, None);
 }
 
 #[test]
-fn test_global_only_thresholds_no_device() {
+fn test_long_press_drag_option() {
     let config = load(
         r#"
-[global.thresholds]
-swipe_time_max = 1.5
+[global]
+long_press_drag = true
+
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.b]
+device_usb_id = "2222:2222"
+enabled = true
+long_press_drag = false
 "#,
-        false,
+        true,
     );
-    assert!(config.devices.is_empty());
+    assert!(config.devices["a"].long_press_drag);
+    assert!(!config.devices["b"].long_press_drag);
 }
 
-// ── Full roundtrip ───────────────────────────────────────────
+// ── Threshold merging ────────────────────────────────────────
 
 #[test]
-fn test_full_config_roundtrip() {
+fn test_complete_thresholds_pass() {
     let config = load(
         r#"
-[global]
-log_level = "DEBUG"
+[device.d1]
+device_usb_id = "1234:5678"
+enabled = true
+"#,
+        true,
+    );
+    assert!(config.devices.contains_key("d1"));
+}
 
+#[test]
+fn test_device_inherits_global_thresholds() {
+    let config = load(
+        r#"
 [global.thresholds]
-swipe_time_max = 1.5
+swipe_time_max = 2.0
 swipe_distance_min_pct = 0.15
 angle_tolerance_deg = 30.0
 tap_time_max = 0.2
-tap_distance_max = 60.0
 long_press_time_min = 0.8
 double_tap_interval = 0.3
+tap_distance_max = 80.0
 double_tap_distance_max = 50.0
 pinch_threshold_pct = 0.1
 
-[global.gestures.tap]
-action = "xdotool click 1"
+[device.d1]
+device_usb_id = "1234:5678"
 enabled = true
+"#,
+        false,
+    );
+    let th = &config.devices["d1"].thresholds;
+    assert_eq!(th.swipe_time_max, 2.0);
+    assert_eq!(th.tap_distance_max, 80.0);
+}
 
-[global.gestures.swipe_left]
-action = "xdotool key Left"
+#[test]
+fn test_device_swipe_distance_px_replaces_global_pct() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1234:5678"
+enabled = true
+
+[device.d1.thresholds]
+swipe_distance_min_px = 120.0
+
+[device.d2]
+device_usb_id = "1234:5679"
+enabled = true
+"#,
+        true,
+    );
+    let px = &config.devices["d1"].thresholds;
+    assert_eq!(px.swipe_distance_min_px, Some(120.0));
+    assert_eq!(px.swipe_distance_min_pct, 0.0);
+    let pct = &config.devices["d2"].thresholds;
+    assert_eq!(pct.swipe_distance_min_px, None);
+    assert_eq!(pct.swipe_distance_min_pct, 0.15);
+}
+
+#[test]
+fn test_swipe_distance_pct_and_px_in_one_table_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+swipe_distance_min_px = 120.0
+
+[device.d1]
+device_usb_id = "1234:5678"
 enabled = true
+"#
+    ));
+    assert!(msg.contains("mutually exclusive"), "{msg}");
+}
 
+#[test]
+fn test_device_overrides_global_thresholds() {
+    let config = load(
+        r#"
 [device.d1]
 device_usb_id = "1234:5678"
 enabled = true
 
-[device.d1.gestures.long_press]
-action = "echo long"
+[device.d1.thresholds]
+swipe_time_max = 3.0
+"#,
+        true,
+    );
+    let th = &config.devices["d1"].thresholds;
+    assert_eq!(th.swipe_time_max, 3.0);
+    assert_eq!(th.tap_time_max, 0.2); // inherited
+}
+
+#[test]
+fn test_all_threshold_fields() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
+
+[device.d1.thresholds]
+swipe_time_max = 1.1
+swipe_distance_min_pct = 0.2
+angle_tolerance_deg = 25.0
+tap_time_max = 0.3
+long_press_time_min = 1.0
+double_tap_interval = 0.4
+tap_distance_max = 40.0
+double_tap_distance_max = 55.0
+pinch_threshold_pct = 0.15
+"#,
+        true,
+    );
+    let th = &config.devices["d1"].thresholds;
+    assert_eq!(th.swipe_time_max, 1.1);
+    assert_eq!(th.swipe_distance_min_pct, 0.2);
+    assert_eq!(th.angle_tolerance_deg, 25.0);
+    assert_eq!(th.tap_time_max, 0.3);
+    assert_eq!(th.long_press_time_min, 1.0);
+    assert_eq!(th.double_tap_interval, 0.4);
+    assert_eq!(th.tap_distance_max, 40.0);
+    assert_eq!(th.double_tap_distance_max, 55.0);
+    assert_eq!(th.pinch_threshold_pct, 0.15);
+}
+
+#[test]
+fn test_orientation_thresholds_chosen_by_aspect() {
+    let config = load(
+        r#"
+[global.thresholds.landscape]
+swipe_distance_min_pct = 0.1
+
+[device.d1]
+device_usb_id = "1234:5678"
+enabled = true
+
+[device.d1.thresholds]
+swipe_time_max = 1.5
+
+[device.d1.thresholds.portrait]
+swipe_distance_min_pct = 0.25
+"#,
+        true,
+    );
+    let dev = &config.devices["d1"];
+
+    let portrait = dev.thresholds_for((0.0, 1080.0), (0.0, 1920.0));
+    assert_eq!(portrait.swipe_distance_min_pct, 0.25);
+    assert_eq!(portrait.swipe_time_max, 1.5); // from the device's base set
+
+    let landscape = dev.thresholds_for((0.0, 1920.0), (0.0, 1080.0));
+    assert_eq!(landscape.swipe_distance_min_pct, 0.1); // from global
+    assert_eq!(landscape.swipe_time_max, 1.5);
+
+    let square = dev.thresholds_for((0.0, 1000.0), (0.0, 1000.0));
+    assert_eq!(square.swipe_distance_min_pct, 0.15);
+}
+
+#[test]
+fn test_orientation_thresholds_absent_fall_back() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1234:5678"
+enabled = true
+"#,
+        true,
+    );
+    let dev = &config.devices["d1"];
+    assert!(dev.portrait_thresholds.is_none());
+    assert!(dev.landscape_thresholds.is_none());
+    let th = dev.thresholds_for((0.0, 1080.0), (0.0, 1920.0));
+    assert_eq!(th.swipe_distance_min_pct, 0.15);
+}
+
+#[test]
+fn test_profile_thresholds_between_device_and_global() {
+    let config = load(
+        r#"
+[profiles.kiosk.thresholds]
+swipe_time_max = 1.2
+tap_time_max = 0.3
+
+[profiles.kiosk.thresholds.portrait]
+swipe_distance_min_pct = 0.25
+
+[device.d1]
+device_usb_id = "1234:5678"
+profile = "kiosk"
 enabled = true
 
+[device.d1.thresholds]
+tap_time_max = 0.25
+
 [device.d2]
-device_usb_id = "5678:9abc"
+device_usb_id = "1234:9999"
+enabled = true
+"#,
+        true,
+    );
+    let th = &config.devices["d1"].thresholds;
+    assert_eq!(th.swipe_time_max, 1.2); // from the profile
+    assert_eq!(th.tap_time_max, 0.25); // device wins over the profile
+    assert_eq!(th.angle_tolerance_deg, 30.0); // global fills the rest
+    let portrait = config.devices["d1"].thresholds_for((0.0, 1080.0), (0.0, 1920.0));
+    assert_eq!(portrait.swipe_distance_min_pct, 0.25);
+
+    // Devices without the profile keep the global values.
+    assert_eq!(config.devices["d2"].thresholds.swipe_time_max, 0.9);
+}
+
+#[test]
+fn test_unknown_profile_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1234:5678"
+profile = "kiosk"
 enabled = true
+"#
+    ));
+    assert!(msg.contains("unknown profile 'kiosk'"));
+    assert!(msg.contains("d1"));
+}
 
-[device.d2.gestures.tap]
-action = "xdotool click 3"
+// ── Gesture configuration ────────────────────────────────────
 
-[device.d2.thresholds]
-swipe_time_max = 2.0
+#[test]
+fn test_device_gesture() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1234:5678"
+enabled = true
+
+[device.d1.gestures.tap]
+action = "echo tap"
+enabled = true
 "#,
-        false,
+        true,
     );
+    let g = &config.devices["d1"].gestures["tap"];
+    assert_eq!(g.action, Some("echo tap".to_string()));
+    assert!(g.enabled);
+}
 
-    assert_eq!(config.log_level, "DEBUG");
+#[test]
+fn test_all_gesture_types_configurable() {
+    let names = [
+        "swipe_left",
+        "swipe_right",
+        "swipe_up",
+        "swipe_down",
+        "tap",
+        "double_tap",
+        "long_press",
+        "pinch_in",
+        "pinch_out",
+    ];
+    let gesture_toml: String = names
+        .iter()
+        .map(|g| format!("[device.d1.gestures.{g}]\naction = \"echo {g}\"\nenabled = true\n\n"))
+        .collect();
+    let config = load(
+        &format!(
+            r#"
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
 
-    let d1 = &config.devices["d1"];
-    assert_eq!(d1.thresholds.swipe_time_max, 1.5);
-    assert_eq!(d1.gestures["tap"].action, Some("xdotool click 1".into()));
-    assert_eq!(
-        d1.gestures["swipe_left"].action,
-        Some("xdotool key Left".into())
+{gesture_toml}
+"#
+        ),
+        true,
     );
-    assert_eq!(d1.gestures["long_press"].action, Some("echo long".into()));
+    for g in &names {
+        assert!(
+            config.devices["d1"].gestures[*g].enabled,
+            "gesture {g} not enabled"
+        );
+    }
+}
 
-    let d2 = &config.devices["d2"];
-    assert_eq!(d2.gestures["tap"].action, Some("xdotool click 3".into()));
-    assert!(d2.gestures["tap"].enabled);
-    assert_eq!(d2.thresholds.swipe_time_max, 2.0);
-    assert_eq!(d2.thresholds.tap_distance_max, 60.0);
+#[test]
+fn test_gesture_names_case_insensitive() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
+
+[device.d1.gestures.Swipe_Left]
+action = "echo left"
+enabled = true
+"#,
+        true,
+    );
+    assert_eq!(
+        resolve_action(GestureType::SwipeLeft, &config.devices["d1"].gestures),
+        Some("echo left")
+    );
+}
+
+#[test]
+fn test_device_overrides_global_gesture_across_case() {
+    let config = load(
+        r#"
+[global.gestures.TAP]
+action = "xdotool click 1"
+enabled = false
+
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
+
+[device.d1.gestures.tap]
+enabled = true
+"#,
+        true,
+    );
+    let tap = &config.devices["d1"].gestures["tap"];
+    assert!(tap.enabled);
+    assert_eq!(tap.action, Some("xdotool click 1".into()));
+}
+
+#[test]
+fn test_gesture_thresholds_merged_over_device() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
+
+[device.d1.thresholds]
+double_tap_interval = 0.4
+
+[device.d1.gestures.double_tap.thresholds]
+double_tap_interval = 0.2
+"#,
+        true,
+    );
+    let dev = &config.devices["d1"];
+    assert_eq!(
+        dev.gestures["double_tap"].thresholds["double_tap_interval"],
+        0.2
+    );
+    assert_eq!(dev.thresholds.double_tap_interval, 0.4);
+
+    let per_gesture = dev.gesture_thresholds(&dev.thresholds);
+    assert_eq!(per_gesture.len(), 1);
+    let (gesture, thresholds) = &per_gesture[0];
+    assert_eq!(*gesture, GestureType::DoubleTap);
+    assert_eq!(thresholds.double_tap_interval, 0.2);
+    assert_eq!(thresholds.tap_time_max, dev.thresholds.tap_time_max);
+}
+
+#[test]
+fn test_unknown_gesture_threshold_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
+
+[device.d1.gestures.tap.thresholds]
+tap_intervall = 0.2
+"#
+    ));
+    assert!(msg.contains("unknown threshold 'tap_intervall'"), "{msg}");
+    assert!(msg.contains("'tap'"), "{msg}");
+}
+
+#[test]
+fn test_gesture_log_settings_merged() {
+    let config = load(
+        r#"
+[global.gestures.pinch_out_repeat]
+action = "xdotool key ctrl+plus"
+log = false
+
+[global.gestures.circle_clockwise]
+action = "pactl set-sink-volume @DEFAULT_SINK@ +5%"
+log = false
+
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
+
+[device.d1.gestures.circle_clockwise]
+log_level = "debug"
+"#,
+        true,
+    );
+    let gestures = &config.devices["d1"].gestures;
+    assert_eq!(
+        gestures["pinch_out_repeat"].log_level,
+        Some(LevelFilter::Off)
+    );
+    assert_eq!(
+        gestures["circle_clockwise"].log_level,
+        Some(LevelFilter::Debug)
+    );
+}
+
+#[test]
+fn test_invalid_gesture_log_level_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1111:2222"
+enabled = true
+
+[device.d1.gestures.tap]
+log_level = "chatty"
+"#
+    ));
+    assert!(msg.contains("invalid log_level 'chatty'"), "{msg}");
+}
+
+// ── Global gesture inheritance ───────────────────────────────
+
+#[test]
+fn test_global_gestures_inherited() {
+    let config = load(
+        r#"
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = true
+
+[global.gestures.swipe_left]
+action = "xdotool key ctrl+shift+Tab"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+        true,
+    );
+    let d1 = &config.devices["d1"];
+    assert_eq!(d1.gestures["tap"].action, Some("xdotool click 1".into()));
+    assert_eq!(
+        d1.gestures["swipe_left"].action,
+        Some("xdotool key ctrl+shift+Tab".into())
+    );
+}
+
+#[test]
+fn test_device_overrides_global_gesture() {
+    let config = load(
+        r#"
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap]
+action = "xdotool click 3"
+"#,
+        true,
+    );
+    assert_eq!(
+        config.devices["d1"].gestures["tap"].action,
+        Some("xdotool click 3".into())
+    );
+    assert!(config.devices["d1"].gestures["tap"].enabled);
+}
+
+#[test]
+fn test_device_disables_global_gesture() {
+    let config = load(
+        r#"
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap]
+enabled = false
+"#,
+        true,
+    );
+    assert!(!config.devices["d1"].gestures["tap"].enabled);
+    assert_eq!(
+        config.devices["d1"].gestures["tap"].action,
+        Some("xdotool click 1".into())
+    );
+}
+
+#[test]
+fn test_device_adds_gesture_beyond_global() {
+    let config = load(
+        r#"
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.long_press]
+action = "xdotool key ctrl+r"
+enabled = true
+"#,
+        true,
+    );
+    let d1 = &config.devices["d1"];
+    assert!(d1.gestures.contains_key("tap"));
+    assert!(d1.gestures.contains_key("long_press"));
+}
+
+#[test]
+fn test_override_does_not_mutate_other_devices() {
+    let config = load(
+        r#"
+[global.gestures.tap]
+action = "global tap"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap]
+action = "device1 tap"
+
+[device.d2]
+device_usb_id = "2222:2222"
+enabled = true
+"#,
+        true,
+    );
+    assert_eq!(
+        config.devices["d1"].gestures["tap"].action,
+        Some("device1 tap".into())
+    );
+    assert_eq!(
+        config.devices["d2"].gestures["tap"].action,
+        Some("global tap".into())
+    );
+}
+
+#[test]
+fn test_no_global_gestures_fine() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap]
+action = "echo tap"
+enabled = true
+"#,
+        true,
+    );
+    assert_eq!(
+        config.devices["d1"].gestures["tap"].action,
+        Some("echo tap".into())
+    );
+}
+
+// ── Global-only configs (no auto-device creation) ────────────
+
+#[test]
+fn test_global_only_gestures_no_device() {
+    let config = load(
+        r#"
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = true
+"#,
+        true,
+    );
+    assert!(config.devices.is_empty());
+}
+
+#[test]
+fn test_global_only_thresholds_no_device() {
+    let config = load(
+        r#"
+[global.thresholds]
+swipe_time_max = 1.5
+"#,
+        false,
+    );
+    assert!(config.devices.is_empty());
+}
+
+// ── Full roundtrip ───────────────────────────────────────────
+
+#[test]
+fn test_full_config_roundtrip() {
+    let config = load(
+        r#"
+[global]
+log_level = "DEBUG"
+
+[global.thresholds]
+swipe_time_max = 1.5
+swipe_distance_min_pct = 0.15
+angle_tolerance_deg = 30.0
+tap_time_max = 0.2
+tap_distance_max = 60.0
+long_press_time_min = 0.8
+double_tap_interval = 0.3
+double_tap_distance_max = 50.0
+pinch_threshold_pct = 0.1
+
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = true
+
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+
+[device.d1]
+device_usb_id = "1234:5678"
+enabled = true
+
+[device.d1.gestures.long_press]
+action = "echo long"
+enabled = true
+
+[device.d2]
+device_usb_id = "5678:9abc"
+enabled = true
+
+[device.d2.gestures.tap]
+action = "xdotool click 3"
+
+[device.d2.thresholds]
+swipe_time_max = 2.0
+"#,
+        false,
+    );
+
+    assert_eq!(config.log_level, "DEBUG");
+
+    let d1 = &config.devices["d1"];
+    assert_eq!(d1.thresholds.swipe_time_max, 1.5);
+    assert_eq!(d1.gestures["tap"].action, Some("xdotool click 1".into()));
+    assert_eq!(
+        d1.gestures["swipe_left"].action,
+        Some("xdotool key Left".into())
+    );
+    assert_eq!(d1.gestures["long_press"].action, Some("echo long".into()));
+
+    let d2 = &config.devices["d2"];
+    assert_eq!(d2.gestures["tap"].action, Some("xdotool click 3".into()));
+    assert!(d2.gestures["tap"].enabled);
+    assert_eq!(d2.thresholds.swipe_time_max, 2.0);
+    assert_eq!(d2.thresholds.tap_distance_max, 60.0);
+}
+
+#[test]
+fn test_gesture_key_parsed_to_evdev_code() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.swipe_left]
+key = "KEY_LEFT"
+enabled = true
+"#,
+        true,
+    );
+    let gc = &config.devices["d1"].gestures["swipe_left"];
+    assert_eq!(gc.key, Some(evdev::Key::KEY_LEFT));
+    assert_eq!(gc.action, None);
+}
+
+#[test]
+fn test_gesture_unknown_key_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap]
+key = "KEY_BOGUS"
+enabled = true
+"#
+    ));
+    assert!(msg.contains("unknown key 'KEY_BOGUS'"));
+    assert!(msg.contains("'tap'"));
+}
+
+#[test]
+fn test_gesture_scroll_axis_parsed() {
+    let config = load(
+        r#"
+[global.gestures.pinch_out]
+scroll = "vertical"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.swipe_left]
+scroll = "horizontal"
+enabled = true
+"#,
+        true,
+    );
+    let gestures = &config.devices["d1"].gestures;
+    assert_eq!(gestures["pinch_out"].scroll, Some(ScrollAxis::Vertical));
+    assert_eq!(gestures["swipe_left"].scroll, Some(ScrollAxis::Horizontal));
+}
+
+#[test]
+fn test_only_when_class_global_and_device_override() {
+    let config = load(
+        r#"
+[global]
+only_when_class = "firefox"
+
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.b]
+device_usb_id = "2222:2222"
+enabled = true
+only_when_class = "chromium"
+
+[device.c]
+device_usb_id = "3333:3333"
+enabled = true
+only_when_class = ""
+"#,
+        true,
+    );
+    assert_eq!(
+        config.devices["a"].only_when_class.as_deref(),
+        Some("firefox")
+    );
+    assert_eq!(
+        config.devices["b"].only_when_class.as_deref(),
+        Some("chromium")
+    );
+    assert_eq!(config.devices["c"].only_when_class, None);
+}
+
+#[test]
+fn test_dump_shows_inherited_thresholds_on_device() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.thresholds]
+tap_time_max = 0.3
+
+[device.d1.gestures.swipe_left]
+key = "KEY_LEFT"
+enabled = true
+"#,
+        true,
+    );
+    let dumped: toml::Table = toml::to_string(&config).unwrap().parse().unwrap();
+    let device = &dumped["device"]["d1"];
+    assert_eq!(device["thresholds"]["swipe_time_max"].as_float(), Some(0.9));
+    assert_eq!(device["thresholds"]["tap_time_max"].as_float(), Some(0.3));
+    assert_eq!(
+        device["gestures"]["swipe_left"]["key"].as_str(),
+        Some("KEY_LEFT")
+    );
+}
+
+fn hm(h: u16, m: u16) -> u16 {
+    h * 60 + m
+}
+
+#[test]
+fn test_active_hours_parse() {
+    assert_eq!(
+        ActiveHours::parse("08:00-20:30"),
+        Some(ActiveHours {
+            start: hm(8, 0),
+            end: hm(20, 30)
+        })
+    );
+    assert_eq!(ActiveHours::parse("8:00"), None);
+    assert_eq!(ActiveHours::parse("24:00-06:00"), None);
+    assert_eq!(ActiveHours::parse("08:60-09:00"), None);
+}
+
+#[test]
+fn test_active_hours_in_and_out_of_window() {
+    let hours = ActiveHours::parse("08:00-20:00").unwrap();
+    assert!(!hours.contains(hm(7, 59)));
+    assert!(hours.contains(hm(8, 0)));
+    assert!(hours.contains(hm(19, 59)));
+    assert!(!hours.contains(hm(20, 0)));
+}
+
+#[test]
+fn test_active_hours_wrap_around_midnight() {
+    let hours = ActiveHours::parse("22:00-06:00").unwrap();
+    assert!(hours.contains(hm(23, 30)));
+    assert!(hours.contains(hm(0, 0)));
+    assert!(hours.contains(hm(5, 59)));
+    assert!(!hours.contains(hm(6, 0)));
+    assert!(!hours.contains(hm(12, 0)));
+}
+
+#[test]
+fn test_active_hours_device_and_gesture_windows() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+active_hours = "08:00-20:00"
+
+[device.d1.gestures.tap]
+action = "true"
+enabled = true
+
+[device.d1.gestures.swipe_left]
+action = "true"
+enabled = true
+active_hours = "12:00-13:00"
+"#,
+        true,
+    );
+    let d1 = &config.devices["d1"];
+    assert!(within_active_hours(GestureType::Tap, d1, hm(9, 0)));
+    assert!(!within_active_hours(GestureType::Tap, d1, hm(21, 0)));
+    assert!(!within_active_hours(GestureType::SwipeLeft, d1, hm(9, 0)));
+    assert!(within_active_hours(GestureType::SwipeLeft, d1, hm(12, 30)));
+}
+
+#[test]
+fn test_active_hours_invalid_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+active_hours = "morning"
+"#
+    ));
+    assert!(msg.contains("invalid active_hours 'morning'"));
+}
+
+#[test]
+fn test_by_fingers_merged_over_global() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "echo one"
+enabled = true
+
+[global.gestures.swipe_left.by_fingers]
+2 = "echo two"
+3 = "echo three"
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.swipe_left.by_fingers]
+3 = "echo device three"
+"#,
+        true,
+    );
+    let gc = &config.devices["d1"].gestures["swipe_left"];
+    assert_eq!(gc.action.as_deref(), Some("echo one"));
+    assert_eq!(gc.by_fingers[&2], "echo two");
+    assert_eq!(gc.by_fingers[&3], "echo device three");
+}
+
+#[test]
+fn test_by_fingers_invalid_count_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap.by_fingers]
+many = "echo"
+"#
+    ));
+    assert!(msg.contains("invalid finger count 'many'"));
+}
+
+#[test]
+fn test_tap_count_only_on_tap() {
+    let config = load(
+        r#"
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap]
+action = "echo triple"
+tap_count = 3
+enabled = true
+"#,
+        true,
+    );
+    assert_eq!(config.devices["d1"].gestures["tap"].tap_count, Some(3));
+
+    for (gesture, count) in [("swipe_left", 2), ("tap", 0)] {
+        let msg = load_err(&format!(
+            r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.{gesture}]
+tap_count = {count}
+"#
+        ));
+        assert!(msg.contains(&format!(
+            "invalid tap_count {count} for gesture '{gesture}'"
+        )));
+    }
+}
+
+#[test]
+fn test_swipe_commit_distance_is_optional_and_inherited() {
+    let config = load(
+        &format!(
+            "{ALL_THRESHOLDS}swipe_commit_distance_pct = 0.3\n{}",
+            r#"
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+"#
+        ),
+        false,
+    );
+    assert_eq!(
+        config.devices["a"].thresholds.swipe_commit_distance_pct,
+        Some(0.3)
+    );
+
+    let config = load(
+        r#"
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+        true,
+    );
+    assert_eq!(
+        config.devices["a"].thresholds.swipe_commit_distance_pct,
+        None
+    );
+}
+
+#[test]
+fn test_max_track_points_inherited_and_zero_is_unbounded() {
+    let config = load(
+        r#"
+[global]
+max_track_points = 128
+
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.b]
+device_usb_id = "2222:2222"
+enabled = true
+max_track_points = 0
+"#,
+        true,
+    );
+    assert_eq!(config.devices["a"].max_track_points, Some(128));
+    assert_eq!(config.devices["b"].max_track_points, None);
+}
+
+#[test]
+fn test_reference_resolution_inherited_and_validated() {
+    let config = load(
+        r#"
+[global]
+reference_resolution = [1920, 1080]
+
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+        true,
+    );
+    assert_eq!(
+        config.devices["a"].reference_resolution,
+        Some([1920.0, 1080.0])
+    );
+
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+reference_resolution = [1920, 0]
+"#
+    ));
+    assert!(msg.contains("reference_resolution must be two positive numbers"));
+}
+
+#[test]
+fn test_swipe_velocity_min_device_override() {
+    let config = load(
+        r#"
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.a.thresholds]
+swipe_velocity_min = 0.5
+
+[device.b]
+device_usb_id = "2222:2222"
+enabled = true
+"#,
+        true,
+    );
+    assert_eq!(config.devices["a"].thresholds.swipe_velocity_min, Some(0.5));
+    assert_eq!(config.devices["b"].thresholds.swipe_velocity_min, None);
+}
+
+#[test]
+fn test_serialize_actions_defaults_off() {
+    let config = load("", true);
+    assert!(!config.serialize_actions);
+
+    let config = load("[global]\nserialize_actions = true\n", false);
+    assert!(config.serialize_actions);
+}
+
+#[test]
+fn test_action_queue_capacity() {
+    let config = load("", true);
+    assert_eq!(config.action_queue_capacity, None);
+
+    let config = load("[global]\naction_queue_capacity = 4\n", false);
+    assert_eq!(config.action_queue_capacity, Some(4));
+
+    // Zero means no queue, not a queue that drops everything.
+    let config = load("[global]\naction_queue_capacity = 0\n", false);
+    assert_eq!(config.action_queue_capacity, None);
+}
+
+#[test]
+fn test_log_targets_parsed() {
+    let config = load(
+        "[global.log_targets]\nrecognizer = \"trace\"\nmanager = \"warn\"\n",
+        true,
+    );
+    assert_eq!(config.log_targets["recognizer"], LevelFilter::Trace);
+    assert_eq!(config.log_targets["manager"], LevelFilter::Warn);
+
+    let msg = load_err(&format!(
+        "{ALL_THRESHOLDS}\n[global.log_targets]\nevent = \"loud\"\n"
+    ));
+    assert!(msg.contains("invalid log level 'loud' for log target 'event'"));
+}
+
+#[test]
+fn test_target_level_resolution() {
+    let targets = BTreeMap::from([
+        ("recognizer".to_string(), LevelFilter::Trace),
+        ("manager".to_string(), LevelFilter::Warn),
+        ("bodgestr::manager".to_string(), LevelFilter::Error),
+    ]);
+    let level = |target| target_level(&targets, LevelFilter::Info, target);
+
+    assert_eq!(level("bodgestr::recognizer"), LevelFilter::Trace);
+    // The longest matching key wins.
+    assert_eq!(level("bodgestr::manager"), LevelFilter::Error);
+    // Suffixes only match whole path segments.
+    assert_eq!(level("bodgestr::mymanager"), LevelFilter::Info);
+    assert_eq!(level("bodgestr::event"), LevelFilter::Info);
+    assert_eq!(level("bodgestr"), LevelFilter::Info);
+}
+
+#[test]
+fn test_disabled_gestures() {
+    let config = load("", true);
+    assert!(config.disabled_gestures.is_empty());
+
+    let config = load(
+        "[global]\ndisabled_gestures = [\"pinch_in\", \"Pinch_Out\"]\n",
+        true,
+    );
+    assert_eq!(
+        config.disabled_gestures,
+        vec![GestureType::PinchIn, GestureType::PinchOut]
+    );
+
+    let msg = load_err(&format!(
+        "[global]\ndisabled_gestures = [\"pinch\"]\n{ALL_THRESHOLDS}"
+    ));
+    assert!(msg.contains("unknown gesture 'pinch' in disabled_gestures"));
+}
+
+#[test]
+fn test_watchdog_timeout() {
+    let config = load("", true);
+    assert_eq!(config.watchdog_timeout, None);
+
+    let config = load("[global]\nwatchdog_timeout_secs = 90\n", false);
+    assert_eq!(
+        config.watchdog_timeout,
+        Some(std::time::Duration::from_secs(90))
+    );
+
+    let config = load("[global]\nwatchdog_timeout_secs = 0\n", false);
+    assert_eq!(config.watchdog_timeout, None);
+}
+
+#[test]
+fn test_instant_tap() {
+    let config = load("", true);
+    assert!(!config.instant_tap);
+
+    let config = load("[global]\ninstant_tap = true\n", false);
+    assert!(config.instant_tap);
+}
+
+#[test]
+fn test_mqtt_section() {
+    let config = load("", true);
+    assert_eq!(config.mqtt, None);
+
+    let config = load(
+        "[global.mqtt]\nbroker = \"ha.local\"\ntopic = \"bodgestr\"\n",
+        true,
+    );
+    let mqtt = config.mqtt.unwrap();
+    assert_eq!(mqtt.broker, "ha.local");
+    assert_eq!(mqtt.port, 1883);
+    assert_eq!(mqtt.topic, "bodgestr");
+}
+
+#[test]
+fn test_templates_expand_in_actions() {
+    let config = load(
+        r#"
+[global.templates]
+xd = "xdotool key"
+
+[global.gestures.swipe_left]
+action = "{{xd}} Left"
+
+[global.gestures.swipe_left.by_fingers]
+3 = "{{ xd }} super+Left && {{xd}} Return"
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+wake_action = "{{xd}} shift"
+"#,
+        true,
+    );
+    let dev = &config.devices["d1"];
+    let gc = &dev.gestures["swipe_left"];
+    assert_eq!(gc.action.as_deref(), Some("xdotool key Left"));
+    assert_eq!(
+        gc.by_fingers[&3],
+        "xdotool key super+Left && xdotool key Return"
+    );
+    assert_eq!(dev.wake_action.as_deref(), Some("xdotool key shift"));
+}
+
+#[test]
+fn test_undefined_template_rejected() {
+    let msg = load_err(&format!(
+        r#"{ALL_THRESHOLDS}
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d1.gestures.tap]
+action = "{{{{nope}}}} x"
+"#
+    ));
+    assert!(msg.contains("undefined template 'nope'"));
+}
+
+// ── JSON configs ─────────────────────────────────────────────
+
+/// Write content to a temp file with the given extension and parse it.
+fn parse_with_suffix(content: &str, suffix: &str) -> Result<AppConfig, String> {
+    let mut f = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+    f.write_all(content.as_bytes()).unwrap();
+    f.flush().unwrap();
+    parse_config_file(f.path()).map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_json_config_matches_toml() {
+    let toml_config = parse_with_suffix(
+        &format!(
+            r#"{ALL_THRESHOLDS}
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = true
+
+[device.d1]
+device_usb_id = "1234:5678"
+enabled = true
+y_axis = "up"
+
+[device.d1.gestures.swipe_left]
+key = "KEY_LEFT"
+enabled = true
+
+[device.d1.thresholds]
+swipe_time_max = 2.0
+"#
+        ),
+        ".toml",
+    )
+    .unwrap();
+    let json_config = parse_with_suffix(
+        r#"{
+  "global": {
+    "thresholds": {
+      "swipe_time_max": 0.9,
+      "swipe_distance_min_pct": 0.15,
+      "angle_tolerance_deg": 30.0,
+      "tap_time_max": 0.2,
+      "tap_distance_max": 50.0,
+      "long_press_time_min": 0.8,
+      "double_tap_interval": 0.3,
+      "double_tap_distance_max": 50.0,
+      "pinch_threshold_pct": 0.1
+    },
+    "gestures": { "tap": { "action": "xdotool click 1", "enabled": true } }
+  },
+  "device": {
+    "d1": {
+      "device_usb_id": "1234:5678",
+      "enabled": true,
+      "y_axis": "up",
+      "gestures": { "swipe_left": { "key": "KEY_LEFT", "enabled": true } },
+      "thresholds": { "swipe_time_max": 2.0 }
+    }
+  }
+}"#,
+        ".json",
+    )
+    .unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&json_config).unwrap(),
+        serde_json::to_value(&toml_config).unwrap()
+    );
+    assert_eq!(json_config.devices["d1"].thresholds.swipe_time_max, 2.0);
+}
+
+#[test]
+fn test_parse_errors_name_the_format() {
+    let msg = parse_with_suffix("{ not json", ".json").unwrap_err();
+    assert!(msg.contains("as JSON"));
+
+    let msg = parse_with_suffix("this is not valid toml [[[", ".toml").unwrap_err();
+    assert!(msg.contains("as TOML"));
+}
+
+// ── Config diff ──────────────────────────────────────────────
+
+#[test]
+fn test_diff_reports_changed_action_and_added_device() {
+    let kiosk = r#"
+[device.kiosk]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.kiosk.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+"#;
+    let old = load(kiosk, true);
+    let new = load(
+        &format!(
+            "{}\n{}",
+            kiosk.replace("xdotool key Left", "xdotool key Home"),
+            r#"
+[device.tablet]
+device_usb_id = "2222:2222"
+enabled = true
+"#
+        ),
+        true,
+    );
+
+    let changes = diff_configs(&old, &new);
+    assert_eq!(changes.len(), 2, "{changes:?}");
+    assert_eq!(
+        changes[0].to_string(),
+        r#"~ device.kiosk.gestures.swipe_left.action: "xdotool key Left" -> "xdotool key Home""#
+    );
+    assert!(matches!(&changes[1], ConfigChange::Added(path, _) if path == "device.tablet"));
+    assert_eq!(changes[1].to_string(), "+ device.tablet");
+
+    // Reversed, the device is removed.
+    let changes = diff_configs(&new, &old);
+    assert!(changes.contains(&ConfigChange::Removed(
+        "device.tablet".to_string(),
+        serde_json::to_value(&new.devices["tablet"]).unwrap()
+    )));
+    assert!(diff_configs(&old, &old).is_empty());
+}
+
+// ── Default config path ──────────────────────────────────────
+
+#[test]
+fn test_default_config_path_precedence() {
+    let xdg = Path::new("/xdg");
+    let home = Path::new("/home/kiosk");
+    let xdg_file = Path::new("/xdg/bodgestr/gestures.toml");
+    let home_file = Path::new("/home/kiosk/.config/bodgestr/gestures.toml");
+    let system = Path::new(SYSTEM_CONFIG_PATH);
+
+    let all = |_: &Path| true;
+    assert_eq!(resolve_config_path(Some(xdg), Some(home), all), system);
+
+    let no_system = |p: &Path| p != system;
+    assert_eq!(
+        resolve_config_path(Some(xdg), Some(home), no_system),
+        xdg_file
+    );
+
+    let home_only = |p: &Path| p == home_file;
+    assert_eq!(
+        resolve_config_path(Some(xdg), Some(home), home_only),
+        home_file
+    );
+    // An empty XDG_CONFIG_HOME counts as unset.
+    assert_eq!(
+        resolve_config_path(Some(Path::new("")), Some(home), no_system),
+        home_file
+    );
+
+    // Nothing exists: the system path is reported in the read error.
+    let none = |_: &Path| false;
+    assert_eq!(resolve_config_path(Some(xdg), Some(home), none), system);
+    assert_eq!(resolve_config_path(None, None, none), system);
 }
@@ -0,0 +1,18 @@
+//! Tests for `bodgestr::control` - the runtime rebinding socket.
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::sync::Arc;
+
+use bodgestr::control::{RuntimeBindings, listen};
+
+#[test]
+fn test_socket_is_owner_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("control.sock");
+    let bindings = Arc::new(RuntimeBindings::new(["kiosk".to_string()]));
+    listen(&path, bindings).unwrap();
+
+    let meta = fs::symlink_metadata(&path).unwrap();
+    assert!(meta.file_type().is_socket());
+    assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+}
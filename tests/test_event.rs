@@ -2,14 +2,21 @@
 //!
 //! Tests use `TouchEvent` directly (no hardware) and also verify
 //! `classify_event` with synthetic `evdev::InputEvent`s.
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::time::{Duration, Instant, SystemTime};
 
-use bodgestr::config::{GestureConfig, ValidatedThresholds};
+use bodgestr::config::{
+    CoordinateSpace, GestureConfig, PrimaryFinger, ValidatedThresholds, parse_config_file,
+};
 use bodgestr::event::{
-    TouchEvent, classify_event, parse_usb_id, process_touch_events, resolve_action,
+    ReleaseSignal, TouchEvent, WakeGate, binds_double_tap, classify_event,
+    classify_single_touch_event, format_bindings, max_tap_count, parse_usb_id, process_batch,
+    process_touch_events, process_touch_events_detailed, resolve_action, resolve_finger_action,
+    skip_initial_gestures, tap_count_matches,
 };
-use bodgestr::recognizer::{GestureRecognizer, GestureType};
-use evdev::{AbsoluteAxisType, EventType, InputEvent, Synchronization};
+use bodgestr::recognizer::{GestureDetails, GestureRecognizer, GestureType, MockClock};
+use evdev::{AbsoluteAxisType, AttributeSet, EventType, InputEvent, Key, Synchronization};
 
 // -- Helpers --------------------------------------------------
 
@@ -24,6 +31,17 @@ fn default_thresholds() -> ValidatedThresholds {
         tap_distance_max: 50.0,
         double_tap_distance_max: 50.0,
         pinch_threshold_pct: 0.1,
+        swipe_commit_distance_pct: None,
+        swipe_reversal_pct: None,
+        swipe_velocity_min: None,
+        pinch_threshold_min_px: None,
+        swipe_distance_min_px: None,
+        pinch_repeat_interval: None,
+        circle_turn_min: None,
+        circle_radius_tolerance_pct: None,
+        swipe_reject_band_deg: None,
+        long_press_drift_max: None,
+        long_swipe_distance_pct: None,
     }
 }
 
@@ -43,6 +61,14 @@ fn make_gestures(entries: &[(&str, &str, bool)]) -> HashMap<String, GestureConfi
                     } else {
                         Some(action.to_string())
                     },
+                    key: None,
+                    scroll: None,
+                    active_hours: None,
+                    by_fingers: BTreeMap::new(),
+                    tap_count: None,
+                    post_action_delay_ms: None,
+                    thresholds: BTreeMap::new(),
+                    log_level: None,
                     enabled: *enabled,
                 },
             )
@@ -135,6 +161,122 @@ fn test_swipe_down() {
     assert_eq!(gestures, vec![GestureType::SwipeDown]);
 }
 
+/// Two fingers swipe left; the first lift reports a swipe, then the finger
+/// still down swipes left again before lifting too.
+fn swipe_left_twice_without_lift() -> Vec<TouchEvent> {
+    vec![
+        TouchEvent::Slot(0),
+        TouchEvent::TrackingId(0),
+        TouchEvent::PositionX(800.0),
+        TouchEvent::PositionY(400.0),
+        TouchEvent::Slot(1),
+        TouchEvent::TrackingId(1),
+        TouchEvent::PositionX(800.0),
+        TouchEvent::PositionY(600.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(0),
+        TouchEvent::PositionX(100.0),
+        TouchEvent::Slot(1),
+        TouchEvent::PositionX(100.0),
+        TouchEvent::SynReport,
+        // Finger 0 lifts while finger 1 moves back and swipes again.
+        TouchEvent::Slot(0),
+        TouchEvent::FingerUp,
+        TouchEvent::Slot(1),
+        TouchEvent::PositionX(900.0),
+        TouchEvent::PositionY(600.0),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(100.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+        TouchEvent::SynReport,
+    ]
+}
+
+#[test]
+fn test_suppress_repeat_needs_lift_between_identical_gestures() {
+    let mut rec = make_recognizer();
+    assert_eq!(
+        process_touch_events(&mut rec, &swipe_left_twice_without_lift()),
+        vec![GestureType::SwipeLeft, GestureType::SwipeLeft]
+    );
+
+    let mut rec = make_recognizer().with_suppress_repeat(true);
+    assert_eq!(
+        process_touch_events(&mut rec, &swipe_left_twice_without_lift()),
+        vec![GestureType::SwipeLeft]
+    );
+    // All fingers lifted: the next swipe is reported again.
+    assert_eq!(
+        process_touch_events(&mut rec, &swipe_left()),
+        vec![GestureType::SwipeLeft]
+    );
+}
+
+#[test]
+fn test_swipe_in_normalized_space() {
+    let (x_range, y_range) = CoordinateSpace::Normalized.fixed_ranges().unwrap();
+    let mut rec = GestureRecognizer::new(default_thresholds(), x_range, y_range);
+    let events = [
+        TouchEvent::TrackingId(0),
+        TouchEvent::PositionX(0.2),
+        TouchEvent::PositionY(0.5),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(0.5),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(0.9),
+        TouchEvent::PositionY(0.52),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+    ];
+    assert_eq!(
+        process_touch_events(&mut rec, &events),
+        vec![GestureType::SwipeRight]
+    );
+}
+
+#[test]
+fn test_swipe_once_per_contact_collapses_repeated_crossings() {
+    let mut rec = make_recognizer().with_swipe_once_per_contact(true);
+    assert_eq!(
+        process_touch_events(&mut rec, &swipe_left_twice_without_lift()),
+        vec![GestureType::SwipeLeft]
+    );
+
+    // The remaining finger crossing the threshold the other way is
+    // collapsed too.
+    let mut events = swipe_left_twice_without_lift();
+    let first_lift = events
+        .iter()
+        .position(|e| *e == TouchEvent::FingerUp)
+        .unwrap();
+    events.truncate(first_lift + 1);
+    events.extend([
+        TouchEvent::Slot(1),
+        TouchEvent::PositionX(100.0),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(900.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+        TouchEvent::SynReport,
+    ]);
+    assert_eq!(
+        feed(&events),
+        vec![GestureType::SwipeLeft, GestureType::SwipeRight]
+    );
+    let mut rec = make_recognizer().with_swipe_once_per_contact(true);
+    assert_eq!(
+        process_touch_events(&mut rec, &events),
+        vec![GestureType::SwipeLeft]
+    );
+
+    // Every finger lifted: the next contact swipes again.
+    assert_eq!(
+        process_touch_events(&mut rec, &swipe_left()),
+        vec![GestureType::SwipeLeft]
+    );
+}
+
 // -- process_touch_events: edge cases -------------------------
 
 #[test]
@@ -223,6 +365,473 @@ fn test_recognizer_reset_after_finger_up() {
     assert_eq!(g2, vec![GestureType::SwipeLeft]);
 }
 
+// -- process_batch --------------------------------------------
+
+/// Raw evdev events for a one-finger swipe from `x_from` to `x_to`.
+fn raw_swipe(x_from: i32, x_to: i32) -> Vec<InputEvent> {
+    let abs = |axis: AbsoluteAxisType, value| InputEvent::new(EventType::ABSOLUTE, axis.0, value);
+    let syn = || InputEvent::new(EventType::SYNCHRONIZATION, Synchronization::SYN_REPORT.0, 0);
+    vec![
+        abs(AbsoluteAxisType::ABS_MT_TRACKING_ID, 1),
+        abs(AbsoluteAxisType::ABS_MT_POSITION_X, x_from),
+        abs(AbsoluteAxisType::ABS_MT_POSITION_Y, 500),
+        syn(),
+        abs(AbsoluteAxisType::ABS_MT_POSITION_X, x_to),
+        syn(),
+        abs(AbsoluteAxisType::ABS_MT_TRACKING_ID, -1),
+        syn(),
+    ]
+}
+
+#[test]
+fn test_process_batch_recognizes_raw_events() {
+    let mut rec = make_recognizer();
+    let mut scratch = Vec::new();
+    let fired = process_batch(&mut rec, &raw_swipe(800, 100), &mut scratch, false);
+    let gestures: Vec<_> = fired.iter().map(|d| d.gesture).collect();
+    assert_eq!(gestures, vec![GestureType::SwipeLeft]);
+}
+
+#[test]
+fn test_process_batch_reuses_scratch_buffer() {
+    let mut rec = make_recognizer();
+    let mut scratch = Vec::with_capacity(64);
+    let buffer = scratch.as_ptr();
+
+    for (from, to) in [(800, 100), (100, 800), (800, 100)] {
+        assert_eq!(
+            process_batch(&mut rec, &raw_swipe(from, to), &mut scratch, false).len(),
+            1
+        );
+        // Refilled in place - no reallocation while batches fit.
+        assert_eq!(scratch.as_ptr(), buffer);
+        assert_eq!(scratch.capacity(), 64);
+    }
+}
+
+// -- skip_initial_gestures ------------------------------------
+
+#[test]
+fn test_skip_initial_gestures_drops_first_n() {
+    let mut rec = make_recognizer();
+    let mut remaining = 1;
+    let first = skip_initial_gestures(
+        process_touch_events(&mut rec, &swipe_left()),
+        &mut remaining,
+    );
+    let second = skip_initial_gestures(
+        process_touch_events(&mut rec, &swipe_right()),
+        &mut remaining,
+    );
+    assert!(first.is_empty());
+    assert_eq!(second, vec![GestureType::SwipeRight]);
+    assert_eq!(remaining, 0);
+}
+
+#[test]
+fn test_skip_initial_gestures_zero_passes_through() {
+    let mut remaining = 0;
+    let gestures = skip_initial_gestures(feed(&swipe_left()), &mut remaining);
+    assert_eq!(gestures, vec![GestureType::SwipeLeft]);
+}
+
+#[test]
+fn test_skip_initial_gestures_within_one_batch() {
+    let mut remaining = 2;
+    let gestures = skip_initial_gestures(
+        vec![
+            GestureType::Tap,
+            GestureType::SwipeUp,
+            GestureType::SwipeDown,
+        ],
+        &mut remaining,
+    );
+    assert_eq!(gestures, vec![GestureType::SwipeDown]);
+}
+
+// -- Long-press drag -------------------------------------------
+
+#[test]
+fn test_long_press_drag_sequence_through_pipeline() {
+    let mut rec = make_recognizer().with_long_press_drag(true);
+    process_touch_events(
+        &mut rec,
+        &[
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(500.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+        ],
+    );
+    // Pretend the finger has been held in place for a second.
+    let start = rec.touch_start.as_mut().unwrap();
+    start.time -= Duration::from_secs(1);
+
+    let gestures = process_touch_events(
+        &mut rec,
+        &[
+            TouchEvent::PositionX(800.0),
+            TouchEvent::SynReport,
+            TouchEvent::PositionX(900.0),
+            TouchEvent::SynReport,
+            TouchEvent::FingerUp,
+        ],
+    );
+    assert_eq!(
+        gestures,
+        vec![
+            GestureType::LongPressDragStart,
+            GestureType::LongPressDragEnd
+        ]
+    );
+}
+
+// -- Swipe commit distance ------------------------------------
+
+fn make_commit_recognizer() -> GestureRecognizer {
+    let th = ValidatedThresholds {
+        swipe_commit_distance_pct: Some(0.5),
+        ..default_thresholds()
+    };
+    GestureRecognizer::new(th, (0.0, 1000.0), (0.0, 1000.0))
+}
+
+#[test]
+fn test_swipe_below_commit_distance_begins_and_cancels() {
+    let mut rec = make_commit_recognizer();
+    // 30% of the screen: past swipe_distance_min_pct, short of the commit.
+    let gestures = process_touch_events(
+        &mut rec,
+        &[
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(500.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+            TouchEvent::PositionX(800.0),
+            TouchEvent::SynReport,
+            TouchEvent::FingerUp,
+        ],
+    );
+    assert_eq!(
+        gestures,
+        vec![GestureType::SwipeBegin, GestureType::SwipeCancel]
+    );
+}
+
+#[test]
+fn test_swipe_past_commit_distance_begins_and_fires() {
+    let mut rec = make_commit_recognizer();
+    let gestures = process_touch_events(&mut rec, &swipe_right());
+    assert_eq!(
+        gestures,
+        vec![GestureType::SwipeBegin, GestureType::SwipeRight]
+    );
+}
+
+#[test]
+fn test_no_swipe_begin_without_commit_distance() {
+    let gestures = feed(&swipe_right());
+    assert_eq!(gestures, vec![GestureType::SwipeRight]);
+}
+
+// -- Pinch repeat ---------------------------------------------
+
+/// One report per finger, both at the same height.
+fn two_finger_frame(left: f64, right: f64) -> Vec<TouchEvent> {
+    vec![
+        TouchEvent::TrackingId(0),
+        TouchEvent::PositionX(left),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+        TouchEvent::TrackingId(1),
+        TouchEvent::PositionX(right),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+    ]
+}
+
+/// Spread two fingers from 200 to 400 apart, hold for 600 ms, release.
+fn spread_and_hold(rec: &mut GestureRecognizer, clock: &MockClock) -> Vec<GestureType> {
+    let mut gestures = process_touch_events(rec, &two_finger_frame(400.0, 600.0));
+    gestures.extend(process_touch_events(rec, &two_finger_frame(300.0, 700.0)));
+    for step in 0..6 {
+        clock.advance(Duration::from_millis(100));
+        let jitter = f64::from(step % 2);
+        gestures.extend(process_touch_events(
+            rec,
+            &two_finger_frame(300.0 + jitter, 700.0),
+        ));
+    }
+    gestures.extend(process_touch_events(rec, &[TouchEvent::FingerUp]));
+    gestures
+}
+
+#[test]
+fn test_spread_hold_repeats_pinch_out() {
+    let clock = MockClock::new();
+    let th = ValidatedThresholds {
+        pinch_repeat_interval: Some(0.15),
+        ..default_thresholds()
+    };
+    let mut rec =
+        GestureRecognizer::new(th, (0.0, 1000.0), (0.0, 1000.0)).with_clock(clock.clone());
+    assert_eq!(
+        spread_and_hold(&mut rec, &clock),
+        vec![
+            GestureType::PinchOutRepeat,
+            GestureType::PinchOutRepeat,
+            GestureType::PinchOutRepeat,
+            GestureType::PinchOut,
+        ]
+    );
+}
+
+#[test]
+fn test_spread_hold_without_repeat_interval() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer().with_clock(clock.clone());
+    assert_eq!(
+        spread_and_hold(&mut rec, &clock),
+        vec![GestureType::PinchOut]
+    );
+}
+
+// -- Palm guard -----------------------------------------------
+
+/// Three fingers side by side, all moved `dx` to the left, then lifted.
+fn three_finger_drag(dx: f64) -> Vec<TouchEvent> {
+    let mut events = Vec::new();
+    for (slot, x) in [(0, 700.0), (1, 800.0), (2, 900.0)] {
+        events.extend([
+            TouchEvent::Slot(slot),
+            TouchEvent::TrackingId(slot),
+            TouchEvent::PositionX(x),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+        ]);
+    }
+    for (slot, x) in [(0, 700.0), (1, 800.0), (2, 900.0)] {
+        events.extend([
+            TouchEvent::Slot(slot),
+            TouchEvent::PositionX(x - dx),
+            TouchEvent::SynReport,
+        ]);
+    }
+    for slot in 0..3 {
+        events.extend([TouchEvent::Slot(slot), TouchEvent::FingerUp]);
+    }
+    events.push(TouchEvent::SynReport);
+    events
+}
+
+#[test]
+fn test_max_fingers_cancels_palm_contact() {
+    let mut rec = make_recognizer();
+    assert!(!process_touch_events(&mut rec, &three_finger_drag(600.0)).is_empty());
+
+    let mut rec = make_recognizer().with_max_fingers(Some(2));
+    assert!(process_touch_events(&mut rec, &three_finger_drag(600.0)).is_empty());
+    // Once every finger has lifted, gestures are recognized again.
+    assert_eq!(
+        process_touch_events(&mut rec, &swipe_left()),
+        vec![GestureType::SwipeLeft]
+    );
+}
+
+#[test]
+fn test_max_fingers_allows_contacts_up_to_limit() {
+    let mut rec = make_recognizer().with_max_fingers(Some(2));
+    let mut gestures = process_touch_events(&mut rec, &two_finger_frame(400.0, 600.0));
+    gestures.extend(process_touch_events(
+        &mut rec,
+        &two_finger_frame(300.0, 700.0),
+    ));
+    gestures.extend(process_touch_events(&mut rec, &[TouchEvent::FingerUp]));
+    assert_eq!(gestures, vec![GestureType::PinchOut]);
+}
+
+// -- Second finger during a swipe ----------------------------
+
+/// One finger in slot 0 moves from 800 to `first_to`, then a second finger
+/// lands in slot 1 at `second_at` and moves to `second_to` while the first
+/// continues to 100; both lift.
+fn interrupted_drag(first_to: f64, second_at: f64, second_to: f64) -> Vec<TouchEvent> {
+    let mut events = vec![
+        TouchEvent::Slot(0),
+        TouchEvent::TrackingId(0),
+        TouchEvent::PositionX(800.0),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(first_to),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(1),
+        TouchEvent::TrackingId(1),
+        TouchEvent::PositionX(second_at),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(0),
+        TouchEvent::PositionX(100.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(1),
+        TouchEvent::PositionX(second_to),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(0),
+        TouchEvent::FingerUp,
+    ];
+    // The remaining finger then swipes right on its own before lifting.
+    events.extend([
+        TouchEvent::Slot(1),
+        TouchEvent::PositionX(second_to),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(second_to + 400.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+        TouchEvent::SynReport,
+    ]);
+    events
+}
+
+#[test]
+fn test_second_finger_cancels_single_finger_swipe() {
+    let mut rec = make_recognizer();
+    assert!(process_touch_events(&mut rec, &interrupted_drag(500.0, 900.0, 200.0)).is_empty());
+    // Once every finger has lifted, swipes are recognized again.
+    assert_eq!(
+        process_touch_events(&mut rec, &swipe_left()),
+        vec![GestureType::SwipeLeft]
+    );
+}
+
+#[test]
+fn test_second_finger_at_rest_still_swipes() {
+    // Both fingers land before either moves: no single-finger touch is cut
+    // short, so the primary finger's swipe fires.
+    let mut events = two_finger_frame(800.0, 900.0);
+    events.extend([
+        TouchEvent::TrackingId(0),
+        TouchEvent::PositionX(100.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+    ]);
+    assert_eq!(feed(&events), vec![GestureType::SwipeLeft]);
+}
+
+#[test]
+fn test_second_finger_pinch_still_recognized() {
+    let mut rec = make_recognizer();
+    let gestures = process_touch_events(&mut rec, &interrupted_drag(700.0, 750.0, 1000.0));
+    assert_eq!(gestures, vec![GestureType::PinchOut]);
+}
+
+/// Two fingers landing a frame apart and lifting a frame apart, the second
+/// finger reporting once more between the lifts.
+fn staggered_two_finger_tap() -> Vec<TouchEvent> {
+    vec![
+        TouchEvent::Slot(0),
+        TouchEvent::TrackingId(10),
+        TouchEvent::PositionX(400.0),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(1),
+        TouchEvent::TrackingId(11),
+        TouchEvent::PositionX(600.0),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(0),
+        TouchEvent::FingerUp,
+        TouchEvent::SynReport,
+        TouchEvent::Slot(1),
+        TouchEvent::PositionX(601.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+        TouchEvent::SynReport,
+    ]
+}
+
+#[test]
+fn test_staggered_two_finger_tap_counts_both_fingers() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer().with_clock(clock.clone());
+    // The last finger's lift is neither a second tap nor a tap of its own.
+    assert!(process_touch_events(&mut rec, &staggered_two_finger_tap()).is_empty());
+    clock.advance(Duration::from_millis(400));
+    let gestures = process_touch_events_detailed(&mut rec, &[TouchEvent::SynReport]);
+    assert_eq!(gestures.len(), 1);
+    assert_eq!(gestures[0].gesture, GestureType::Tap);
+    assert_eq!(gestures[0].finger_count, 2);
+
+    let mut rec = make_recognizer().with_immediate_tap(true);
+    let gestures = process_touch_events_detailed(&mut rec, &staggered_two_finger_tap());
+    assert_eq!(gestures.len(), 1);
+    assert_eq!(gestures[0].finger_count, 2);
+    // A later one-finger tap is counted afresh.
+    let gestures = process_touch_events_detailed(&mut rec, &single_tap());
+    assert_eq!(gestures[0].finger_count, 1);
+}
+
+// -- touch_down / touch_up ------------------------------------
+
+#[test]
+fn test_touch_down_and_up_actions_when_bound() {
+    let mut rec = make_recognizer().with_touch_events(true);
+    let gestures = process_touch_events(&mut rec, &swipe_left());
+    let config_gestures = make_gestures(&[
+        ("touch_down", "echo down", true),
+        ("touch_up", "echo up", true),
+    ]);
+    let actions: Vec<_> = gestures
+        .iter()
+        .filter_map(|g| resolve_action(*g, &config_gestures))
+        .collect();
+    assert_eq!(actions, vec!["echo down", "echo up"]);
+}
+
+#[test]
+fn test_touch_events_bracket_recognized_gesture() {
+    let mut rec = make_recognizer().with_touch_events(true);
+    let gestures = process_touch_events(&mut rec, &swipe_left());
+    assert_eq!(
+        gestures,
+        vec![
+            GestureType::TouchDown,
+            GestureType::SwipeLeft,
+            GestureType::TouchUp
+        ]
+    );
+}
+
+#[test]
+fn test_touch_events_off_by_default() {
+    assert_eq!(feed(&swipe_left()), vec![GestureType::SwipeLeft]);
+}
+
+// -- WakeGate -------------------------------------------------
+
+#[test]
+fn test_wake_gate_disabled_never_consumes() {
+    let t0 = Instant::now();
+    let mut gate = WakeGate::new(None, t0);
+    assert!(!gate.consume_as_wake(t0 + Duration::from_secs(3600)));
+}
+
+#[test]
+fn test_wake_gate_active_use_not_consumed() {
+    let t0 = Instant::now();
+    let mut gate = WakeGate::new(Some(Duration::from_secs(60)), t0);
+    assert!(!gate.consume_as_wake(t0 + Duration::from_secs(30)));
+    assert!(!gate.consume_as_wake(t0 + Duration::from_secs(80)));
+}
+
+#[test]
+fn test_wake_gate_idle_then_wake_suppresses_one() {
+    let t0 = Instant::now();
+    let mut gate = WakeGate::new(Some(Duration::from_secs(60)), t0);
+    let woke = t0 + Duration::from_secs(120);
+    assert!(gate.consume_as_wake(woke));
+    // The gesture right after waking is dispatched normally.
+    assert!(!gate.consume_as_wake(woke + Duration::from_secs(1)));
+}
+
 // -- classify_event: evdev → TouchEvent -----------------------
 
 #[test]
@@ -265,12 +874,93 @@ fn test_classify_tracking_id_finger_up() {
     assert_eq!(classify_event(&ev), Some(TouchEvent::FingerUp));
 }
 
+#[test]
+fn test_classify_mt_slot() {
+    let ev = InputEvent::new(EventType::ABSOLUTE, AbsoluteAxisType::ABS_MT_SLOT.0, 1);
+    assert_eq!(classify_event(&ev), Some(TouchEvent::Slot(1)));
+}
+
+#[test]
+fn test_primary_lowest_slot_follows_slot_zero() {
+    // Slot 1 holds a resting finger with the lower tracking ID; the finger
+    // in slot 0 swipes right.
+    let events = [
+        TouchEvent::Slot(1),
+        TouchEvent::TrackingId(3),
+        TouchEvent::PositionX(450.0),
+        TouchEvent::PositionY(900.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(0),
+        TouchEvent::TrackingId(8),
+        TouchEvent::PositionX(100.0),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(1),
+        TouchEvent::PositionX(451.0),
+        TouchEvent::SynReport,
+        TouchEvent::Slot(0),
+        TouchEvent::PositionX(800.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+    ];
+    let mut rec = make_recognizer().with_primary_finger(PrimaryFinger::LowestSlot);
+    assert_eq!(
+        process_touch_events(&mut rec, &events),
+        vec![GestureType::SwipeRight]
+    );
+    let mut rec = make_recognizer().with_primary_finger(PrimaryFinger::LowestId);
+    assert_ne!(
+        process_touch_events(&mut rec, &events),
+        vec![GestureType::SwipeRight]
+    );
+}
+
 #[test]
 fn test_classify_syn_report() {
     let ev = InputEvent::new(EventType::SYNCHRONIZATION, Synchronization::SYN_REPORT.0, 0);
     assert_eq!(classify_event(&ev), Some(TouchEvent::SynReport));
 }
 
+#[test]
+fn test_classify_syn_report_keeps_timestamp() {
+    let ev = InputEvent::new_now(EventType::SYNCHRONIZATION, Synchronization::SYN_REPORT.0, 0);
+    assert_eq!(
+        classify_event(&ev),
+        Some(TouchEvent::SynReportAt(ev.timestamp()))
+    );
+}
+
+#[test]
+fn test_swipe_duration_follows_event_timestamps() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer().with_clock(clock.clone());
+    let t0 = SystemTime::now();
+
+    let mut details = process_touch_events_detailed(
+        &mut rec,
+        &[
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(800.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReportAt(t0),
+        ],
+    );
+    // Processing lags the hardware: 600 ms pass for a 300 ms motion.
+    clock.advance(Duration::from_millis(600));
+    details.extend(process_touch_events_detailed(
+        &mut rec,
+        &[
+            TouchEvent::PositionX(100.0),
+            TouchEvent::SynReportAt(t0 + Duration::from_millis(300)),
+            TouchEvent::FingerUp,
+        ],
+    ));
+
+    assert_eq!(details.len(), 1);
+    assert_eq!(details[0].gesture, GestureType::SwipeLeft);
+    assert!((details[0].duration - 0.3).abs() < 1e-6);
+}
+
 #[test]
 fn test_classify_irrelevant_abs_axis() {
     // ABS_X (not multi-touch) should be ignored
@@ -278,12 +968,236 @@ fn test_classify_irrelevant_abs_axis() {
     assert_eq!(classify_event(&ev), None);
 }
 
+#[test]
+fn test_classify_single_touch_abs_axes() {
+    let ev = InputEvent::new(EventType::ABSOLUTE, AbsoluteAxisType::ABS_X.0, 100);
+    assert_eq!(
+        classify_single_touch_event(&ev),
+        Some(TouchEvent::PositionX(100.0))
+    );
+    let ev = InputEvent::new(EventType::ABSOLUTE, AbsoluteAxisType::ABS_Y.0, 200);
+    assert_eq!(
+        classify_single_touch_event(&ev),
+        Some(TouchEvent::PositionY(200.0))
+    );
+}
+
+#[test]
+fn test_classify_single_touch_keeps_other_events() {
+    let ev = InputEvent::new(EventType::KEY, Key::BTN_TOUCH.code(), 1);
+    assert_eq!(
+        classify_single_touch_event(&ev),
+        Some(TouchEvent::TouchBegin)
+    );
+    let ev = InputEvent::new(
+        EventType::ABSOLUTE,
+        AbsoluteAxisType::ABS_MT_POSITION_X.0,
+        42,
+    );
+    assert_eq!(
+        classify_single_touch_event(&ev),
+        Some(TouchEvent::PositionX(42.0))
+    );
+}
+
+#[test]
+fn test_process_batch_single_touch_swipe() {
+    let abs = |axis: AbsoluteAxisType, value| InputEvent::new(EventType::ABSOLUTE, axis.0, value);
+    let touch = |value| InputEvent::new(EventType::KEY, Key::BTN_TOUCH.code(), value);
+    let syn = || InputEvent::new(EventType::SYNCHRONIZATION, Synchronization::SYN_REPORT.0, 0);
+    let events = [
+        touch(1),
+        abs(AbsoluteAxisType::ABS_X, 800),
+        abs(AbsoluteAxisType::ABS_Y, 500),
+        syn(),
+        abs(AbsoluteAxisType::ABS_X, 100),
+        syn(),
+        touch(0),
+        syn(),
+    ];
+
+    let mut scratch = Vec::new();
+    let fired = process_batch(&mut make_recognizer(), &events, &mut scratch, true);
+    let gestures: Vec<_> = fired.iter().map(|d| d.gesture).collect();
+    assert_eq!(gestures, vec![GestureType::SwipeLeft]);
+    // Without single-touch mode the legacy axes are ignored.
+    assert!(process_batch(&mut make_recognizer(), &events, &mut scratch, false).is_empty());
+}
+
+#[test]
+fn test_classify_btn_touch_down() {
+    let ev = InputEvent::new(EventType::KEY, Key::BTN_TOUCH.code(), 1);
+    assert_eq!(classify_event(&ev), Some(TouchEvent::TouchBegin));
+}
+
+#[test]
+fn test_classify_btn_touch_up() {
+    let ev = InputEvent::new(EventType::KEY, Key::BTN_TOUCH.code(), 0);
+    assert_eq!(classify_event(&ev), Some(TouchEvent::FingerUp));
+}
+
+#[test]
+fn test_classify_btn_touch_autorepeat_ignored() {
+    let ev = InputEvent::new(EventType::KEY, Key::BTN_TOUCH.code(), 2);
+    assert_eq!(classify_event(&ev), None);
+}
+
+#[test]
+fn test_btn_touch_only_device_swipe() {
+    let gestures = feed(&[
+        TouchEvent::PositionX(800.0),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::TouchBegin,
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(100.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+    ]);
+    assert_eq!(gestures, vec![GestureType::SwipeLeft]);
+}
+
+#[test]
+fn test_btn_touch_begin_discards_stale_contact() {
+    let gestures = feed(&[
+        // Stale contact whose release was never reported.
+        TouchEvent::PositionX(900.0),
+        TouchEvent::PositionY(900.0),
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(100.0),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::TouchBegin,
+        TouchEvent::SynReport,
+        TouchEvent::PositionX(800.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+    ]);
+    assert_eq!(gestures, vec![GestureType::SwipeRight]);
+}
+
+#[test]
+fn test_btn_touch_begin_ignored_with_tracking_id() {
+    let mut events = swipe_left();
+    // Mid-swipe BTN_TOUCH must not discard an ID-tracked contact.
+    events.insert(4, TouchEvent::TouchBegin);
+    assert_eq!(feed(&events), vec![GestureType::SwipeLeft]);
+}
+
 #[test]
 fn test_classify_key_event_ignored() {
     let ev = InputEvent::new(EventType::KEY, 0x110, 1); // BTN_LEFT
     assert_eq!(classify_event(&ev), None);
 }
 
+// -- tap_count ------------------------------------------------
+
+fn tap_details(tap_count: usize) -> GestureDetails {
+    GestureDetails {
+        gesture: GestureType::Tap,
+        angle_deg: 0.0,
+        distance: 0.0,
+        duration: 0.05,
+        finger_count: 1,
+        tap_count,
+        pinch: None,
+        trajectory: None,
+    }
+}
+
+#[test]
+fn test_tap_count_resolves_matching_taps_only() {
+    for wanted in [1, 2, 3] {
+        let mut gestures = make_gestures(&[("tap", "echo tap", true)]);
+        if wanted > 1 {
+            gestures.get_mut("tap").unwrap().tap_count = Some(wanted);
+        }
+        assert_eq!(max_tap_count(&gestures), wanted);
+        for taps in [1, 2, 3] {
+            assert_eq!(
+                tap_count_matches(&tap_details(taps), &gestures),
+                taps == wanted,
+                "tap_count = {wanted}, {taps} taps"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_tap_count_ignores_other_gestures() {
+    let mut gestures = make_gestures(&[("tap", "echo tap", true)]);
+    gestures.get_mut("tap").unwrap().tap_count = Some(3);
+    let swipe = GestureDetails {
+        gesture: GestureType::SwipeLeft,
+        ..tap_details(1)
+    };
+    assert!(tap_count_matches(&swipe, &gestures));
+}
+
+// -- Immediate tap --------------------------------------------
+
+fn single_tap() -> Vec<TouchEvent> {
+    vec![
+        TouchEvent::TrackingId(0),
+        TouchEvent::PositionX(500.0),
+        TouchEvent::PositionY(500.0),
+        TouchEvent::SynReport,
+        TouchEvent::FingerUp,
+        TouchEvent::SynReport,
+    ]
+}
+
+#[test]
+fn test_immediate_tap_fires_on_finger_up() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer().with_clock(clock.clone());
+    // Waits for a possible second tap.
+    assert!(process_touch_events(&mut rec, &single_tap()).is_empty());
+
+    let mut rec = make_recognizer()
+        .with_clock(clock.clone())
+        .with_immediate_tap(true);
+    assert_eq!(
+        process_touch_events(&mut rec, &single_tap()),
+        vec![GestureType::Tap]
+    );
+    // A quick second tap is just another tap.
+    clock.advance(Duration::from_millis(100));
+    assert_eq!(
+        process_touch_events(&mut rec, &single_tap()),
+        vec![GestureType::Tap]
+    );
+}
+
+#[test]
+fn test_instant_tap_never_reports_double_tap() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer()
+        .with_clock(clock.clone())
+        .with_immediate_tap(true);
+    for _ in 0..3 {
+        assert_eq!(
+            process_touch_events(&mut rec, &single_tap()),
+            vec![GestureType::Tap]
+        );
+        assert!(!rec.has_pending_tap());
+        clock.advance(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn test_binds_double_tap_needs_enabled_binding() {
+    let bound = make_gestures(&[("double_tap", "echo double", true)]);
+    assert!(binds_double_tap(&bound, &[]));
+    assert!(!binds_double_tap(&bound, &[GestureType::DoubleTap]));
+    assert!(!binds_double_tap(
+        &make_gestures(&[("double_tap", "echo double", false)]),
+        &[]
+    ));
+    assert!(!binds_double_tap(
+        &make_gestures(&[("tap", "echo tap", true)]),
+        &[]
+    ));
+}
+
 // -- resolve_action -------------------------------------------
 
 #[test]
@@ -343,6 +1257,166 @@ fn test_resolve_action_all_gesture_types() {
     }
 }
 
+// -- resolve_finger_action ------------------------------------
+
+/// Swipe-left with a top-level action plus a three-finger override.
+fn make_finger_gestures() -> HashMap<String, GestureConfig> {
+    let mut g = make_gestures(&[("swipe_left", "echo one", true)]);
+    g.get_mut("swipe_left")
+        .unwrap()
+        .by_fingers
+        .insert(3, "echo three".to_string());
+    g
+}
+
+#[test]
+fn test_resolve_finger_action_picks_matching_count() {
+    let g = make_finger_gestures();
+    assert_eq!(
+        resolve_finger_action(GestureType::SwipeLeft, 3, &g),
+        Some("echo three")
+    );
+}
+
+#[test]
+fn test_resolve_finger_action_falls_back_to_action() {
+    let g = make_finger_gestures();
+    assert_eq!(
+        resolve_finger_action(GestureType::SwipeLeft, 1, &g),
+        Some("echo one")
+    );
+    assert_eq!(
+        resolve_finger_action(GestureType::SwipeLeft, 2, &g),
+        Some("echo one")
+    );
+}
+
+#[test]
+fn test_resolve_finger_action_disabled() {
+    let mut g = make_finger_gestures();
+    g.get_mut("swipe_left").unwrap().enabled = false;
+    assert_eq!(resolve_finger_action(GestureType::SwipeLeft, 3, &g), None);
+}
+
+// -- format_bindings ------------------------------------------
+
+#[test]
+fn test_format_bindings_lists_inherited_and_overridden() {
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(
+        br#"
+[global.thresholds]
+swipe_time_max = 0.9
+swipe_distance_min_pct = 0.15
+angle_tolerance_deg = 30.0
+tap_time_max = 0.2
+long_press_time_min = 0.8
+double_tap_interval = 0.3
+tap_distance_max = 50.0
+double_tap_distance_max = 50.0
+pinch_threshold_pct = 0.1
+
+[global.gestures.swipe_left]
+action = "echo global left"
+enabled = true
+
+[global.gestures.tap]
+action = "echo tap"
+enabled = true
+
+[device.b]
+device_usb_id = "2222:2222"
+enabled = true
+
+[device.b.gestures.tap]
+enabled = false
+
+[device.a]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.a.gestures.swipe_left]
+action = "echo a left"
+
+[device.a.gestures.swipe_left.by_fingers]
+3 = "echo a three"
+"#,
+    )
+    .unwrap();
+    let config = parse_config_file(f.path()).unwrap();
+    let out = format_bindings(&config);
+    let lines: Vec<&str> = out.lines().map(str::trim_end).collect();
+
+    // Devices and gestures are sorted by name.
+    let a = lines.iter().position(|l| *l == "[a]").unwrap();
+    let b = lines.iter().position(|l| *l == "[b]").unwrap();
+    assert!(a < b);
+    let device_a = &lines[a + 1..b];
+    let device_b = &lines[b + 1..];
+    let mut names: Vec<&str> = device_b
+        .iter()
+        .map(|l| l.split_whitespace().next().unwrap())
+        .collect();
+    assert!(names.is_sorted());
+    names.dedup();
+    assert_eq!(names.len(), device_b.len());
+
+    let binding = |lines: &[&str], label: &str| {
+        lines
+            .iter()
+            .find(|l| l.trim_start().starts_with(&format!("{label:<24}")))
+            .map(|l| l.trim_start()[24..].to_string())
+    };
+    assert_eq!(binding(device_a, "swipe_left").unwrap(), "echo a left");
+    assert_eq!(
+        binding(device_a, "swipe_left (3 fingers)").unwrap(),
+        "echo a three"
+    );
+    assert_eq!(binding(device_a, "tap").unwrap(), "echo tap");
+    assert_eq!(binding(device_a, "pinch_in").unwrap(), "[unbound]");
+    assert_eq!(binding(device_b, "swipe_left").unwrap(), "echo global left");
+    assert_eq!(binding(device_b, "tap").unwrap(), "[disabled]");
+}
+
+// -- ReleaseSignal --------------------------------------------
+
+#[test]
+fn test_release_signal_follows_supported_axes() {
+    let timeout = Duration::from_millis(150);
+    let mt_axes: AttributeSet<AbsoluteAxisType> = [
+        AbsoluteAxisType::ABS_MT_POSITION_X,
+        AbsoluteAxisType::ABS_MT_POSITION_Y,
+        AbsoluteAxisType::ABS_MT_TRACKING_ID,
+    ]
+    .into_iter()
+    .collect();
+    let no_id_axes: AttributeSet<AbsoluteAxisType> = [
+        AbsoluteAxisType::ABS_MT_POSITION_X,
+        AbsoluteAxisType::ABS_MT_POSITION_Y,
+    ]
+    .into_iter()
+    .collect();
+    let btn_touch: AttributeSet<Key> = [Key::BTN_TOUCH].into_iter().collect();
+
+    assert_eq!(
+        ReleaseSignal::select(Some(&mt_axes), Some(&btn_touch), false, timeout),
+        ReleaseSignal::TrackingId
+    );
+    assert_eq!(
+        ReleaseSignal::select(Some(&no_id_axes), Some(&btn_touch), false, timeout),
+        ReleaseSignal::BtnTouch
+    );
+    assert_eq!(
+        ReleaseSignal::select(Some(&no_id_axes), None, false, timeout),
+        ReleaseSignal::Timeout(timeout)
+    );
+    // Single-touch panels ignore tracking IDs and release on BTN_TOUCH.
+    assert_eq!(
+        ReleaseSignal::select(Some(&mt_axes), Some(&btn_touch), true, timeout),
+        ReleaseSignal::BtnTouch
+    );
+}
+
 // -- parse_usb_id ---------------------------------------------
 
 #[test]
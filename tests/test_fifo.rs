@@ -0,0 +1,48 @@
+//! Tests for `bodgestr::fifo` - gesture lines written to a named pipe.
+use std::fs::{self, OpenOptions};
+use std::io::Read;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+
+use bodgestr::fifo::{FifoWriter, gesture_line};
+use bodgestr::recognizer::GestureType;
+use nix::fcntl::OFlag;
+
+#[test]
+fn test_gesture_line_is_device_and_gesture() {
+    assert_eq!(gesture_line("kiosk", GestureType::Tap), "kiosk/tap\n");
+}
+
+#[test]
+fn test_lines_dropped_without_reader_and_read_with_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("gestures.fifo");
+    let fifo = FifoWriter::create(&path).unwrap();
+    assert!(fs::metadata(&path).unwrap().file_type().is_fifo());
+
+    // No reader yet: the write is dropped instead of blocking.
+    assert!(!fifo.send("kiosk", GestureType::Tap));
+
+    let mut reader = OpenOptions::new()
+        .read(true)
+        .custom_flags(OFlag::O_NONBLOCK.bits())
+        .open(&path)
+        .unwrap();
+    assert!(fifo.send("kiosk", GestureType::SwipeLeft));
+    assert!(fifo.send("panel-2", GestureType::DoubleTap));
+
+    let mut lines = String::new();
+    reader.read_to_string(&mut lines).ok();
+    assert_eq!(lines, "kiosk/swipe_left\npanel-2/double_tap\n");
+}
+
+#[test]
+fn test_existing_fifo_reused_and_regular_file_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("gestures.fifo");
+    FifoWriter::create(&path).unwrap();
+    assert!(FifoWriter::create(&path).is_ok());
+
+    let file = dir.path().join("plain");
+    fs::write(&file, "").unwrap();
+    assert!(FifoWriter::create(&file).is_err());
+}
@@ -0,0 +1,48 @@
+//! Tests for `bodgestr::focus` - the `only_when_class` decision and the
+//! focus query cache.
+use std::time::{Duration, Instant};
+
+use bodgestr::focus::{FOCUS_CACHE_TTL, FocusCache, focus_allows};
+
+#[test]
+fn test_no_filter_always_allows() {
+    assert!(focus_allows(None, None));
+    assert!(focus_allows(None, Some("xterm")));
+}
+
+#[test]
+fn test_filter_matches_focused_class_ignoring_case() {
+    assert!(focus_allows(Some("firefox"), Some("firefox")));
+    assert!(focus_allows(Some("firefox"), Some("Firefox")));
+    assert!(!focus_allows(Some("firefox"), Some("xterm")));
+}
+
+#[test]
+fn test_filter_rejects_unknown_focus() {
+    assert!(!focus_allows(Some("firefox"), None));
+}
+
+#[test]
+fn test_cache_reuses_result_within_ttl() {
+    let mut cache = FocusCache::default();
+    let now = Instant::now();
+    let mut calls = 0;
+
+    let mut query = |class: &str| {
+        calls += 1;
+        Some(class.to_string())
+    };
+    assert_eq!(
+        cache.focused_class(now, || query("firefox")),
+        Some("firefox")
+    );
+    assert_eq!(
+        cache.focused_class(now + Duration::from_millis(10), || query("xterm")),
+        Some("firefox")
+    );
+    assert_eq!(
+        cache.focused_class(now + FOCUS_CACHE_TTL, || query("xterm")),
+        Some("xterm")
+    );
+    assert_eq!(calls, 2);
+}
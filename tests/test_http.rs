@@ -0,0 +1,50 @@
+//! Tests for `bodgestr::http` - request parsing, SSE event and stats JSON
+//! formatting.
+use bodgestr::http::{MAX_REQUEST_BYTES, request_path, sse_event, stats_json};
+use bodgestr::recognizer::GestureType;
+use bodgestr::stats::GestureStats;
+
+#[test]
+fn test_sse_event_is_one_gesture_event() {
+    assert_eq!(
+        sse_event("kiosk", GestureType::SwipeLeft),
+        "event: gesture\ndata: {\"device\":\"kiosk\",\"gesture\":\"swipe_left\"}\n\n"
+    );
+}
+
+#[test]
+fn test_sse_event_escapes_device_id() {
+    let event = sse_event("panel \"2\"", GestureType::Tap);
+    assert!(event.contains(r#""device":"panel \"2\"""#), "{event}");
+    assert_eq!(event.matches('\n').count(), 3);
+}
+
+#[test]
+fn test_stats_json_maps_gesture_names_to_counts() {
+    let mut stats = GestureStats::default();
+    stats.record(GestureType::Tap);
+    stats.record(GestureType::Tap);
+    stats.record(GestureType::SwipeUp);
+    assert_eq!(stats_json(&stats), r#"{"swipe_up":1,"tap":2}"#);
+    assert_eq!(stats_json(&GestureStats::default()), "{}");
+}
+
+#[test]
+fn test_request_path_reads_get_target_after_headers() {
+    let request = b"GET /stats?pretty=1 HTTP/1.1\r\nHost: kiosk\r\n\r\n";
+    assert_eq!(request_path(&request[..]), Some("/stats".to_string()));
+    assert_eq!(request_path(&b"POST /stats HTTP/1.1\r\n\r\n"[..]), None);
+}
+
+#[test]
+fn test_request_path_rejects_unterminated_and_oversized_requests() {
+    // The client never finished its headers.
+    assert_eq!(request_path(&b"GET /stats HTTP/1.1\r\nHost: kio"[..]), None);
+    assert_eq!(request_path(&b"GET /stats"[..]), None);
+
+    let long_header = format!(
+        "GET /stats HTTP/1.1\r\nX-Pad: {}\r\n\r\n",
+        "a".repeat(MAX_REQUEST_BYTES as usize)
+    );
+    assert_eq!(request_path(long_header.as_bytes()), None);
+}
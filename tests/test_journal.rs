@@ -0,0 +1,39 @@
+//! Tests for `bodgestr::journal` - journal fields and their native encoding.
+use bodgestr::journal::{encode_fields, journal_fields};
+use bodgestr::recognizer::GestureType;
+
+fn field<'a>(fields: &'a [(&str, String)], key: &str) -> &'a str {
+    fields
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.as_str())
+        .unwrap_or_else(|| panic!("missing field {key}"))
+}
+
+#[test]
+fn test_journal_fields_carry_gesture_and_device() {
+    let fields = journal_fields("kiosk", GestureType::SwipeLeft);
+    assert_eq!(field(&fields, "GESTURE"), "swipe_left");
+    assert_eq!(field(&fields, "DEVICE"), "kiosk");
+    assert_eq!(field(&fields, "MESSAGE"), "kiosk: swipe_left");
+    assert_eq!(field(&fields, "PRIORITY"), "6");
+    assert_eq!(field(&fields, "SYSLOG_IDENTIFIER"), "bodgestr");
+}
+
+#[test]
+fn test_encode_fields_writes_key_value_lines() {
+    let fields = [
+        ("GESTURE", "tap".to_string()),
+        ("DEVICE", "kiosk".to_string()),
+    ];
+    assert_eq!(encode_fields(&fields), b"GESTURE=tap\nDEVICE=kiosk\n");
+}
+
+#[test]
+fn test_encode_fields_length_prefixes_multiline_values() {
+    let fields = [("MESSAGE", "a\nb".to_string())];
+    let mut expected = b"MESSAGE\n".to_vec();
+    expected.extend_from_slice(&3u64.to_le_bytes());
+    expected.extend_from_slice(b"a\nb\n");
+    assert_eq!(encode_fields(&fields), expected);
+}
@@ -0,0 +1,58 @@
+//! Tests for `bodgestr::logfile` - size-based rotation of the log file.
+use std::fs;
+use std::path::Path;
+
+use bodgestr::logfile::{LogFile, exceeds_limit, rotated_path};
+
+#[test]
+fn test_rotated_path_appends_number() {
+    assert_eq!(
+        rotated_path(Path::new("/var/log/bodgestr/bodgestr.log"), 2),
+        Path::new("/var/log/bodgestr/bodgestr.log.2")
+    );
+}
+
+#[test]
+fn test_exceeds_limit_only_past_max_bytes() {
+    assert!(!exceeds_limit(90, 10, Some(100)));
+    assert!(exceeds_limit(91, 10, Some(100)));
+    // An oversized line still goes into an empty file.
+    assert!(!exceeds_limit(0, 500, Some(100)));
+    assert!(!exceeds_limit(10_000, 10, None));
+}
+
+#[test]
+fn test_rotation_shifts_files_and_keeps_n() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bodgestr.log");
+    let mut log = LogFile::open(&path, Some(10), 2).unwrap();
+    for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+        log.write_line(line).unwrap();
+    }
+    log.flush().unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+    assert_eq!(
+        fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+        "third\n"
+    );
+    assert_eq!(
+        fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+        "second\n"
+    );
+    assert!(!rotated_path(&path, 3).exists());
+}
+
+#[test]
+fn test_rotation_counts_existing_file_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bodgestr.log");
+    fs::write(&path, "old contents\n").unwrap();
+
+    let mut log = LogFile::open(&path, Some(20), 0).unwrap();
+    log.write_line("new line here\n").unwrap();
+    log.flush().unwrap();
+    // Nothing kept: the old contents are dropped.
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new line here\n");
+    assert!(!rotated_path(&path, 1).exists());
+}
@@ -0,0 +1,1297 @@
+//! Tests for the testable seams in `bodgestr::manager`.
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bodgestr::config::{AppConfig, BodgestrError, ValidatedThresholds, parse_config_file};
+use bodgestr::control::RuntimeBindings;
+use bodgestr::manager::{
+    ActionExecutor, ActionFailures, ActionJob, ActionPacer, ActionQueue, CalibrationSample,
+    CalibrationSamples, EventRateLimit, FoundDevice, GestureManager, Heartbeat, LearnedGestures,
+    RecordingExecutor, TouchEvent, TouchSource, apply_threshold_overrides, classify_event,
+    config_dump, counts_gesture, device_access_hint, dispatch_log_level, enumerated_device_matches,
+    expand_auto_devices, gesture_action, is_record_only, parse_threshold_override,
+    permission_denied_message, process_touch_events_detailed, resolution_scale, run_bound_action,
+    run_feedback, run_gesture_action, run_serialized, scale_px_thresholds, selftest,
+    spawn_with_retries, suggest_thresholds, trajectory_json,
+};
+use bodgestr::recognizer::{GestureDetails, GestureRecognizer, GestureType, MockClock};
+use bodgestr::stats::GestureStats;
+use evdev::{AbsoluteAxisType, AttributeSet, AttributeSetRef, EventType, InputEvent, Key};
+use log::Level;
+
+/// Global thresholds every device in these tests falls back to.
+const ALL_THRESHOLDS: &str = r#"
+[global.thresholds]
+swipe_time_max = 0.9
+swipe_distance_min_pct = 0.15
+angle_tolerance_deg = 30.0
+tap_time_max = 0.2
+long_press_time_min = 0.8
+double_tap_interval = 0.3
+tap_distance_max = 50.0
+double_tap_distance_max = 50.0
+pinch_threshold_pct = 0.1
+"#;
+
+/// Write TOML to a temp file, after [`ALL_THRESHOLDS`].
+fn config_file(toml_content: &str) -> tempfile::NamedTempFile {
+    let mut f = tempfile::NamedTempFile::new().unwrap();
+    f.write_all(format!("{ALL_THRESHOLDS}\n{toml_content}").as_bytes())
+        .unwrap();
+    f
+}
+
+/// Parse TOML with [`ALL_THRESHOLDS`] prepended.
+fn load(toml_content: &str) -> AppConfig {
+    parse_config_file(config_file(toml_content).path()).unwrap()
+}
+
+/// The thresholds of [`ALL_THRESHOLDS`], with every optional one off.
+fn default_thresholds() -> ValidatedThresholds {
+    ValidatedThresholds {
+        swipe_time_max: 0.9,
+        swipe_distance_min_pct: 0.15,
+        angle_tolerance_deg: 30.0,
+        tap_time_max: 0.2,
+        long_press_time_min: 0.8,
+        double_tap_interval: 0.3,
+        tap_distance_max: 50.0,
+        double_tap_distance_max: 50.0,
+        pinch_threshold_pct: 0.1,
+        ..ValidatedThresholds::default()
+    }
+}
+
+// -- start_background -----------------------------------------
+
+/// A manager whose only device points at a node that does not exist.
+fn manager_without_devices() -> (GestureManager, tempfile::NamedTempFile) {
+    let f = config_file(
+        r#"
+[device.missing]
+device_path = "/nonexistent/event99"
+enabled = true
+"#,
+    );
+    (GestureManager::new(f.path()).unwrap(), f)
+}
+
+#[test]
+fn test_start_background_returns_and_joins() {
+    let (mut manager, _file) = manager_without_devices();
+    let (done_tx, done_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let handle = manager.start_background();
+        done_tx.send(()).unwrap();
+        handle.stop();
+        handle.join();
+        done_tx.send(()).unwrap();
+    });
+    // Both the start and the stop/join must come back promptly.
+    done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+}
+
+#[test]
+fn test_stop_and_join_interrupt_an_idle_device_thread() {
+    let (mut manager, _, polls) = manager_with_stub_panels("", &["panel"], Vec::new);
+    let handle = manager.start_background();
+    let started = Instant::now();
+    while polls.load(Ordering::Relaxed) == 0 {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "device loop never ran"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let (done_tx, done_rx) = mpsc::channel();
+    thread::spawn(move || {
+        handle.stop();
+        handle.join();
+        done_tx.send(()).unwrap();
+    });
+    // The loop is waiting on a silent panel and must still notice the stop.
+    done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+}
+
+#[test]
+fn test_manager_handle_stop_clears_running_flag() {
+    let (mut manager, _file) = manager_without_devices();
+    let running = manager.running_flag();
+    let handle = manager.start_background();
+    running.store(true, Ordering::Relaxed);
+    handle.stop();
+    assert!(!running.load(Ordering::Relaxed));
+    handle.join();
+}
+
+// -- ActionExecutor --------------------------------------------
+
+#[test]
+fn test_recording_executor_sees_swipe_action() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+
+[global.gestures.swipe_right]
+action = "xdotool key Right"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+
+    let mut recognizer =
+        GestureRecognizer::new(device.thresholds.clone(), (0.0, 1000.0), (0.0, 1000.0));
+    let recorder = RecordingExecutor::default();
+    let fired = process_touch_events_detailed(
+        &mut recognizer,
+        &[
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(800.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+            TouchEvent::PositionX(100.0),
+            TouchEvent::SynReport,
+            TouchEvent::FingerUp,
+        ],
+    );
+    for details in &fired {
+        run_gesture_action(details, device, &recorder);
+    }
+    assert_eq!(recorder.actions(), ["xdotool key Left"]);
+}
+
+#[test]
+fn test_bind_command_overrides_configured_action() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+    let bindings = RuntimeBindings::new(["d1".to_string()]);
+    let recorder = RecordingExecutor::default();
+    let swipe = details(GestureType::SwipeLeft);
+
+    run_bound_action("d1", &swipe, device, Some(&bindings), &recorder);
+    bindings
+        .apply("bind d1 swipe_left notify-send 'went left'")
+        .unwrap();
+    run_bound_action("d1", &swipe, device, Some(&bindings), &recorder);
+    assert!(bindings.apply("bind d2 swipe_left true").is_err());
+    assert!(bindings.apply("bind d1 swipe_sideways true").is_err());
+
+    assert_eq!(
+        recorder.actions(),
+        ["xdotool key Left", "notify-send 'went left'"]
+    );
+}
+
+#[test]
+fn test_unbound_gesture_runs_no_action() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = false
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+
+    let mut recognizer =
+        GestureRecognizer::new(device.thresholds.clone(), (0.0, 1000.0), (0.0, 1000.0));
+    let recorder = RecordingExecutor::default();
+    let fired = process_touch_events_detailed(
+        &mut recognizer,
+        &[
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(800.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+            TouchEvent::PositionX(100.0),
+            TouchEvent::SynReport,
+            TouchEvent::FingerUp,
+        ],
+    );
+    // Recognized, but disabled: the no-action path.
+    assert_eq!(fired.len(), 1);
+    assert_eq!(run_gesture_action(&fired[0], device, &recorder), None);
+    assert!(recorder.actions().is_empty());
+}
+
+#[test]
+fn test_record_only_gesture_is_counted_but_runs_nothing() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+enabled = true
+
+[global.gestures.swipe_right]
+action = "xdotool key Right"
+enabled = false
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+    let recorder = RecordingExecutor::default();
+    let swipe = details(GestureType::SwipeLeft);
+
+    assert!(is_record_only(GestureType::SwipeLeft, &device.gestures));
+    assert_eq!(
+        run_bound_action("d1", &swipe, device, None, &recorder),
+        None
+    );
+    assert!(recorder.actions().is_empty());
+    assert!(counts_gesture("d1", GestureType::SwipeLeft, device, None));
+
+    // Disabled and unlisted gestures are unbound: neither record-only nor counted.
+    for unbound in [GestureType::SwipeRight, GestureType::Tap] {
+        assert!(!is_record_only(unbound, &device.gestures));
+        assert!(!counts_gesture("d1", unbound, device, None));
+    }
+    // An action bound at runtime counts even without a configured binding.
+    let bindings = RuntimeBindings::new(["d1".to_string()]);
+    bindings.apply("bind d1 tap true").unwrap();
+    assert!(counts_gesture(
+        "d1",
+        GestureType::Tap,
+        device,
+        Some(&bindings)
+    ));
+}
+
+#[test]
+fn test_trajectory_json_matches_recorded_swipe() {
+    let clock = MockClock::new();
+    let th = default_thresholds();
+    let swipe = |recognizer: &mut GestureRecognizer| {
+        let mut fired = process_touch_events_detailed(
+            recognizer,
+            &[
+                TouchEvent::TrackingId(3),
+                TouchEvent::PositionX(800.0),
+                TouchEvent::PositionY(500.0),
+                TouchEvent::SynReport,
+            ],
+        );
+        for x in [600.0, 400.0, 100.0] {
+            clock.advance(Duration::from_millis(50));
+            fired.extend(process_touch_events_detailed(
+                recognizer,
+                &[TouchEvent::PositionX(x), TouchEvent::SynReport],
+            ));
+        }
+        fired.extend(process_touch_events_detailed(
+            recognizer,
+            &[TouchEvent::FingerUp],
+        ));
+        fired
+    };
+
+    let mut recognizer = GestureRecognizer::new(th.clone(), (0.0, 1000.0), (0.0, 1000.0))
+        .with_clock(clock.clone())
+        .with_trajectory(true);
+    let fired = swipe(&mut recognizer);
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].gesture, GestureType::SwipeLeft);
+    let points = fired[0].trajectory.as_deref().unwrap();
+    let json: serde_json::Value = serde_json::from_str(&trajectory_json(points)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([
+            {"x": 800.0, "y": 500.0, "t_ms": 0.0, "id": 3},
+            {"x": 600.0, "y": 500.0, "t_ms": 50.0, "id": 3},
+            {"x": 400.0, "y": 500.0, "t_ms": 100.0, "id": 3},
+            {"x": 100.0, "y": 500.0, "t_ms": 150.0, "id": 3},
+        ])
+    );
+    assert_eq!(trajectory_json(&[]), "[]");
+
+    // Without pass_trajectory nothing is kept.
+    let mut recognizer =
+        GestureRecognizer::new(th, (0.0, 1000.0), (0.0, 1000.0)).with_clock(clock.clone());
+    assert_eq!(swipe(&mut recognizer)[0].trajectory, None);
+}
+
+#[test]
+fn test_feedback_command_runs_for_unbound_gesture() {
+    let config = load(
+        r#"
+[global]
+feedback_command = "aplay -q /usr/share/sounds/click.wav"
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+    let command = config.feedback_command.as_deref().unwrap();
+
+    let mut recognizer =
+        GestureRecognizer::new(device.thresholds.clone(), (0.0, 1000.0), (0.0, 1000.0));
+    let recorder = RecordingExecutor::default();
+    let fired = process_touch_events_detailed(
+        &mut recognizer,
+        &[
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(800.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+            TouchEvent::PositionX(100.0),
+            TouchEvent::SynReport,
+            TouchEvent::FingerUp,
+        ],
+    );
+    assert_eq!(fired.len(), 1);
+    run_feedback(command, fired[0].gesture, &recorder);
+    assert_eq!(run_gesture_action(&fired[0], device, &recorder), None);
+    assert_eq!(
+        recorder.actions(),
+        ["BODGESTR_GESTURE=swipe_left aplay -q /usr/share/sounds/click.wav"]
+    );
+}
+
+#[test]
+fn test_gesture_action_looks_up_device_and_gesture() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+
+[device.d2]
+device_usb_id = "2222:2222"
+enabled = true
+
+[device.d2.gestures.swipe_left]
+action = "xdotool key ctrl+Left"
+enabled = true
+"#,
+    );
+
+    let action = |device, gesture| gesture_action(&config, device, gesture).map(|(_, a)| a);
+    assert_eq!(action("d1", "swipe_left"), Ok("xdotool key Left"));
+    assert_eq!(action("d2", "swipe_left"), Ok("xdotool key ctrl+Left"));
+    assert_eq!(
+        action("2222:2222", "swipe_left"),
+        Ok("xdotool key ctrl+Left")
+    );
+    assert!(action("d1", "swipe_up").unwrap_err().contains("not bound"));
+    assert!(
+        action("d1", "swipe_sideways")
+            .unwrap_err()
+            .contains("Unknown gesture")
+    );
+    assert!(
+        action("d3", "swipe_left")
+            .unwrap_err()
+            .contains("No device")
+    );
+}
+
+#[test]
+fn test_enumerated_device_matches_phys_and_usb_id() {
+    let config = load(
+        r#"
+[device.left]
+device_usb_id = "1111:2222"
+device_phys = "usb-0000:00:14.0-3/input0"
+enabled = true
+
+[device.right]
+device_phys = "usb-0000:00:14.0-4/input0"
+enabled = true
+
+[device.any]
+device_usb_id = "1111:2222"
+enabled = true
+"#,
+    );
+    let port3 = Some("usb-0000:00:14.0-3/input0");
+    let port4 = Some("usb-0000:00:14.0-4/input0");
+
+    let left = &config.devices["left"];
+    assert!(enumerated_device_matches(left, (0x1111, 0x2222), port3));
+    assert!(!enumerated_device_matches(left, (0x1111, 0x2222), port4));
+    assert!(!enumerated_device_matches(left, (0x1111, 0x3333), port3));
+    assert!(!enumerated_device_matches(left, (0x1111, 0x2222), None));
+
+    // phys alone matches any USB ID on that port.
+    let right = &config.devices["right"];
+    assert!(enumerated_device_matches(right, (0x0000, 0x0000), port4));
+    assert!(!enumerated_device_matches(right, (0x0000, 0x0000), port3));
+
+    // Without device_phys the port does not matter.
+    let any = &config.devices["any"];
+    assert!(enumerated_device_matches(any, (0x1111, 0x2222), port4));
+    assert!(enumerated_device_matches(any, (0x1111, 0x2222), None));
+}
+
+#[test]
+fn test_px_thresholds_scale_to_device_resolution() {
+    // Authored for 1920x1080, running on 1024x576: everything shrinks.
+    let factor = resolution_scale([1920.0, 1080.0], (0.0, 1024.0), (0.0, 576.0));
+    assert!((factor - 1024.0 / 1920.0).abs() < 1e-9);
+    // Same panel rotated: the diagonal is unchanged.
+    let rotated = resolution_scale([1920.0, 1080.0], (0.0, 1080.0), (0.0, 1920.0));
+    assert!((rotated - 1.0).abs() < 1e-9);
+
+    let thresholds = ValidatedThresholds {
+        tap_distance_max: 48.0,
+        double_tap_distance_max: 96.0,
+        pinch_threshold_min_px: Some(24.0),
+        ..default_thresholds()
+    };
+    let scaled = scale_px_thresholds(&thresholds, 0.5);
+    assert_eq!(scaled.tap_distance_max, 24.0);
+    assert_eq!(scaled.double_tap_distance_max, 48.0);
+    assert_eq!(scaled.pinch_threshold_min_px, Some(12.0));
+    assert_eq!(scaled.swipe_distance_min_pct, 0.15);
+}
+
+#[test]
+fn test_threshold_overrides_parse_and_apply() {
+    assert_eq!(
+        parse_threshold_override("swipe_time_max=1.2"),
+        Ok(("swipe_time_max".to_string(), 1.2))
+    );
+    assert!(
+        parse_threshold_override("swipe_time_maxx=1.2")
+            .unwrap_err()
+            .contains("unknown threshold 'swipe_time_maxx'")
+    );
+    assert!(parse_threshold_override("swipe_time_max").is_err());
+    assert!(parse_threshold_override("swipe_time_max=fast").is_err());
+    assert!(parse_threshold_override("swipe_time_max=NaN").is_err());
+
+    let overrides: BTreeMap<String, f64> = [
+        "swipe_time_max=1.2",
+        "tap_distance_max=80",
+        "circle_turn_min=0.9",
+    ]
+    .into_iter()
+    .map(|arg| parse_threshold_override(arg).unwrap())
+    .collect();
+    let mut thresholds = default_thresholds();
+    apply_threshold_overrides(&mut thresholds, &overrides);
+    assert_eq!(thresholds.swipe_time_max, 1.2);
+    assert_eq!(thresholds.tap_distance_max, 80.0);
+    // Optional thresholds are switched on by an override.
+    assert_eq!(thresholds.circle_turn_min, Some(0.9));
+    assert_eq!(thresholds.angle_tolerance_deg, 30.0);
+}
+
+#[test]
+fn test_auto_enable_all_expands_to_unlisted_devices() {
+    let config = load(
+        r#"
+[global]
+auto_enable_all = true
+
+[global.gestures.swipe_left]
+action = "echo left"
+enabled = true
+
+[device.kiosk]
+device_usb_id = "1234:5678"
+enabled = true
+"#,
+    );
+    let template = config.auto_device.as_ref().unwrap();
+    assert!(!config.devices.contains_key("*"));
+
+    let found = |node: &str, usb_id: &str| FoundDevice {
+        path: format!("/dev/input/{node}").into(),
+        usb_id: usb_id.to_string(),
+        phys: None,
+        multi_touch: true,
+    };
+    let legacy = FoundDevice {
+        multi_touch: false,
+        ..found("event7", "0eef:0001")
+    };
+    let devices = expand_auto_devices(
+        &config.devices,
+        template,
+        &[
+            found("event3", "1234:5678"),
+            found("event5", "abcd:0001"),
+            legacy.clone(),
+        ],
+    );
+
+    // The listed panel keeps its own entry; the other multi-touch one is
+    // added, the ABS_X/ABS_Y-only panel is not.
+    assert_eq!(devices.len(), 2);
+    assert_eq!(devices["kiosk"].device_usb_id, "1234:5678");
+    let auto = &devices["event5"];
+    assert_eq!(
+        auto.device_path.as_deref(),
+        Some(std::path::Path::new("/dev/input/event5"))
+    );
+    assert_eq!(
+        auto.gestures["swipe_left"].action.as_deref(),
+        Some("echo left")
+    );
+    assert_eq!(auto.thresholds.swipe_time_max, 0.9);
+
+    // A single_touch template picks up the legacy panel too.
+    let config = load(
+        r#"
+[device."*"]
+single_touch = true
+enabled = true
+"#,
+    );
+    let template = config.auto_device.as_ref().unwrap();
+    let devices = expand_auto_devices(&config.devices, template, &[legacy]);
+    assert!(devices["event7"].single_touch);
+}
+
+#[test]
+fn test_selftest_fails_degenerate_thresholds() {
+    let sane = default_thresholds();
+    assert!(selftest(&sane));
+
+    let degenerate = ValidatedThresholds {
+        swipe_distance_min_pct: 1.5,
+        ..sane
+    };
+    assert!(!selftest(&degenerate));
+}
+
+#[test]
+fn test_action_spawn_error_wraps_io_error() {
+    let e = BodgestrError::ActionSpawnFailed {
+        action: "xdotool key Left".to_string(),
+        source: io::Error::new(ErrorKind::NotFound, "No such file or directory"),
+    };
+    assert_eq!(
+        e.to_string(),
+        "Failed to execute action 'xdotool key Left': No such file or directory"
+    );
+    let source = std::error::Error::source(&e)
+        .and_then(|s| s.downcast_ref::<io::Error>())
+        .unwrap();
+    assert_eq!(source.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn test_action_failures_are_counted_and_rate_limited() {
+    let failures = ActionFailures::default();
+    let t0 = Instant::now();
+    assert_eq!(failures.record("xdotool key Left", t0), Some(1));
+    assert_eq!(
+        failures.record("xdotool key Left", t0 + Duration::from_secs(1)),
+        None
+    );
+    assert_eq!(
+        failures.record("xdotool key Left", t0 + Duration::from_secs(2)),
+        None
+    );
+    // Another action is reported on its own schedule.
+    assert_eq!(failures.record("notify-send hi", t0), Some(1));
+    // After the interval, the next report covers the suppressed failures.
+    let later = t0 + ActionFailures::REPORT_INTERVAL;
+    assert_eq!(failures.record("xdotool key Left", later), Some(3));
+    assert_eq!(failures.count("xdotool key Left"), 4);
+    assert_eq!(failures.count("notify-send hi"), 1);
+    assert_eq!(failures.count("true"), 0);
+}
+
+#[test]
+fn test_event_rate_limit_drops_events_above_rate() {
+    let t0 = Instant::now();
+    let mut limit = EventRateLimit::new(100, t0);
+    // A full second's burst passes; then frames that do not fit are dropped whole.
+    assert!(limit.admit(80, t0));
+    assert!(!limit.admit(50, t0));
+    assert!(limit.admit(20, t0));
+    assert!(!limit.admit(1, t0));
+    assert_eq!(limit.report(t0), Some(51));
+    // 100 ms refills 10 events.
+    let later = t0 + Duration::from_millis(100);
+    assert!(!limit.admit(30, later));
+    assert!(limit.admit(10, later));
+    // Warnings are rate-limited; the next one covers everything dropped since.
+    assert_eq!(limit.report(later), None);
+    let much_later = t0 + EventRateLimit::REPORT_INTERVAL;
+    assert!(limit.admit(100, much_later));
+    assert_eq!(limit.report(much_later), Some(30));
+    assert_eq!(
+        limit.report(much_later + EventRateLimit::REPORT_INTERVAL),
+        None
+    );
+}
+
+#[test]
+fn test_event_rate_limit_keeps_releases_of_dropped_frames() {
+    let t0 = Instant::now();
+    let mut limit = EventRateLimit::new(4, t0);
+    // Touch-down frame, a move frame, then the release frame.
+    let mut events = tap_events();
+    events.splice(
+        4..4,
+        [
+            InputEvent::new(
+                EventType::ABSOLUTE,
+                AbsoluteAxisType::ABS_MT_POSITION_X.0,
+                600,
+            ),
+            InputEvent::new(EventType::SYNCHRONIZATION, 0, 0),
+        ],
+    );
+
+    limit.admit_frames(&mut events, t0);
+    let touch: Vec<_> = events.iter().filter_map(classify_event).collect();
+    assert_eq!(
+        touch,
+        [
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(500.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+            TouchEvent::FingerUp,
+            TouchEvent::SynReport,
+        ]
+    );
+    // Only the move frame is gone.
+    assert_eq!(limit.report(t0), Some(2));
+}
+
+#[test]
+fn test_action_queue_is_an_executor() {
+    let (done_tx, done_rx) = mpsc::channel();
+    let queue = ActionQueue::start(4, move |job| done_tx.send(job).unwrap());
+    queue.execute("echo hi", 2, false);
+    assert_eq!(
+        done_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        ActionJob {
+            action: "echo hi".to_string(),
+            retries: 2,
+            input: None,
+        }
+    );
+}
+
+// -- Device access hints --------------------------------------
+
+#[test]
+fn test_permission_denied_suggests_input_group() {
+    let hint = device_access_hint(ErrorKind::PermissionDenied).unwrap();
+    assert!(hint.contains("'input' group"));
+    assert!(hint.contains("root"));
+}
+
+#[test]
+fn test_other_open_errors_have_no_hint() {
+    assert_eq!(device_access_hint(ErrorKind::NotFound), None);
+    assert_eq!(device_access_hint(ErrorKind::Other), None);
+}
+
+#[test]
+fn test_permission_denied_message_only_when_nodes_denied() {
+    assert_eq!(permission_denied_message(0), None);
+    let message = permission_denied_message(3).unwrap();
+    assert!(message.starts_with("3 input device(s)"));
+    assert!(message.contains("'input' group"));
+}
+
+// -- Watchdog heartbeat ---------------------------------------
+
+#[test]
+fn test_heartbeat_stale_after_timeout() {
+    let timeout = Duration::from_secs(30);
+    let t0 = Instant::now();
+    let heartbeat = Heartbeat::default();
+    heartbeat.beat(t0);
+
+    assert!(!heartbeat.is_stale(t0 + Duration::from_secs(10), timeout));
+    assert!(!heartbeat.is_stale(t0 + timeout, timeout));
+    assert!(heartbeat.is_stale(t0 + Duration::from_secs(31), timeout));
+
+    heartbeat.beat(t0 + Duration::from_secs(31));
+    assert!(!heartbeat.is_stale(t0 + Duration::from_secs(40), timeout));
+}
+
+#[test]
+fn test_heartbeat_never_beaten_is_not_stale() {
+    let heartbeat = Heartbeat::default();
+    let later = Instant::now() + Duration::from_secs(3600);
+    assert!(!heartbeat.is_stale(later, Duration::from_secs(1)));
+}
+
+#[test]
+fn test_heartbeat_retire() {
+    let heartbeat = Heartbeat::default();
+    assert!(!heartbeat.is_retired());
+    heartbeat.retire();
+    assert!(heartbeat.is_retired());
+}
+
+// -- Device loop ----------------------------------------------
+
+/// A panel that has `pending` events ready, then stays idle.  Reading it
+/// while idle blocks like a hung driver would.
+struct StubPanel {
+    axes: AttributeSet<AbsoluteAxisType>,
+    pending: Vec<InputEvent>,
+    /// How often the loop polled or read the panel.
+    polls: Arc<AtomicUsize>,
+}
+
+impl StubPanel {
+    fn new(pending: Vec<InputEvent>, polls: Arc<AtomicUsize>) -> Self {
+        let mut axes = AttributeSet::new();
+        for axis in [
+            AbsoluteAxisType::ABS_MT_POSITION_X,
+            AbsoluteAxisType::ABS_MT_POSITION_Y,
+            AbsoluteAxisType::ABS_MT_TRACKING_ID,
+        ] {
+            axes.insert(axis);
+        }
+        Self {
+            axes,
+            pending,
+            polls,
+        }
+    }
+}
+
+impl TouchSource for StubPanel {
+    fn axis_ranges(&self, _single_touch: bool) -> io::Result<((f64, f64), (f64, f64))> {
+        Ok(((0.0, 1000.0), (0.0, 1000.0)))
+    }
+
+    fn supported_absolute_axes(&self) -> Option<&AttributeSetRef<AbsoluteAxisType>> {
+        Some(&self.axes)
+    }
+
+    fn supported_keys(&self) -> Option<&AttributeSetRef<Key>> {
+        None
+    }
+
+    fn wait_readable(&self, timeout: Duration) -> bool {
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        if self.pending.is_empty() {
+            thread::sleep(timeout);
+        }
+        !self.pending.is_empty()
+    }
+
+    fn fetch_events(&mut self, events: &mut Vec<InputEvent>) -> io::Result<()> {
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        if self.pending.is_empty() {
+            thread::sleep(Duration::from_secs(10));
+        }
+        events.append(&mut self.pending);
+        Ok(())
+    }
+}
+
+/// One tap at the panel's centre, as raw evdev events.
+fn tap_events() -> Vec<InputEvent> {
+    let abs = |axis: AbsoluteAxisType, value| InputEvent::new(EventType::ABSOLUTE, axis.0, value);
+    let syn = InputEvent::new(EventType::SYNCHRONIZATION, 0, 0);
+    vec![
+        abs(AbsoluteAxisType::ABS_MT_TRACKING_ID, 0),
+        abs(AbsoluteAxisType::ABS_MT_POSITION_X, 500),
+        abs(AbsoluteAxisType::ABS_MT_POSITION_Y, 500),
+        syn,
+        abs(AbsoluteAxisType::ABS_MT_TRACKING_ID, -1),
+        syn,
+    ]
+}
+
+/// A manager reading one stub panel per name in `panels`, counting how
+/// often one is opened.  `global` is appended under `[global]`.
+///
+/// `/dev/null` stands in for the device node so the watchdog sees it as present.
+fn manager_with_stub_panels(
+    global: &str,
+    panels: &[&str],
+    pending: fn() -> Vec<InputEvent>,
+) -> (GestureManager, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+    let devices: String = panels
+        .iter()
+        .map(|id| format!("[device.{id}]\ndevice_path = \"/dev/null\"\nenabled = true\n\n"))
+        .collect();
+    let f = config_file(&format!("[global]\n{global}\n\n{devices}"));
+    let (opens, polls) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let (opened, polled) = (Arc::clone(&opens), Arc::clone(&polls));
+    let manager = GestureManager::new(f.path())
+        .unwrap()
+        .with_device_opener(move |_, _| {
+            opened.fetch_add(1, Ordering::Relaxed);
+            let panel = StubPanel::new(pending(), Arc::clone(&polled));
+            Some(Box::new(panel) as Box<dyn TouchSource>)
+        });
+    (manager, opens, polls)
+}
+
+#[test]
+fn test_idle_panel_after_activity_is_not_restarted() {
+    let (mut manager, opens, _) =
+        manager_with_stub_panels("watchdog_timeout_secs = 0.2", &["panel"], tap_events);
+    let handle = manager.start_background();
+    // Five watchdog timeouts of silence after the tap.
+    thread::sleep(Duration::from_secs(1));
+    handle.stop();
+    handle.join();
+    assert_eq!(opens.load(Ordering::Relaxed), 1);
+}
+
+// -- spawn_with_retries ---------------------------------------
+
+#[test]
+fn test_spawn_succeeds_first_attempt() {
+    let mut calls = 0;
+    let result = spawn_with_retries(3, Duration::ZERO, || {
+        calls += 1;
+        Ok::<_, String>(calls)
+    });
+    assert_eq!(result, Ok(1));
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn test_spawn_retries_after_first_failure() {
+    let mut calls = 0;
+    let result = spawn_with_retries(2, Duration::ZERO, || {
+        calls += 1;
+        if calls == 1 {
+            Err("fork failed")
+        } else {
+            Ok(calls)
+        }
+    });
+    assert_eq!(result, Ok(2));
+    assert_eq!(calls, 2);
+}
+
+#[test]
+fn test_spawn_gives_up_after_retries_exhausted() {
+    let mut calls = 0;
+    let result: Result<(), _> = spawn_with_retries(2, Duration::ZERO, || {
+        calls += 1;
+        Err(format!("attempt {calls}"))
+    });
+    assert_eq!(result, Err("attempt 3".to_string()));
+    assert_eq!(calls, 3);
+}
+
+#[test]
+fn test_spawn_no_retries_by_default() {
+    let mut calls = 0;
+    let result: Result<(), _> = spawn_with_retries(0, Duration::ZERO, || {
+        calls += 1;
+        Err("fork failed")
+    });
+    assert!(result.is_err());
+    assert_eq!(calls, 1);
+}
+
+// -- ActionQueue ----------------------------------------------
+
+fn job(action: &str) -> ActionJob {
+    ActionJob {
+        action: action.to_string(),
+        retries: 0,
+        input: None,
+    }
+}
+
+#[test]
+fn test_action_queue_runs_jobs_in_order() {
+    let (done_tx, done_rx) = mpsc::channel();
+    let queue = ActionQueue::start(8, move |job| done_tx.send(job.action).unwrap());
+    for action in ["a", "b", "c", "d"] {
+        assert!(queue.submit(job(action)));
+    }
+    let ran: Vec<String> = (0..4)
+        .map(|_| done_rx.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    assert_eq!(ran, ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn test_action_queue_drops_when_full() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let (done_tx, done_rx) = mpsc::channel();
+    let queue = ActionQueue::start(1, move |job| {
+        started_tx.send(()).unwrap();
+        release_rx.recv().unwrap();
+        done_tx.send(job.action).unwrap();
+    });
+
+    // The worker holds "busy"; one more fits in the queue, the next is dropped.
+    assert!(queue.submit(job("busy")));
+    started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(queue.submit(job("queued")));
+    assert!(!queue.submit(job("dropped")));
+
+    release_tx.send(()).unwrap();
+    release_tx.send(()).unwrap();
+    let ran: Vec<String> = (0..2)
+        .map(|_| done_rx.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    assert_eq!(ran, ["busy", "queued"]);
+    assert!(done_rx.recv_timeout(Duration::from_millis(50)).is_err());
+}
+
+// -- ActionPacer -----------------------------------------------
+
+/// Sends each action with the time it started.
+struct TimedExecutor(Mutex<mpsc::Sender<(String, Instant)>>);
+
+impl ActionExecutor for TimedExecutor {
+    fn execute(&self, action: &str, _retries: u32, _wait: bool) {
+        let tx = self.0.lock().unwrap();
+        tx.send((action.to_string(), Instant::now())).unwrap();
+    }
+}
+
+#[test]
+fn test_post_action_delay_spaces_sequential_actions() {
+    let config = load(
+        r#"
+[global]
+post_action_delay_ms = 150
+
+[global.gestures.swipe_left]
+action = "wmctrl -s 0"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+    let delay = device.post_action_delay(GestureType::SwipeLeft);
+    assert_eq!(delay, Duration::from_millis(150));
+
+    let (tx, rx) = mpsc::channel();
+    let pacer = ActionPacer::start("d1", Arc::new(TimedExecutor(Mutex::new(tx))));
+    let swipe = details(GestureType::SwipeLeft);
+    // Dispatch returns at once; only the second action's start is held back.
+    let dispatched = Instant::now();
+    run_gesture_action(&swipe, device, &pacer.with_delay(delay));
+    run_gesture_action(&swipe, device, &pacer.with_delay(delay));
+    assert!(dispatched.elapsed() < delay);
+
+    let (first, first_at) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let (second, second_at) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(
+        (first.as_str(), second.as_str()),
+        ("wmctrl -s 0", "wmctrl -s 0")
+    );
+    assert!(second_at.duration_since(first_at) >= delay);
+}
+
+// -- run_serialized -------------------------------------------
+
+/// Run `f` from four threads at once and return the peak number of
+/// overlapping calls.
+fn peak_concurrency(lock: Option<&Mutex<()>>) -> usize {
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                run_serialized(lock, || {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                });
+            });
+        }
+    });
+    peak.into_inner()
+}
+
+#[test]
+fn test_run_serialized_one_at_a_time_with_lock() {
+    let lock = Mutex::new(());
+    assert_eq!(peak_concurrency(Some(&lock)), 1);
+}
+
+#[test]
+fn test_run_serialized_without_lock_runs_directly() {
+    assert_eq!(run_serialized(None, || 42), 42);
+}
+
+#[test]
+fn test_run_serialized_waits_for_held_lock() {
+    let lock = Mutex::new(());
+    let ran = AtomicUsize::new(0);
+    let guard = lock.lock().unwrap();
+    thread::scope(|s| {
+        s.spawn(|| run_serialized(Some(&lock), || ran.fetch_add(1, Ordering::SeqCst)));
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(ran.load(Ordering::SeqCst), 0);
+        drop(guard);
+    });
+    assert_eq!(ran.into_inner(), 1);
+}
+
+/// Takes a while to spawn each action, recording the peak number of spawns
+/// in progress at once.
+#[derive(Clone, Default)]
+struct SlowSpawner {
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+    spawned: Arc<AtomicUsize>,
+}
+
+impl ActionExecutor for SlowSpawner {
+    fn execute(&self, _action: &str, _retries: u32, _wait: bool) {
+        let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(100));
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        self.spawned.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_serialize_actions_spawns_one_at_a_time_across_devices() {
+    let (manager, _, _) = manager_with_stub_panels(
+        "serialize_actions = true\n\n[global.gestures.tap]\naction = \"wmctrl -s 1\"\nenabled = true",
+        &["left", "right"],
+        tap_events,
+    );
+    let spawner = SlowSpawner::default();
+    let mut manager = manager.with_executor(spawner.clone());
+    // Both panels report a tap at once.
+    let handle = manager.start_background();
+    let started = Instant::now();
+    while spawner.spawned.load(Ordering::SeqCst) < 2 {
+        assert!(started.elapsed() < Duration::from_secs(5), "taps never ran");
+        thread::sleep(Duration::from_millis(10));
+    }
+    handle.stop();
+    handle.join();
+    assert_eq!(spawner.peak.load(Ordering::SeqCst), 1);
+}
+
+// -- suggest_thresholds ---------------------------------------
+
+fn sample(distance: f64, duration: f64) -> CalibrationSample {
+    CalibrationSample { distance, duration }
+}
+
+fn recorded_samples() -> CalibrationSamples {
+    CalibrationSamples {
+        taps: vec![sample(4.0, 0.08), sample(10.0, 0.12), sample(6.0, 0.10)],
+        swipes: vec![sample(0.40, 0.25), sample(0.30, 0.40), sample(0.50, 0.30)],
+        long_presses: vec![sample(3.0, 1.10), sample(5.0, 0.90)],
+    }
+}
+
+#[test]
+fn test_suggest_thresholds_from_samples() {
+    let s = suggest_thresholds(&recorded_samples()).unwrap();
+    assert!((s.swipe_time_max - 0.60).abs() < 1e-9);
+    assert!((s.swipe_distance_min_pct - 0.15).abs() < 1e-9);
+    assert!((s.tap_time_max - 0.18).abs() < 1e-9);
+    assert!((s.tap_distance_max - 20.0).abs() < 1e-9);
+    assert!((s.long_press_time_min - 0.51).abs() < 1e-9);
+}
+
+#[test]
+fn test_suggest_tap_time_capped_by_long_press() {
+    let mut samples = recorded_samples();
+    samples.long_presses = vec![sample(2.0, 0.20)];
+    let s = suggest_thresholds(&samples).unwrap();
+    assert!((s.long_press_time_min - 0.16).abs() < 1e-9);
+    assert!((s.tap_time_max - 0.16).abs() < 1e-9);
+}
+
+#[test]
+fn test_suggest_thresholds_needs_every_step() {
+    let mut samples = recorded_samples();
+    samples.swipes.clear();
+    assert!(suggest_thresholds(&samples).is_none());
+}
+
+#[test]
+fn test_suggested_thresholds_render_as_toml() {
+    let toml = suggest_thresholds(&recorded_samples())
+        .unwrap()
+        .to_toml("kiosk");
+    let parsed: toml::Table = toml.parse().unwrap();
+    let th = &parsed["device"]["kiosk"]["thresholds"];
+    assert_eq!(th["swipe_time_max"].as_float(), Some(0.6));
+    assert_eq!(th["tap_distance_max"].as_float(), Some(20.0));
+}
+
+#[test]
+fn test_gesture_with_log_false_is_not_logged() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+
+[global.gestures.pinch_out_repeat]
+action = "xdotool key ctrl+plus"
+log = false
+enabled = true
+
+[global.gestures.circle_clockwise]
+action = "pactl set-sink-volume @DEFAULT_SINK@ +5%"
+log_level = "debug"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+
+    assert_eq!(
+        dispatch_log_level(GestureType::SwipeLeft, device),
+        Some(Level::Info)
+    );
+    assert_eq!(
+        dispatch_log_level(GestureType::PinchOutRepeat, device),
+        None
+    );
+    assert_eq!(
+        dispatch_log_level(GestureType::CircleClockwise, device),
+        Some(Level::Debug)
+    );
+    // Unbound gestures keep the default.
+    assert_eq!(
+        dispatch_log_level(GestureType::Tap, device),
+        Some(Level::Info)
+    );
+}
+
+// -- Config dump ----------------------------------------------
+
+#[test]
+fn test_config_dump_lists_devices_thresholds_bindings_and_counts() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+
+[global.gestures.tap]
+action = "xdotool click 1"
+enabled = false
+
+[device.kiosk]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let mut stats = GestureStats::default();
+    stats.record(GestureType::SwipeLeft);
+    stats.record(GestureType::SwipeLeft);
+
+    let dump = config_dump(&config, Some(&stats));
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(lines[0], "1 active device(s)");
+    assert_eq!(lines[1], "device kiosk (USB ID 1111:1111)");
+    assert!(lines[2].contains("swipe_time_max=0.9"), "{}", lines[2]);
+    assert!(!lines[2].contains("null"), "{}", lines[2]);
+    assert_eq!(lines[3], "  swipe_left -> xdotool key Left");
+    assert_eq!(lines[4], "gesture counts: swipe_left=2");
+    assert_eq!(lines.len(), 5);
+}
+
+// -- Learn mode -----------------------------------------------
+
+fn details(gesture: GestureType) -> GestureDetails {
+    GestureDetails {
+        gesture,
+        angle_deg: 0.0,
+        distance: 0.0,
+        duration: 0.1,
+        finger_count: 1,
+        tap_count: 1,
+        pinch: None,
+        trajectory: None,
+    }
+}
+
+#[test]
+fn test_learn_stubs_unbound_gestures_once_and_tallies() {
+    let config = load(
+        r#"
+[global.gestures.swipe_left]
+action = "xdotool key Left"
+enabled = true
+
+[device.d1]
+device_usb_id = "1111:1111"
+enabled = true
+"#,
+    );
+    let device = &config.devices["d1"];
+
+    let mut learned = LearnedGestures::default();
+    let stubs: Vec<_> = [
+        GestureType::SwipeLeft,
+        GestureType::SwipeUp,
+        GestureType::Tap,
+        GestureType::SwipeUp,
+        GestureType::SwipeLeft,
+        GestureType::SwipeUp,
+    ]
+    .into_iter()
+    .filter_map(|gesture| learned.record("d1", &details(gesture), device))
+    .collect();
+
+    // One stub per unbound gesture, in order of first occurrence.
+    assert_eq!(stubs.len(), 2);
+    let parsed: toml::Table = stubs[0].parse().unwrap();
+    let stub = &parsed["device"]["d1"]["gestures"]["swipe_up"];
+    assert_eq!(stub["enabled"].as_bool(), Some(true));
+    assert!(stub["action"].is_str());
+    assert!(stubs[1].starts_with("[device.d1.gestures.tap]"));
+
+    assert_eq!(learned.count("d1", GestureType::SwipeUp), 3);
+    assert_eq!(learned.count("d1", GestureType::Tap), 1);
+    assert_eq!(learned.count("d1", GestureType::SwipeLeft), 0);
+    assert_eq!(
+        learned.summary(),
+        "Unbound gestures seen:\n  d1: swipe_up x3\n  d1: tap x1\n"
+    );
+}
@@ -0,0 +1,18 @@
+//! Tests for `bodgestr::mqtt` - the published topic and payload.
+use bodgestr::mqtt::gesture_message;
+use bodgestr::recognizer::GestureType;
+
+#[test]
+fn test_gesture_message_uses_configured_topic() {
+    let (topic, _) = gesture_message("bodgestr/gestures", "kiosk", GestureType::Tap);
+    assert_eq!(topic, "bodgestr/gestures");
+}
+
+#[test]
+fn test_gesture_message_payload_is_device_and_gesture() {
+    let (_, payload) = gesture_message("t", "kiosk", GestureType::SwipeLeft);
+    assert_eq!(payload, "kiosk/swipe_left");
+
+    let (_, payload) = gesture_message("t", "panel-2", GestureType::LongPressDragEnd);
+    assert_eq!(payload, "panel-2/long_press_drag_end");
+}
@@ -2,8 +2,11 @@
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
-use bodgestr::config::ValidatedThresholds;
-use bodgestr::recognizer::{GestureRecognizer, GestureType, TouchPoint};
+use bodgestr::config::{PrimaryFinger, ValidatedThresholds, YAxis};
+use bodgestr::recognizer::{
+    GestureDetails, GestureRecognizer, GestureType, MockClock, NUM_GESTURES, PinchDetails,
+    TouchPoint, classify_swipe,
+};
 
 /// Screen range used for all tests: 0–1000 in both axes.
 const X_RANGE: (f64, f64) = (0.0, 1000.0);
@@ -20,6 +23,17 @@ fn default_thresholds() -> ValidatedThresholds {
         tap_distance_max: 50.0,
         double_tap_distance_max: 50.0,
         pinch_threshold_pct: 0.1,
+        swipe_commit_distance_pct: None,
+        swipe_reversal_pct: None,
+        swipe_velocity_min: None,
+        pinch_threshold_min_px: None,
+        swipe_distance_min_px: None,
+        pinch_repeat_interval: None,
+        circle_turn_min: None,
+        circle_radius_tolerance_pct: None,
+        swipe_reject_band_deg: None,
+        long_press_drift_max: None,
+        long_swipe_distance_pct: None,
     }
 }
 
@@ -120,6 +134,241 @@ fn test_diagonal_rejected() {
     );
 }
 
+// -- classify_swipe (free function) -----------------------
+
+fn point(x: f64, y: f64, time: Instant) -> TouchPoint {
+    TouchPoint {
+        x,
+        y,
+        time,
+        tracking_id: 0,
+    }
+}
+
+fn classify(x0: f64, y0: f64, x1: f64, y1: f64, duration: f64) -> Option<GestureType> {
+    let t0 = Instant::now();
+    classify_swipe(
+        point(x0, y0, t0),
+        point(x1, y1, t0 + Duration::from_secs_f64(duration)),
+        X_RANGE,
+        Y_RANGE,
+        &default_thresholds(),
+    )
+}
+
+#[test]
+fn test_classify_swipe_each_direction() {
+    assert_eq!(
+        classify(800.0, 500.0, 100.0, 500.0, 0.3),
+        Some(GestureType::SwipeLeft)
+    );
+    assert_eq!(
+        classify(100.0, 500.0, 800.0, 500.0, 0.3),
+        Some(GestureType::SwipeRight)
+    );
+    assert_eq!(
+        classify(500.0, 800.0, 500.0, 100.0, 0.3),
+        Some(GestureType::SwipeUp)
+    );
+    assert_eq!(
+        classify(500.0, 100.0, 500.0, 800.0, 0.3),
+        Some(GestureType::SwipeDown)
+    );
+}
+
+#[test]
+fn test_classify_swipe_rejects_too_slow() {
+    assert_eq!(classify(800.0, 500.0, 100.0, 500.0, 1.0), None);
+}
+
+#[test]
+fn test_classify_swipe_rejects_too_short() {
+    assert_eq!(classify(500.0, 500.0, 600.0, 500.0, 0.3), None);
+}
+
+#[test]
+fn test_classify_swipe_rejects_diagonal() {
+    assert_eq!(classify(100.0, 100.0, 900.0, 900.0, 0.3), None);
+}
+
+#[test]
+fn test_classify_swipe_uses_axis_span() {
+    let t0 = Instant::now();
+    // 100 units is 10% of a 1000-wide range but 50% of a 200-wide one.
+    let result = classify_swipe(
+        point(0.0, 50.0, t0),
+        point(100.0, 50.0, t0 + Duration::from_millis(100)),
+        (0.0, 200.0),
+        (0.0, 200.0),
+        &default_thresholds(),
+    );
+    assert_eq!(result, Some(GestureType::SwipeRight));
+}
+
+#[test]
+fn test_swipe_distance_min_px_ignores_axis_span() {
+    let th = ValidatedThresholds {
+        swipe_distance_min_px: Some(150.0),
+        ..default_thresholds()
+    };
+    let t0 = Instant::now();
+    let swipe = |span: f64, travel: f64| {
+        classify_swipe(
+            point(0.0, 50.0, t0),
+            point(travel, 50.0, t0 + Duration::from_millis(100)),
+            (0.0, span),
+            (0.0, span),
+            &th,
+        )
+    };
+    // 160 units pass and 140 fail whether they are 80% or 4% of the span.
+    for span in [200.0, 4000.0] {
+        assert_eq!(
+            swipe(span, 160.0),
+            Some(GestureType::SwipeRight),
+            "span {span}"
+        );
+        assert_eq!(swipe(span, 140.0), None, "span {span}");
+    }
+
+    // Through the recognizer as well, with and without normalized angles.
+    for normalize in [false, true] {
+        let mut rec = make_recognizer(Some(th.clone())).with_normalized_coordinates(normalize);
+        simulate_touch(&mut rec, 400.0, 500.0, 560.0, 500.0, 0.2, 0);
+        assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+        rec.reset();
+        simulate_touch(&mut rec, 400.0, 500.0, 540.0, 500.0, 0.2, 0);
+        assert_eq!(rec.recognize_gesture(), None);
+    }
+}
+
+// -- Y axis orientation -----------------------------------
+
+#[test]
+fn test_y_axis_down_decreasing_y_is_swipe_up() {
+    let mut rec = make_recognizer(None).with_y_axis(YAxis::Down);
+    simulate_touch(&mut rec, 500.0, 800.0, 500.0, 100.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeUp));
+}
+
+#[test]
+fn test_y_axis_up_increasing_y_is_swipe_up() {
+    let mut rec = make_recognizer(None).with_y_axis(YAxis::Up);
+    simulate_touch(&mut rec, 500.0, 100.0, 500.0, 800.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeUp));
+}
+
+#[test]
+fn test_y_axis_up_decreasing_y_is_swipe_down() {
+    let mut rec = make_recognizer(None).with_y_axis(YAxis::Up);
+    simulate_touch(&mut rec, 500.0, 800.0, 500.0, 100.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeDown));
+}
+
+#[test]
+fn test_y_axis_up_leaves_horizontal_swipes_alone() {
+    let mut rec = make_recognizer(None).with_y_axis(YAxis::Up);
+    simulate_touch(&mut rec, 100.0, 500.0, 800.0, 500.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+// -- Normalized coordinates ------------------------------
+
+#[test]
+fn test_normalized_coordinates_on_wide_screen() {
+    // On a 4:1 panel, 20% of the width right and 20% of the height down is
+    // 14° in device units but a 45° diagonal in the normalized square.
+    let swipe = |normalize: bool| {
+        let mut rec = GestureRecognizer::new(default_thresholds(), (0.0, 2000.0), (0.0, 500.0))
+            .with_normalized_coordinates(normalize);
+        simulate_touch(&mut rec, 800.0, 200.0, 1200.0, 300.0, 0.3, 0);
+        rec.recognize_gesture()
+    };
+    assert_eq!(swipe(false), Some(GestureType::SwipeRight));
+    assert_eq!(swipe(true), None);
+}
+
+#[test]
+fn test_normalized_coordinates_keep_axis_swipes() {
+    let mut rec = GestureRecognizer::new(default_thresholds(), (0.0, 2000.0), (0.0, 500.0))
+        .with_normalized_coordinates(true);
+    simulate_touch(&mut rec, 1000.0, 50.0, 1020.0, 400.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeDown));
+}
+
+// -- Long-press drag --------------------------------------
+
+/// Put a finger down at (x, y) `held_for` seconds ago, without releasing it.
+fn press_held(rec: &mut GestureRecognizer, x: f64, y: f64, held_for: f64) {
+    let start = TouchPoint {
+        x,
+        y,
+        time: Instant::now() - Duration::from_secs_f64(held_for),
+        tracking_id: 0,
+    };
+    rec.touch_start = Some(start);
+    rec.touch_current = Some(start);
+    rec.touch_points = vec![start];
+    rec.active_touches = HashMap::from([(0, start)]);
+}
+
+fn move_to(rec: &mut GestureRecognizer, x: f64) {
+    rec.set_pending_x(x);
+    rec.flush_pending();
+}
+
+#[test]
+fn test_long_press_then_move_starts_and_ends_drag() {
+    let mut rec = make_recognizer(None).with_long_press_drag(true);
+    press_held(&mut rec, 500.0, 500.0, 1.0);
+    move_to(&mut rec, 700.0);
+    assert_eq!(
+        rec.check_long_press_drag(),
+        Some(GestureType::LongPressDragStart)
+    );
+    // Reported once only.
+    move_to(&mut rec, 750.0);
+    assert_eq!(rec.check_long_press_drag(), None);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::LongPressDragEnd));
+}
+
+#[test]
+fn test_long_press_small_move_does_not_drag() {
+    let mut rec = make_recognizer(None).with_long_press_drag(true);
+    press_held(&mut rec, 500.0, 500.0, 1.0);
+    move_to(&mut rec, 510.0);
+    assert_eq!(rec.check_long_press_drag(), None);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::LongPress));
+}
+
+#[test]
+fn test_move_before_long_press_time_is_not_drag() {
+    let mut rec = make_recognizer(None).with_long_press_drag(true);
+    press_held(&mut rec, 500.0, 500.0, 0.1);
+    move_to(&mut rec, 700.0);
+    assert_eq!(rec.check_long_press_drag(), None);
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::LongPressDragEnd));
+}
+
+#[test]
+fn test_long_press_drag_disabled_by_default() {
+    let mut rec = make_recognizer(None);
+    press_held(&mut rec, 500.0, 500.0, 1.0);
+    move_to(&mut rec, 700.0);
+    assert_eq!(rec.check_long_press_drag(), None);
+    assert_eq!(rec.recognize_gesture(), None);
+}
+
+#[test]
+fn test_long_press_drag_state_cleared_on_reset() {
+    let mut rec = make_recognizer(None).with_long_press_drag(true);
+    press_held(&mut rec, 500.0, 500.0, 1.0);
+    move_to(&mut rec, 700.0);
+    rec.reset();
+    simulate_touch(&mut rec, 500.0, 500.0, 500.0, 500.0, 1.0, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::LongPress));
+}
+
 // -- Tap tests --------------------------------------------
 
 #[test]
@@ -196,6 +445,44 @@ fn test_long_press_with_too_much_movement() {
     assert_ne!(rec.recognize_gesture(), Some(GestureType::LongPress));
 }
 
+/// A one-second press at (500, 500) that drifts 80 units right and back.
+fn wobbly_long_press(rec: &mut GestureRecognizer) {
+    press_held(rec, 500.0, 500.0, 1.0);
+    move_to(rec, 580.0);
+    move_to(rec, 505.0);
+}
+
+#[test]
+fn test_long_press_drifting_within_allowance_is_long_press() {
+    let th = ValidatedThresholds {
+        long_press_drift_max: Some(100.0),
+        ..default_thresholds()
+    };
+    let mut rec = make_recognizer(Some(th)).with_long_press_drag(true);
+    wobbly_long_press(&mut rec);
+    assert_eq!(rec.check_long_press_drag(), None);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::LongPress));
+
+    // Without the allowance the drift past tap_distance_max starts a drag.
+    let mut rec = make_recognizer(None).with_long_press_drag(true);
+    wobbly_long_press(&mut rec);
+    assert_eq!(
+        rec.check_long_press_drag(),
+        Some(GestureType::LongPressDragStart)
+    );
+}
+
+#[test]
+fn test_long_press_drifting_past_allowance_is_not_long_press() {
+    let th = ValidatedThresholds {
+        long_press_drift_max: Some(60.0),
+        ..default_thresholds()
+    };
+    let mut rec = make_recognizer(Some(th));
+    wobbly_long_press(&mut rec);
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::LongPress));
+}
+
 // -- Pinch tests ------------------------------------------
 
 fn simulate_pinch(rec: &mut GestureRecognizer, start_dist: f64, end_dist: f64) {
@@ -227,69 +514,659 @@ fn simulate_pinch(rec: &mut GestureRecognizer, start_dist: f64, end_dist: f64) {
         tracking_id: 1,
     };
 
-    rec.touch_start = Some(p1_start);
-    rec.touch_current = Some(p1_end);
-    rec.touch_points = vec![p1_start, p2_start, p1_end, p2_end];
-    rec.active_touches = HashMap::from([(0, p1_end), (1, p2_end)]);
+    rec.touch_start = Some(p1_start);
+    rec.touch_current = Some(p1_end);
+    rec.touch_points = vec![p1_start, p2_start, p1_end, p2_end];
+    rec.active_touches = HashMap::from([(0, p1_end), (1, p2_end)]);
+}
+
+#[test]
+fn test_pinch_in() {
+    let mut rec = make_recognizer(None);
+    simulate_pinch(&mut rec, 400.0, 100.0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::PinchIn));
+}
+
+#[test]
+fn test_pinch_out() {
+    let mut rec = make_recognizer(None);
+    simulate_pinch(&mut rec, 100.0, 400.0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::PinchOut));
+}
+
+#[test]
+fn test_pinch_details_scale_and_center() {
+    let mut rec = make_recognizer(None);
+    simulate_pinch(&mut rec, 100.0, 200.0);
+    let details = rec.recognize_gesture_detailed().unwrap();
+    assert_eq!(details.gesture, GestureType::PinchOut);
+    let PinchDetails { center, scale } = details.pinch.unwrap();
+    assert!((scale - 2.0).abs() < 1e-9, "scale {scale}");
+    assert_eq!(center, (500.0, 500.0));
+
+    let mut rec = make_recognizer(None);
+    simulate_pinch(&mut rec, 400.0, 100.0);
+    let pinch = rec.recognize_gesture_detailed().unwrap().pinch.unwrap();
+    assert!((pinch.scale - 0.25).abs() < 1e-9);
+}
+
+#[test]
+fn test_non_pinch_has_no_pinch_details() {
+    let mut rec = make_recognizer(None);
+    simulate_touch(&mut rec, 800.0, 500.0, 100.0, 500.0, 0.3, 0);
+    let details = rec.recognize_gesture_detailed().unwrap();
+    assert_eq!(details.gesture, GestureType::SwipeLeft);
+    assert_eq!(details.pinch, None);
+}
+
+#[test]
+fn test_pinch_no_movement() {
+    let mut rec = make_recognizer(None);
+    simulate_pinch(&mut rec, 200.0, 200.0);
+    let result = rec.recognize_gesture();
+    assert!(result != Some(GestureType::PinchIn) && result != Some(GestureType::PinchOut));
+}
+
+#[test]
+fn test_globally_disabled_pinch_yields_nothing() {
+    let disabled = [GestureType::PinchIn, GestureType::PinchOut];
+    let mut rec = make_recognizer(None).with_disabled_gestures(&disabled);
+    simulate_pinch(&mut rec, 100.0, 400.0);
+    assert_eq!(rec.recognize_gesture(), None);
+
+    let mut rec = make_recognizer(None).with_disabled_gestures(&disabled);
+    simulate_pinch(&mut rec, 400.0, 100.0);
+    assert_eq!(rec.recognize_gesture(), None);
+}
+
+#[test]
+fn test_close_finger_jitter_is_pinch_without_floor() {
+    let mut rec = make_recognizer(None);
+    // 30 → 36 is a 20% change, above the 10% proportional threshold.
+    simulate_pinch(&mut rec, 30.0, 36.0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::PinchOut));
+}
+
+#[test]
+fn test_pinch_floor_ignores_close_finger_jitter() {
+    let th = ValidatedThresholds {
+        pinch_threshold_min_px: Some(20.0),
+        ..default_thresholds()
+    };
+    let mut rec = make_recognizer(Some(th.clone()));
+    simulate_pinch(&mut rec, 30.0, 36.0);
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::PinchOut));
+
+    // A real pinch still clears the floor.
+    let mut rec = make_recognizer(Some(th));
+    simulate_pinch(&mut rec, 30.0, 120.0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::PinchOut));
+}
+
+#[test]
+fn test_pinch_needs_enough_points() {
+    let mut rec = make_recognizer(None);
+    let now = Instant::now();
+    let later = now + Duration::from_secs_f64(0.3);
+    let start = TouchPoint {
+        x: 400.0,
+        y: 500.0,
+        time: now,
+        tracking_id: 0,
+    };
+    let current = TouchPoint {
+        x: 600.0,
+        y: 500.0,
+        time: later,
+        tracking_id: 0,
+    };
+    rec.touch_start = Some(start);
+    rec.touch_current = Some(current);
+    rec.touch_points = vec![start, current];
+    rec.active_touches = HashMap::from([
+        (0, current),
+        (
+            1,
+            TouchPoint {
+                x: 700.0,
+                y: 500.0,
+                time: later,
+                tracking_id: 1,
+            },
+        ),
+    ]);
+    // Only 2 points - pinch should not trigger
+    let result = rec.recognize_gesture();
+    assert!(result != Some(GestureType::PinchIn) && result != Some(GestureType::PinchOut));
+}
+
+// -- max_track_points tests ------------------------------
+
+#[test]
+fn test_track_points_bounded_and_swipe_still_recognized() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_max_track_points(Some(16));
+    rec.set_tracking_id(0);
+    for step in 0..500 {
+        rec.set_pending_x(100.0 + step as f64 * 1.5);
+        rec.set_pending_y(500.0);
+        rec.flush_pending();
+        clock.advance(Duration::from_millis(1));
+        assert!(rec.touch_points.len() <= 16);
+    }
+    assert_eq!(rec.touch_points[0].x, 100.0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+#[test]
+fn test_track_points_cap_keeps_reversal_peak() {
+    let clock = MockClock::new();
+    let th = ValidatedThresholds {
+        swipe_reversal_pct: Some(0.5),
+        ..default_thresholds()
+    };
+    let mut rec = make_recognizer(Some(th))
+        .with_clock(clock.clone())
+        .with_max_track_points(Some(8));
+    // Out to 800 and back to 350 in many small steps.
+    let xs = (0..=70)
+        .map(|i| 100.0 + i as f64 * 10.0)
+        .chain((1..=45).map(|i| 800.0 - i as f64 * 10.0));
+    assert_eq!(
+        mock_trajectory(&mut rec, &clock, &xs.collect::<Vec<_>>()),
+        None
+    );
+}
+
+#[test]
+fn test_track_points_unbounded_by_default() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    rec.set_tracking_id(0);
+    for step in 0..100 {
+        rec.set_pending_x(step as f64);
+        rec.flush_pending();
+    }
+    assert_eq!(rec.touch_points.len(), 100);
+}
+
+// -- Held tap (chorded) tests ----------------------------
+
+/// Put finger `id` down (or move it) at (`x`, 500).
+fn mock_finger(rec: &mut GestureRecognizer, id: i32, x: f64) {
+    rec.set_tracking_id(id);
+    rec.set_pending_x(x);
+    rec.set_pending_y(500.0);
+    rec.flush_pending();
+}
+
+#[test]
+fn test_held_primary_with_secondary_tap() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    mock_finger(&mut rec, 0, 300.0);
+    clock.advance(Duration::from_millis(500));
+    mock_finger(&mut rec, 0, 302.0);
+    mock_finger(&mut rec, 1, 600.0);
+    clock.advance(Duration::from_millis(80));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::HeldTapSecondary));
+}
+
+#[test]
+fn test_held_tap_needs_primary_held_first() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    // Both fingers land together: a two-finger touch, not a chord.
+    mock_finger(&mut rec, 0, 300.0);
+    mock_finger(&mut rec, 1, 600.0);
+    clock.advance(Duration::from_millis(80));
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::HeldTapSecondary));
+}
+
+#[test]
+fn test_held_tap_rejects_slow_secondary() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    mock_finger(&mut rec, 0, 300.0);
+    clock.advance(Duration::from_millis(500));
+    mock_finger(&mut rec, 1, 600.0);
+    clock.advance(Duration::from_millis(400));
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::HeldTapSecondary));
+}
+
+// -- Circle tests ----------------------------------------
+
+fn circle_recognizer() -> GestureRecognizer {
+    make_recognizer(Some(ValidatedThresholds {
+        circle_turn_min: Some(0.75),
+        ..default_thresholds()
+    }))
+}
+
+/// Feed a single-finger path through the recognizer.
+fn trace(rec: &mut GestureRecognizer, path: impl IntoIterator<Item = (f64, f64)>) {
+    rec.set_tracking_id(0);
+    for (x, y) in path {
+        rec.set_pending_x(x);
+        rec.set_pending_y(y);
+        rec.flush_pending();
+    }
+}
+
+/// One full turn of radius 200 around (500, 500); with Y growing downward,
+/// an increasing angle runs clockwise on screen.
+fn circle_path(clockwise: bool) -> impl Iterator<Item = (f64, f64)> {
+    let sign = if clockwise { 1.0 } else { -1.0 };
+    (0..=24).map(move |i| {
+        let angle = sign * f64::from(i) * std::f64::consts::TAU / 24.0;
+        (500.0 + 200.0 * angle.cos(), 500.0 + 200.0 * angle.sin())
+    })
+}
+
+#[test]
+fn test_circle_clockwise() {
+    let mut rec = circle_recognizer();
+    trace(&mut rec, circle_path(true));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::CircleClockwise));
+}
+
+#[test]
+fn test_circle_counter_clockwise() {
+    let mut rec = circle_recognizer();
+    trace(&mut rec, circle_path(false));
+    assert_eq!(
+        rec.recognize_gesture(),
+        Some(GestureType::CircleCounterClockwise)
+    );
+}
+
+#[test]
+fn test_straight_path_is_not_circle() {
+    let mut rec = circle_recognizer();
+    trace(
+        &mut rec,
+        (0..10).map(|i| (100.0 + f64::from(i) * 70.0, 500.0)),
+    );
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+#[test]
+fn test_circle_off_without_turn_threshold() {
+    let mut rec = make_recognizer(None);
+    trace(&mut rec, circle_path(true));
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::CircleClockwise));
+}
+
+// -- Tracking ID churn tests -----------------------------
+
+/// One finger swiping right from x=100 in 40-unit frames 10 ms apart, its
+/// tracking ID replaced (without a lift) every fifth frame.
+fn churning_swipe(rec: &mut GestureRecognizer, clock: &MockClock) {
+    for frame in 0..18 {
+        if frame % 5 == 0 {
+            rec.set_tracking_id(100 + frame);
+        }
+        rec.set_pending_x(100.0 + f64::from(frame) * 40.0);
+        rec.set_pending_y(500.0);
+        rec.flush_pending();
+        clock.advance(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_id_churn_inflates_finger_count_without_merge() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    churning_swipe(&mut rec, &clock);
+    assert_eq!(rec.active_touches.len(), 4);
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+#[test]
+fn test_id_merge_keeps_churning_finger_single() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_id_merge(Some(Duration::from_millis(50)));
+    churning_swipe(&mut rec, &clock);
+    assert_eq!(rec.active_touches.len(), 1);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+#[test]
+fn test_id_merge_ignores_distant_new_contact() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_id_merge(Some(Duration::from_millis(50)));
+    rec.set_tracking_id(1);
+    rec.set_pending_x(100.0);
+    rec.set_pending_y(500.0);
+    rec.flush_pending();
+    clock.advance(Duration::from_millis(10));
+    // Same slot, new ID, but far away: a different finger.
+    rec.set_tracking_id(2);
+    rec.set_pending_x(700.0);
+    rec.set_pending_y(500.0);
+    rec.flush_pending();
+    assert_eq!(rec.active_touches.len(), 2);
+}
+
+// -- Stale touch tests -----------------------------------
+
+/// A finger lands in slot 0 and is never released.
+fn stuck_finger(rec: &mut GestureRecognizer) {
+    rec.set_slot(0);
+    rec.set_tracking_id(1);
+    rec.set_pending_x(500.0);
+    rec.set_pending_y(200.0);
+    rec.flush_pending();
+}
+
+/// A second finger swipes left in slot 1.
+fn swipe_left_in_slot_1(rec: &mut GestureRecognizer, clock: &MockClock) {
+    rec.set_slot(1);
+    rec.set_tracking_id(2);
+    for x in [800.0, 450.0, 100.0] {
+        rec.set_pending_x(x);
+        rec.set_pending_y(600.0);
+        rec.flush_pending();
+        clock.advance(Duration::from_millis(50));
+    }
+}
+
+#[test]
+fn test_stale_touch_evicted_and_single_finger_resumes() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_stale_touch_timeout(Some(Duration::from_millis(500)));
+    stuck_finger(&mut rec);
+    clock.advance(Duration::from_secs(2));
+    swipe_left_in_slot_1(&mut rec, &clock);
+    assert_eq!(rec.active_touches.len(), 1);
+    assert!(!rec.active_touches.contains_key(&1));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeLeft));
+}
+
+#[test]
+fn test_stale_touch_kept_without_timeout_or_within_it() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    stuck_finger(&mut rec);
+    clock.advance(Duration::from_secs(2));
+    swipe_left_in_slot_1(&mut rec, &clock);
+    assert_eq!(rec.active_touches.len(), 2);
+
+    // A resting finger updated within the timeout is not stale.
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_stale_touch_timeout(Some(Duration::from_millis(500)));
+    stuck_finger(&mut rec);
+    clock.advance(Duration::from_millis(100));
+    swipe_left_in_slot_1(&mut rec, &clock);
+    assert_eq!(rec.active_touches.len(), 2);
+}
+
+// -- Swipe-then-tap tests --------------------------------
+
+/// Swipe right to (700, 500), wait `gap`, then tap at `tap`; returns what
+/// the tap is recognized as.
+fn swipe_right_then_tap(
+    rec: &mut GestureRecognizer,
+    clock: &MockClock,
+    gap: Duration,
+    tap: (f64, f64),
+) -> Option<GestureType> {
+    rec.set_tracking_id(0);
+    for x in [100.0, 300.0, 500.0, 700.0] {
+        rec.set_pending_x(x);
+        rec.set_pending_y(500.0);
+        rec.flush_pending();
+        clock.advance(Duration::from_millis(20));
+    }
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+    rec.reset();
+
+    clock.advance(gap);
+    rec.set_tracking_id(1);
+    rec.set_pending_x(tap.0);
+    rec.set_pending_y(tap.1);
+    rec.flush_pending();
+    clock.advance(Duration::from_millis(50));
+    let tapped = rec.recognize_gesture();
+    rec.reset();
+    tapped
+}
+
+#[test]
+fn test_swipe_then_tap_in_window_is_compound() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_swipe_then_tap(Some(Duration::from_millis(500)));
+    assert_eq!(
+        swipe_right_then_tap(&mut rec, &clock, Duration::from_millis(200), (710.0, 505.0)),
+        Some(GestureType::SwipeRightThenTap)
+    );
+    assert!(!rec.has_pending_tap());
+}
+
+#[test]
+fn test_swipe_then_tap_outside_window_or_away_is_plain_tap() {
+    let window = Some(Duration::from_millis(500));
+    let near_end = (710.0, 505.0);
+
+    // Too late: the tap waits for a double tap as usual.
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_swipe_then_tap(window);
+    let late = swipe_right_then_tap(&mut rec, &clock, Duration::from_millis(800), near_end);
+    assert_eq!(late, None);
+    assert!(rec.has_pending_tap());
+
+    // Far from where the swipe ended.
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_swipe_then_tap(window);
+    let away = swipe_right_then_tap(&mut rec, &clock, Duration::from_millis(200), (200.0, 500.0));
+    assert_eq!(away, None);
+    assert!(rec.has_pending_tap());
+
+    // Not enabled.
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    let off = swipe_right_then_tap(&mut rec, &clock, Duration::from_millis(200), near_end);
+    assert_eq!(off, None);
+    assert!(rec.has_pending_tap());
+}
+
+// -- Hold-then-swipe tests -------------------------------
+
+/// Land at (100, 500), rest there for `pause`, then swipe right.
+fn swipe_right_after(rec: &mut GestureRecognizer, clock: &MockClock, pause: Duration) {
+    rec.set_tracking_id(0);
+    rec.set_pending_x(100.0);
+    rec.set_pending_y(500.0);
+    rec.flush_pending();
+    clock.advance(pause);
+    for x in [105.0, 300.0, 500.0, 700.0] {
+        rec.set_pending_x(x);
+        rec.set_pending_y(500.0);
+        rec.flush_pending();
+        clock.advance(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_pause_then_swipe_is_hold_swipe() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_hold_before_swipe(Some(Duration::from_millis(300)));
+    swipe_right_after(&mut rec, &clock, Duration::from_millis(400));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::HoldSwipeRight));
+    rec.reset();
+
+    swipe_right_after(&mut rec, &clock, Duration::from_millis(20));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+#[test]
+fn test_hold_swipe_pause_longer_than_swipe_time_max() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_hold_before_swipe(Some(Duration::from_millis(800)));
+    // The 1.5 s pause alone exceeds swipe_time_max; only the drag is timed.
+    swipe_right_after(&mut rec, &clock, Duration::from_millis(1500));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::HoldSwipeRight));
+}
+
+#[test]
+fn test_pause_then_swipe_without_hold_before_swipe_is_plain_swipe() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    swipe_right_after(&mut rec, &clock, Duration::from_millis(400));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+// -- Long swipe tests ------------------------------------
+
+#[test]
+fn test_full_width_swipe_is_long_swipe() {
+    let th = ValidatedThresholds {
+        long_swipe_distance_pct: Some(0.8),
+        ..default_thresholds()
+    };
+    let mut rec = make_recognizer(Some(th));
+    simulate_touch(&mut rec, 50.0, 500.0, 950.0, 500.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::LongSwipeRight));
+    rec.reset();
+
+    simulate_touch(&mut rec, 250.0, 500.0, 750.0, 500.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+    rec.reset();
+
+    simulate_touch(&mut rec, 500.0, 950.0, 500.0, 50.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::LongSwipeUp));
 }
 
 #[test]
-fn test_pinch_in() {
+fn test_long_swipe_off_without_threshold_or_when_disabled() {
     let mut rec = make_recognizer(None);
-    simulate_pinch(&mut rec, 400.0, 100.0);
-    assert_eq!(rec.recognize_gesture(), Some(GestureType::PinchIn));
+    simulate_touch(&mut rec, 50.0, 500.0, 950.0, 500.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+
+    let th = ValidatedThresholds {
+        long_swipe_distance_pct: Some(0.8),
+        ..default_thresholds()
+    };
+    let mut rec = make_recognizer(Some(th)).with_disabled_gestures(&[GestureType::LongSwipeRight]);
+    simulate_touch(&mut rec, 50.0, 500.0, 950.0, 500.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+// -- Corner tests ----------------------------------------
+
+/// Down from (200, 100) to (200, 700), then right to (800, 700).
+fn down_right_path() -> impl Iterator<Item = (f64, f64)> {
+    let down = (0..=6).map(|i| (200.0, 100.0 + f64::from(i) * 100.0));
+    let right = (1..=6).map(|i| (200.0 + f64::from(i) * 100.0, 700.0));
+    down.chain(right)
 }
 
 #[test]
-fn test_pinch_out() {
-    let mut rec = make_recognizer(None);
-    simulate_pinch(&mut rec, 100.0, 400.0);
-    assert_eq!(rec.recognize_gesture(), Some(GestureType::PinchOut));
+fn test_corner_down_right() {
+    let mut rec = make_recognizer(None).with_corner_gestures(true);
+    trace(&mut rec, down_right_path());
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::CornerDownRight));
 }
 
 #[test]
-fn test_pinch_no_movement() {
-    let mut rec = make_recognizer(None);
-    simulate_pinch(&mut rec, 200.0, 200.0);
-    let result = rec.recognize_gesture();
-    assert!(result != Some(GestureType::PinchIn) && result != Some(GestureType::PinchOut));
+fn test_corner_right_up() {
+    let mut rec = make_recognizer(None).with_corner_gestures(true);
+    let right = (0..=6).map(|i| (100.0 + f64::from(i) * 100.0, 800.0));
+    let up = (1..=6).map(|i| (700.0, 800.0 - f64::from(i) * 100.0));
+    trace(&mut rec, right.chain(up));
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::CornerRightUp));
 }
 
 #[test]
-fn test_pinch_needs_enough_points() {
+fn test_straight_swipe_is_not_corner() {
+    let mut rec = make_recognizer(None).with_corner_gestures(true);
+    trace(
+        &mut rec,
+        (0..10).map(|i| (100.0 + f64::from(i) * 70.0, 500.0)),
+    );
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
+#[test]
+fn test_curved_path_is_not_corner() {
+    let mut rec = make_recognizer(None).with_corner_gestures(true);
+    // A quarter circle bulges too far from both legs' straight lines.
+    trace(
+        &mut rec,
+        (0..=12).map(|i| {
+            let angle = f64::from(i) * std::f64::consts::FRAC_PI_2 / 12.0;
+            (
+                200.0 + 600.0 * angle.sin(),
+                100.0 + 600.0 * (1.0 - angle.cos()),
+            )
+        }),
+    );
+    let gesture = rec.recognize_gesture();
+    assert!(!gesture.is_some_and(|g| g.to_string().starts_with("corner")));
+}
+
+#[test]
+fn test_corner_off_by_default() {
     let mut rec = make_recognizer(None);
-    let now = Instant::now();
-    let later = now + Duration::from_secs_f64(0.3);
-    let start = TouchPoint {
-        x: 400.0,
-        y: 500.0,
-        time: now,
-        tracking_id: 0,
-    };
-    let current = TouchPoint {
-        x: 600.0,
-        y: 500.0,
-        time: later,
-        tracking_id: 0,
-    };
-    rec.touch_start = Some(start);
-    rec.touch_current = Some(current);
-    rec.touch_points = vec![start, current];
-    rec.active_touches = HashMap::from([
-        (0, current),
-        (
-            1,
-            TouchPoint {
-                x: 700.0,
-                y: 500.0,
-                time: later,
-                tracking_id: 1,
-            },
-        ),
-    ]);
-    // Only 2 points - pinch should not trigger
-    let result = rec.recognize_gesture();
-    assert!(result != Some(GestureType::PinchIn) && result != Some(GestureType::PinchOut));
+    trace(&mut rec, down_right_path());
+    assert_ne!(rec.recognize_gesture(), Some(GestureType::CornerDownRight));
+}
+
+// -- Primary finger tests --------------------------------
+
+/// Finger 5 rests at (450, 900) while finger 2 swipes right along y = 500;
+/// their distance is the same at both ends, so it is no pinch.
+fn rest_and_swipe(policy: PrimaryFinger) -> Option<GestureType> {
+    let mut rec = make_recognizer(None).with_primary_finger(policy);
+    for (id, x, y) in [(5, 450.0, 900.0), (2, 100.0, 500.0), (2, 450.0, 500.0)] {
+        rec.set_tracking_id(id);
+        rec.set_pending_x(x);
+        rec.set_pending_y(y);
+        rec.flush_pending();
+    }
+    for (id, x, y) in [(5, 450.0, 900.0), (2, 800.0, 500.0)] {
+        rec.set_tracking_id(id);
+        rec.set_pending_x(x);
+        rec.set_pending_y(y);
+        rec.flush_pending();
+    }
+    rec.recognize_gesture()
+}
+
+#[test]
+fn test_primary_first_follows_resting_finger() {
+    assert_ne!(
+        rest_and_swipe(PrimaryFinger::First),
+        Some(GestureType::SwipeRight)
+    );
+}
+
+#[test]
+fn test_primary_lowest_id_follows_swiping_finger() {
+    assert_eq!(
+        rest_and_swipe(PrimaryFinger::LowestId),
+        Some(GestureType::SwipeRight)
+    );
 }
 
 // -- Reset tests -----------------------------------------
@@ -342,6 +1219,27 @@ fn test_flush_partial_x_only() {
     assert_eq!(rec.touch_current.unwrap().y, 0.0);
 }
 
+#[test]
+fn test_flush_waits_for_both_axes_of_new_contact() {
+    let mut rec = make_recognizer(None).with_require_both_axes(true);
+    rec.set_tracking_id(3);
+    rec.set_pending_x(300.0);
+    rec.flush_pending();
+    assert!(rec.touch_start.is_none());
+
+    rec.set_pending_y(400.0);
+    rec.flush_pending();
+    let first = rec.touch_start.unwrap();
+    assert_eq!((first.x, first.y), (300.0, 400.0));
+    assert_eq!(rec.touch_points.len(), 1);
+
+    // Once the contact has a point, single-axis frames commit as usual.
+    rec.set_pending_x(350.0);
+    rec.flush_pending();
+    assert_eq!(rec.touch_current.unwrap().y, 400.0);
+    assert_eq!(rec.touch_points.len(), 2);
+}
+
 #[test]
 fn test_flush_preserves_previous_y() {
     let mut rec = make_recognizer(None);
@@ -354,6 +1252,17 @@ fn test_flush_preserves_previous_y() {
     assert_eq!(rec.touch_current.unwrap().y, 200.0);
 }
 
+#[test]
+fn test_flush_clamps_out_of_range_coordinates() {
+    let mut rec = make_recognizer(None);
+    rec.set_pending_x(X_RANGE.1 + 25.0);
+    rec.set_pending_y(Y_RANGE.0 - 10.0);
+    rec.flush_pending();
+    let point = rec.touch_current.unwrap();
+    assert_eq!(point.x, X_RANGE.1);
+    assert_eq!(point.y, Y_RANGE.0);
+}
+
 #[test]
 fn test_multiple_flushes_append_points() {
     let mut rec = make_recognizer(None);
@@ -366,6 +1275,37 @@ fn test_multiple_flushes_append_points() {
     assert_eq!(rec.touch_points.len(), 2);
 }
 
+// -- Degenerate axis ranges ------------------------------
+
+#[test]
+fn test_degenerate_range_small_move_is_not_swipe() {
+    for x_range in [(500.0, 500.0), (1000.0, 0.0), (f64::NAN, f64::NAN)] {
+        let mut rec = GestureRecognizer::new(default_thresholds(), x_range, (0.0, 0.0));
+        simulate_touch(&mut rec, 500.0, 500.0, 520.0, 500.0, 0.1, 0);
+        let result = rec.recognize_gesture();
+        assert!(
+            !matches!(
+                result,
+                Some(
+                    GestureType::SwipeLeft
+                        | GestureType::SwipeRight
+                        | GestureType::SwipeUp
+                        | GestureType::SwipeDown
+                )
+            ),
+            "{x_range:?} gave {result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_degenerate_range_falls_back_to_default_span() {
+    let mut rec = GestureRecognizer::new(default_thresholds(), (0.0, 0.0), (0.0, 0.0));
+    // 15% of the 4096 fallback span is ~614; a 1000-unit move is a swipe.
+    simulate_touch(&mut rec, 100.0, 500.0, 1100.0, 500.0, 0.2, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+}
+
 // -- Custom thresholds tests -----------------------------
 
 #[test]
@@ -381,6 +1321,42 @@ fn test_stricter_swipe_distance() {
     assert_ne!(result, Some(GestureType::SwipeLeft));
 }
 
+#[test]
+fn test_swipe_velocity_floor_rejects_slow_drag() {
+    let th = ValidatedThresholds {
+        swipe_velocity_min: Some(1.0),
+        ..default_thresholds()
+    };
+    // 700px on a 1000px screen in 0.8s = 0.875 screens/s - too slow.
+    let mut rec = make_recognizer(Some(th.clone()));
+    simulate_touch(&mut rec, 800.0, 500.0, 100.0, 500.0, 0.8, 0);
+    assert_eq!(rec.recognize_gesture(), None);
+
+    // The same distance in 0.3s is a normal swipe.
+    let mut rec = make_recognizer(Some(th));
+    simulate_touch(&mut rec, 800.0, 500.0, 100.0, 500.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeLeft));
+}
+
+#[test]
+fn test_swipe_reject_band_drops_near_diagonal_swipe() {
+    let th = ValidatedThresholds {
+        angle_tolerance_deg: 45.0,
+        ..default_thresholds()
+    };
+    // 500px right, 420px down: about 40° off the horizontal.
+    let mut rec = make_recognizer(Some(th.clone()));
+    simulate_touch(&mut rec, 200.0, 200.0, 700.0, 620.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeRight));
+
+    let mut rec = make_recognizer(Some(ValidatedThresholds {
+        swipe_reject_band_deg: Some(10.0),
+        ..th
+    }));
+    simulate_touch(&mut rec, 200.0, 200.0, 700.0, 620.0, 0.3, 0);
+    assert_eq!(rec.recognize_gesture(), None);
+}
+
 #[test]
 fn test_longer_tap_time_allows_slower_taps() {
     let th = ValidatedThresholds {
@@ -439,6 +1415,18 @@ fn test_gesture_from_str() {
     assert!("unknown".parse::<GestureType>().is_err());
 }
 
+#[test]
+fn test_gesture_from_str_case_insensitive() {
+    assert_eq!(
+        "Swipe_Left".parse::<GestureType>(),
+        Ok(GestureType::SwipeLeft)
+    );
+    assert_eq!(
+        "DOUBLE_TAP".parse::<GestureType>(),
+        Ok(GestureType::DoubleTap)
+    );
+}
+
 #[test]
 fn test_gesture_display() {
     assert_eq!(format!("{}", GestureType::Tap), "tap");
@@ -509,6 +1497,344 @@ fn test_check_expired_returns_none_when_no_pending_tap() {
     assert_eq!(rec.check_pending_tap_expired(), None);
 }
 
+// -- gesture_thresholds tests ----------------------------
+
+/// Two taps 0.2s apart; `Some(DoubleTap)` if the second completes a double tap.
+fn taps_200ms_apart(rec: &mut GestureRecognizer) -> Option<GestureType> {
+    simulate_touch(rec, 500.0, 500.0, 500.0, 500.0, 0.05, 0);
+    rec.recognize_gesture();
+    rec.last_tap_time = Some(Instant::now() - Duration::from_secs_f64(0.2));
+    simulate_touch(rec, 500.0, 500.0, 500.0, 500.0, 0.05, 0);
+    rec.recognize_gesture()
+}
+
+#[test]
+fn test_gesture_threshold_overrides_device_double_tap_interval() {
+    // Device interval 0.3s accepts the second tap.
+    let mut rec = make_recognizer(None);
+    assert_eq!(taps_200ms_apart(&mut rec), Some(GestureType::DoubleTap));
+
+    // A 0.1s interval for double_tap only starts a new tap instead.
+    let double_tap = ValidatedThresholds {
+        double_tap_interval: 0.1,
+        ..default_thresholds()
+    };
+    let mut rec =
+        make_recognizer(None).with_gesture_thresholds([(GestureType::DoubleTap, double_tap)]);
+    assert_eq!(taps_200ms_apart(&mut rec), None);
+    assert!(rec.has_pending_tap());
+    assert_eq!(rec.thresholds.double_tap_interval, 0.3);
+}
+
+// -- Mock clock tests ------------------------------------
+
+/// Press and release at (500, 500), holding for `held` on the mock clock.
+fn mock_tap(rec: &mut GestureRecognizer, clock: &MockClock, held: Duration) -> Option<GestureType> {
+    mock_tap_detailed(rec, clock, held).map(|d| d.gesture)
+}
+
+fn mock_tap_detailed(
+    rec: &mut GestureRecognizer,
+    clock: &MockClock,
+    held: Duration,
+) -> Option<GestureDetails> {
+    rec.set_tracking_id(0);
+    rec.set_pending_x(500.0);
+    rec.set_pending_y(500.0);
+    rec.flush_pending();
+    clock.advance(held);
+    rec.set_pending_x(500.0);
+    rec.flush_pending();
+    let details = rec.recognize_gesture_detailed();
+    rec.reset();
+    details
+}
+
+#[test]
+fn test_mock_clock_double_tap_within_interval() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+
+    assert_eq!(mock_tap(&mut rec, &clock, Duration::from_millis(50)), None);
+    clock.advance(Duration::from_millis(200));
+    assert_eq!(rec.check_pending_tap_expired(), None);
+    assert_eq!(
+        mock_tap(&mut rec, &clock, Duration::from_millis(50)),
+        Some(GestureType::DoubleTap)
+    );
+}
+
+#[test]
+fn test_mock_clock_tap_expires_after_interval() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+
+    assert_eq!(mock_tap(&mut rec, &clock, Duration::from_millis(50)), None);
+    clock.advance(Duration::from_millis(299));
+    assert_eq!(rec.check_pending_tap_expired(), None);
+    clock.advance(Duration::from_millis(1));
+    assert_eq!(rec.check_pending_tap_expired(), Some(GestureType::Tap));
+}
+
+#[test]
+fn test_tap_count_max_reports_counted_taps() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None)
+        .with_clock(clock.clone())
+        .with_tap_count_max(3);
+    let held = Duration::from_millis(50);
+
+    // Three quick taps: one Tap counting all three, no DoubleTap.
+    assert_eq!(mock_tap(&mut rec, &clock, held), None);
+    clock.advance(Duration::from_millis(100));
+    assert_eq!(mock_tap(&mut rec, &clock, held), None);
+    clock.advance(Duration::from_millis(100));
+    let triple = mock_tap_detailed(&mut rec, &clock, held).unwrap();
+    assert_eq!((triple.gesture, triple.tap_count), (GestureType::Tap, 3));
+    assert!(!rec.has_pending_tap());
+
+    // Two taps, then the interval passes: a Tap counting two.
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(mock_tap(&mut rec, &clock, held), None);
+    clock.advance(Duration::from_millis(100));
+    assert_eq!(mock_tap(&mut rec, &clock, held), None);
+    clock.advance(Duration::from_millis(300));
+    let double = rec.check_pending_tap_expired_detailed().unwrap();
+    assert_eq!((double.gesture, double.tap_count), (GestureType::Tap, 2));
+
+    // A lone tap counts one.
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(mock_tap(&mut rec, &clock, held), None);
+    clock.advance(Duration::from_millis(300));
+    let single = rec.check_pending_tap_expired_detailed().unwrap();
+    assert_eq!(single.tap_count, 1);
+}
+
+// -- Swipe reversal (zigzag) tests -----------------------
+
+/// Drag one finger along y = 500 through `xs`, 50 ms per step, and release.
+fn mock_trajectory(
+    rec: &mut GestureRecognizer,
+    clock: &MockClock,
+    xs: &[f64],
+) -> Option<GestureType> {
+    rec.set_tracking_id(0);
+    for &x in xs {
+        rec.set_pending_x(x);
+        rec.set_pending_y(500.0);
+        rec.flush_pending();
+        clock.advance(Duration::from_millis(50));
+    }
+    rec.recognize_gesture()
+}
+
+fn reversal_recognizer(clock: &MockClock) -> GestureRecognizer {
+    let th = ValidatedThresholds {
+        swipe_reversal_pct: Some(0.5),
+        ..default_thresholds()
+    };
+    make_recognizer(Some(th)).with_clock(clock.clone())
+}
+
+#[test]
+fn test_swipe_reversed_past_fraction_is_cancelled() {
+    let clock = MockClock::new();
+    let mut rec = reversal_recognizer(&clock);
+    // Out 700, back 400: the net 300 would still be a swipe right.
+    assert_eq!(
+        mock_trajectory(&mut rec, &clock, &[100.0, 800.0, 400.0]),
+        None
+    );
+    assert!(!rec.has_pending_tap());
+}
+
+#[test]
+fn test_swipe_out_and_back_is_not_a_tap() {
+    let clock = MockClock::new();
+    let mut rec = reversal_recognizer(&clock);
+    assert_eq!(
+        mock_trajectory(&mut rec, &clock, &[100.0, 800.0, 110.0]),
+        None
+    );
+    assert!(!rec.has_pending_tap());
+}
+
+#[test]
+fn test_swipe_small_reversal_still_fires() {
+    let clock = MockClock::new();
+    let mut rec = reversal_recognizer(&clock);
+    assert_eq!(
+        mock_trajectory(&mut rec, &clock, &[100.0, 800.0, 700.0]),
+        Some(GestureType::SwipeRight)
+    );
+}
+
+#[test]
+fn test_swipe_reversal_ignored_without_threshold() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    assert_eq!(
+        mock_trajectory(&mut rec, &clock, &[100.0, 800.0, 400.0]),
+        Some(GestureType::SwipeRight)
+    );
+}
+
+// -- current_direction tests ------------------------------
+
+/// Move the single finger to `x` on y = 500, 50 ms after the previous frame.
+fn mock_move(rec: &mut GestureRecognizer, clock: &MockClock, x: f64) {
+    clock.advance(Duration::from_millis(50));
+    rec.set_pending_x(x);
+    rec.set_pending_y(500.0);
+    rec.flush_pending();
+}
+
+#[test]
+fn test_current_direction_tracks_motion_mid_swipe() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    rec.set_tracking_id(0);
+    mock_move(&mut rec, &clock, 500.0);
+    assert_eq!(rec.current_direction(), None);
+
+    mock_move(&mut rec, &clock, 600.0);
+    assert_eq!(rec.current_direction(), None);
+    mock_move(&mut rec, &clock, 700.0);
+    assert_eq!(rec.current_direction(), Some(GestureType::SwipeRight));
+
+    // Reversing back past the start flips the preview.
+    mock_move(&mut rec, &clock, 450.0);
+    assert_eq!(rec.current_direction(), None);
+    mock_move(&mut rec, &clock, 300.0);
+    assert_eq!(rec.current_direction(), Some(GestureType::SwipeLeft));
+
+    // Querying does not consume the touch.
+    assert_eq!(rec.recognize_gesture(), Some(GestureType::SwipeLeft));
+}
+
+#[test]
+fn test_current_direction_none_without_touch() {
+    let rec = make_recognizer(None);
+    assert_eq!(rec.current_direction(), None);
+}
+
+#[test]
+fn test_current_progress_rises_and_caps_at_commit_distance() {
+    let th = ValidatedThresholds {
+        swipe_commit_distance_pct: Some(0.3),
+        ..default_thresholds()
+    };
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(Some(th)).with_clock(clock.clone());
+    assert_eq!(rec.current_progress(), None);
+    rec.set_tracking_id(0);
+    mock_move(&mut rec, &clock, 500.0);
+    assert_eq!(rec.current_progress(), None);
+
+    let mut last = 0.0;
+    for x in [560.0, 650.0, 740.0] {
+        mock_move(&mut rec, &clock, x);
+        let (direction, progress) = rec.current_progress().unwrap();
+        assert_eq!(direction, GestureType::SwipeRight);
+        assert!(progress > last && progress < 1.0, "{progress} after {last}");
+        last = progress;
+    }
+    mock_move(&mut rec, &clock, 800.0);
+    assert_eq!(rec.current_progress(), Some((GestureType::SwipeRight, 1.0)));
+    mock_move(&mut rec, &clock, 950.0);
+    assert_eq!(rec.current_progress(), Some((GestureType::SwipeRight, 1.0)));
+
+    // Heading back the other way reports progress to the left.
+    mock_move(&mut rec, &clock, 350.0);
+    let (direction, progress) = rec.current_progress().unwrap();
+    assert_eq!(direction, GestureType::SwipeLeft);
+    assert!((progress - 0.5).abs() < 1e-9, "{progress}");
+}
+
+// -- Event timestamp tests --------------------------------
+
+#[test]
+fn test_event_time_jump_reanchors_to_clock() {
+    let clock = MockClock::new();
+    let mut rec = make_recognizer(None).with_clock(clock.clone());
+    let t0 = std::time::SystemTime::now();
+    rec.set_tracking_id(0);
+
+    rec.set_event_time(t0);
+    rec.set_pending_x(500.0);
+    rec.flush_pending();
+    let start = rec.touch_start.unwrap().time;
+
+    // A wall-clock jump of an hour must not become an hour-long touch.
+    clock.advance(Duration::from_millis(100));
+    rec.set_event_time(t0 + Duration::from_secs(3600));
+    rec.set_pending_x(510.0);
+    rec.flush_pending();
+    let current = rec.touch_current.unwrap().time;
+    assert_eq!(current.duration_since(start), Duration::from_millis(100));
+}
+
+// -- recognize_gesture_detailed tests ---------------------
+
+#[test]
+fn test_detailed_swipe_reports_kinematics() {
+    let mut rec = make_recognizer(None);
+    simulate_touch(&mut rec, 800.0, 500.0, 100.0, 500.0, 0.3, 0);
+    let details = rec.recognize_gesture_detailed().unwrap();
+    assert_eq!(details.gesture, GestureType::SwipeLeft);
+    assert!((details.angle_deg.abs() - 180.0).abs() < 1e-9);
+    assert!((details.distance - 700.0).abs() < 1e-9);
+    assert!((details.duration - 0.3).abs() < 1e-6);
+    assert_eq!(details.finger_count, 1);
+}
+
+#[test]
+fn test_detailed_swipe_down_angle() {
+    let mut rec = make_recognizer(None);
+    simulate_touch(&mut rec, 500.0, 100.0, 500.0, 800.0, 0.3, 0);
+    let details = rec.recognize_gesture_detailed().unwrap();
+    assert_eq!(details.gesture, GestureType::SwipeDown);
+    assert!((details.angle_deg - 90.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_detailed_expired_tap_keeps_recorded_kinematics() {
+    let mut rec = make_recognizer(None);
+    simulate_touch(&mut rec, 500.0, 500.0, 503.0, 504.0, 0.05, 0);
+    assert_eq!(rec.recognize_gesture_detailed(), None);
+    rec.last_tap_time = Some(Instant::now() - Duration::from_secs_f64(1.0));
+
+    let details = rec.check_pending_tap_expired_detailed().unwrap();
+    assert_eq!(details.gesture, GestureType::Tap);
+    assert!((details.distance - 5.0).abs() < 1e-9);
+    assert_eq!(details.finger_count, 1);
+}
+
+// -- GestureType index tests -----------------------------
+
+#[test]
+fn test_gesture_index_round_trips() {
+    for index in 0..NUM_GESTURES {
+        let gesture = GestureType::from_index(index).unwrap();
+        assert_eq!(gesture.index(), index);
+    }
+    assert_eq!(GestureType::from_index(NUM_GESTURES), None);
+}
+
+#[test]
+fn test_gesture_index_contiguous_from_zero() {
+    assert_eq!(GestureType::SwipeLeft.index(), 0);
+    assert_eq!(GestureType::LongSwipeDown.index(), NUM_GESTURES - 1);
+    assert_eq!(NUM_GESTURES, 39);
+
+    let mut names: Vec<String> = (0..NUM_GESTURES)
+        .map(|i| GestureType::from_index(i).unwrap().to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), NUM_GESTURES);
+}
+
 // -- GestureType IntoStaticStr test -----------------------
 
 #[test]
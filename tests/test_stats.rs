@@ -0,0 +1,76 @@
+//! Tests for `bodgestr::stats` - counting, merging and on-disk round-trips.
+use tempfile::tempdir;
+
+use bodgestr::recognizer::GestureType;
+use bodgestr::stats::GestureStats;
+
+#[test]
+fn test_record_counts_by_gesture_name() {
+    let mut stats = GestureStats::default();
+    stats.record(GestureType::SwipeLeft);
+    stats.record(GestureType::SwipeLeft);
+    stats.record(GestureType::Tap);
+    assert_eq!(stats.counts["swipe_left"], 2);
+    assert_eq!(stats.counts["tap"], 1);
+}
+
+#[test]
+fn test_merge_adds_counts() {
+    let mut a = GestureStats::default();
+    a.record(GestureType::Tap);
+    let mut b = GestureStats::default();
+    b.record(GestureType::Tap);
+    b.record(GestureType::PinchIn);
+
+    a.merge(&b);
+    assert_eq!(a.counts["tap"], 2);
+    assert_eq!(a.counts["pinch_in"], 1);
+}
+
+#[test]
+fn test_load_missing_file_is_empty() {
+    let dir = tempdir().unwrap();
+    let stats = GestureStats::load(&dir.path().join("stats.toml")).unwrap();
+    assert!(stats.counts.is_empty());
+}
+
+#[test]
+fn test_load_merge_save_round_trip() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("stats.toml");
+
+    let mut first = GestureStats::default();
+    first.record(GestureType::SwipeUp);
+    first.save(&path).unwrap();
+
+    // Simulate a restart: load the persisted counts and keep accumulating.
+    let mut loaded = GestureStats::load(&path).unwrap();
+    assert_eq!(loaded, first);
+    let mut session = GestureStats::default();
+    session.record(GestureType::SwipeUp);
+    session.record(GestureType::DoubleTap);
+    loaded.merge(&session);
+    loaded.save(&path).unwrap();
+
+    let reloaded = GestureStats::load(&path).unwrap();
+    assert_eq!(reloaded.counts["swipe_up"], 2);
+    assert_eq!(reloaded.counts["double_tap"], 1);
+}
+
+#[test]
+fn test_save_leaves_no_temp_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("stats.toml");
+    GestureStats::default().save(&path).unwrap();
+    assert!(path.exists());
+    assert!(!dir.path().join("stats.toml.tmp").exists());
+}
+
+#[test]
+fn test_load_corrupt_file_errors() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("stats.toml");
+    std::fs::write(&path, "not [[ toml").unwrap();
+    let msg = GestureStats::load(&path).unwrap_err().to_string();
+    assert!(msg.contains("Failed to read stats file"));
+}
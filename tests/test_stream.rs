@@ -0,0 +1,41 @@
+//! Tests for `bodgestr::stream` (needs the `async` feature).
+#![cfg(feature = "async")]
+
+use bodgestr::config::ValidatedThresholds;
+use bodgestr::event::TouchEvent;
+use bodgestr::recognizer::{GestureRecognizer, GestureType};
+use bodgestr::stream::GestureStream;
+use futures_executor::block_on_stream;
+
+fn make_recognizer() -> GestureRecognizer {
+    let thresholds = ValidatedThresholds {
+        swipe_time_max: 0.9,
+        swipe_distance_min_pct: 0.15,
+        angle_tolerance_deg: 30.0,
+        tap_time_max: 0.2,
+        long_press_time_min: 0.8,
+        double_tap_interval: 0.3,
+        tap_distance_max: 50.0,
+        double_tap_distance_max: 50.0,
+        pinch_threshold_pct: 0.1,
+        ..ValidatedThresholds::default()
+    };
+    GestureRecognizer::new(thresholds, (0.0, 1000.0), (0.0, 1000.0))
+}
+
+#[test]
+fn test_stream_yields_swipe_from_synthetic_source() {
+    let batches = vec![
+        vec![
+            TouchEvent::TrackingId(0),
+            TouchEvent::PositionX(800.0),
+            TouchEvent::PositionY(500.0),
+            TouchEvent::SynReport,
+        ],
+        vec![TouchEvent::PositionX(100.0), TouchEvent::SynReport],
+        vec![TouchEvent::FingerUp, TouchEvent::SynReport],
+    ];
+    let stream = GestureStream::spawn(batches, make_recognizer());
+    let gestures: Vec<_> = block_on_stream(stream).map(|d| d.gesture).collect();
+    assert_eq!(gestures, vec![GestureType::SwipeLeft]);
+}
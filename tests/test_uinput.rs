@@ -0,0 +1,59 @@
+//! Tests for `bodgestr::uinput` - key-name parsing and scroll mapping for
+//! uinput bindings.
+use evdev::{Key, RelativeAxisType};
+
+use bodgestr::config::ScrollAxis;
+use bodgestr::recognizer::GestureType;
+use bodgestr::uinput::{parse_key, scroll_event};
+
+#[test]
+fn test_parse_key_full_name() {
+    assert_eq!(parse_key("KEY_LEFT"), Some(Key::KEY_LEFT));
+    assert_eq!(parse_key("KEY_LEFT").map(|k| k.code()), Some(105));
+}
+
+#[test]
+fn test_parse_key_case_insensitive_without_prefix() {
+    assert_eq!(parse_key("key_pageup"), Some(Key::KEY_PAGEUP));
+    assert_eq!(parse_key("right"), Some(Key::KEY_RIGHT));
+    assert_eq!(parse_key(" Enter "), Some(Key::KEY_ENTER));
+}
+
+#[test]
+fn test_parse_key_unknown() {
+    assert_eq!(parse_key("KEY_NOPE"), None);
+    assert_eq!(parse_key(""), None);
+}
+
+#[test]
+fn test_scroll_pinch_out_wheels_up() {
+    assert_eq!(
+        scroll_event(GestureType::PinchOut, ScrollAxis::Vertical),
+        Some((RelativeAxisType::REL_WHEEL, 1))
+    );
+    assert_eq!(
+        scroll_event(GestureType::PinchIn, ScrollAxis::Vertical),
+        Some((RelativeAxisType::REL_WHEEL, -1))
+    );
+}
+
+#[test]
+fn test_scroll_swipes_follow_the_finger() {
+    assert_eq!(
+        scroll_event(GestureType::SwipeUp, ScrollAxis::Vertical),
+        Some((RelativeAxisType::REL_WHEEL, -1))
+    );
+    assert_eq!(
+        scroll_event(GestureType::SwipeLeft, ScrollAxis::Horizontal),
+        Some((RelativeAxisType::REL_HWHEEL, 1))
+    );
+}
+
+#[test]
+fn test_scroll_ignores_directionless_gestures() {
+    assert_eq!(scroll_event(GestureType::Tap, ScrollAxis::Vertical), None);
+    assert_eq!(
+        scroll_event(GestureType::LongPress, ScrollAxis::Horizontal),
+        None
+    );
+}